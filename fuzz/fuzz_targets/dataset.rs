@@ -0,0 +1,49 @@
+//! Every dataset format loads arbitrary bytes without panics and without allocating much more
+//! than the input, first byte selects the format. Input is loaded from a reader and from a file,
+//! so memory-mapped parsers of uncompressed `bin`, `safb` and `arrow` are covered too, Pubkeys
+//! of loaded blocks are read to decode blocks of indexed datasets.
+
+#![no_main]
+
+use {
+    libfuzzer_sys::fuzz_target,
+    solana_accounts_filter_bench::{
+        dataset::{Compression, DatasetFormat, Format},
+        Dataset,
+    },
+    std::{fs, path::PathBuf, sync::OnceLock},
+};
+
+const FORMATS: &[Format] = &[
+    Format::Json,
+    Format::JsonLines,
+    Format::Bincode,
+    Format::MessagePack,
+    Format::Indexed,
+    Format::Arrow,
+];
+
+/// File of the input, one per fuzzing process.
+fn input_path() -> &'static PathBuf {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| std::env::temp_dir().join(format!("safb-fuzz-{}", std::process::id())))
+}
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((format, input)) = data.split_first() {
+        let format = FORMATS[*format as usize % FORMATS.len()];
+        if let Ok(dataset) = format.loader().load(&mut &input[..]) {
+            dataset.keys().count();
+        }
+
+        let path = input_path();
+        fs::write(path, input).expect("write input");
+        let format = DatasetFormat {
+            format,
+            compression: Compression::None,
+        };
+        if let Ok(dataset) = Dataset::load_with(path, format) {
+            dataset.keys().count();
+        }
+    }
+});
@@ -0,0 +1,40 @@
+/* C API of solana-accounts-filter-bench filters, built with `ffi` feature:
+ *   cargo rustc --release --lib --no-default-features --features ffi --crate-type cdylib
+ * Pubkeys are passed as pointers to 32 bytes. */
+#ifndef SAFB_H
+#define SAFB_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#define SAFB_FILTER_HASHSET 0
+#define SAFB_FILTER_FST 1
+#define SAFB_FILTER_GXHASH 2
+#define SAFB_FILTER_FLURRY 3
+#define SAFB_FILTER_SORTED 4
+#define SAFB_FILTER_PREFIX 5
+/* Bloom and xor filters have false positives and do not support removal,
+ * xor filter is built by `safb_filter_finish` once. */
+#define SAFB_FILTER_BLOOM 6
+#define SAFB_FILTER_XOR 7
+
+typedef struct SafbFilter SafbFilter;
+
+/* Returns NULL if kind is unknown or not enabled in the build. */
+SafbFilter *safb_filter_new(uint32_t kind, size_t capacity);
+void safb_filter_free(SafbFilter *filter);
+
+bool safb_filter_insert(SafbFilter *filter, const uint8_t *pubkey);
+bool safb_filter_remove(SafbFilter *filter, const uint8_t *pubkey);
+/* Should be called after bulk insert, immutable filters merge inserted Pubkeys here. */
+void safb_filter_finish(SafbFilter *filter);
+
+bool safb_filter_contains(const SafbFilter *filter, const uint8_t *pubkey);
+/* Lookup `count` Pubkeys stored one after another, returns number of matches. */
+size_t safb_filter_contains_many(const SafbFilter *filter, const uint8_t *pubkeys, size_t count);
+
+size_t safb_filter_len(const SafbFilter *filter);
+size_t safb_filter_memory_usage(const SafbFilter *filter);
+
+#endif
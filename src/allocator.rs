@@ -0,0 +1,105 @@
+//! Global allocator selected by `jemalloc` / `mimalloc` features, with allocation counters
+//! for `alloc-counter` feature. Binaries install it with `#[global_allocator]`.
+
+use serde::Serialize;
+
+#[cfg(all(feature = "jemalloc", feature = "mimalloc"))]
+compile_error!("features `jemalloc` and `mimalloc` are mutually exclusive");
+
+#[cfg(feature = "jemalloc")]
+pub use tikv_jemallocator::Jemalloc as Allocator;
+#[cfg(feature = "jemalloc")]
+pub const NAME: &str = "jemalloc";
+
+#[cfg(feature = "mimalloc")]
+pub use mimalloc::MiMalloc as Allocator;
+#[cfg(feature = "mimalloc")]
+pub const NAME: &str = "mimalloc";
+
+#[cfg(not(any(feature = "jemalloc", feature = "mimalloc")))]
+pub use std::alloc::System as Allocator;
+#[cfg(not(any(feature = "jemalloc", feature = "mimalloc")))]
+pub const NAME: &str = "system";
+
+#[cfg(feature = "alloc-counter")]
+pub use counter::CountingAllocator;
+
+#[cfg(feature = "alloc-counter")]
+mod counter {
+    use {
+        super::Allocator,
+        std::{
+            alloc::{GlobalAlloc, Layout},
+            sync::atomic::{AtomicU64, Ordering},
+        },
+    };
+
+    pub static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+    pub static BYTES: AtomicU64 = AtomicU64::new(0);
+
+    /// Selected allocator which counts allocations and allocated bytes.
+    pub struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            Allocator.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+            Allocator.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+            Allocator.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            Allocator.dealloc(ptr, layout)
+        }
+    }
+}
+
+/// Number of allocations and allocated bytes, available with `alloc-counter` feature
+/// when binary uses `CountingAllocator` as global allocator.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct AllocStats {
+    pub allocations: u64,
+    pub bytes: u64,
+}
+
+impl AllocStats {
+    #[cfg(feature = "alloc-counter")]
+    pub fn now() -> Option<Self> {
+        use std::sync::atomic::Ordering;
+        Some(Self {
+            allocations: counter::ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: counter::BYTES.load(Ordering::Relaxed),
+        })
+    }
+
+    #[cfg(not(feature = "alloc-counter"))]
+    pub fn now() -> Option<Self> {
+        None
+    }
+
+    pub fn since(start: Option<Self>) -> Option<Self> {
+        let (start, end) = (start?, Self::now()?);
+        Some(Self {
+            allocations: end.allocations - start.allocations,
+            bytes: end.bytes - start.bytes,
+        })
+    }
+
+    pub fn add(&mut self, other: Option<Self>) {
+        if let Some(other) = other {
+            self.allocations += other.allocations;
+            self.bytes += other.bytes;
+        }
+    }
+}
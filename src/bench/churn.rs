@@ -0,0 +1,238 @@
+use {
+    super::{
+        measure::{measure, unique_pubkeys, PubkeyRng, WRITER_STREAM},
+        BenchConfig, Blocks, ChurnStats, Measurement,
+    },
+    crate::{
+        filter::{SharedFilter, SharedStrategy},
+        Result,
+    },
+    rand::Rng,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{HashMap, VecDeque},
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc, Mutex, RwLock,
+        },
+        thread,
+        time::{Duration, SystemTime},
+    },
+};
+
+/// Set of Pubkeys with reference counters, updated by subscriptions in churn benchmarks.
+trait ChurnSet: Sync {
+    fn update(&self, add: &[Pubkey], remove: &[Pubkey]);
+
+    /// Number of Pubkeys in the set.
+    fn lookup(&self, pubkeys: &[Pubkey]) -> usize;
+}
+
+fn refcount_update(map: &mut HashMap<Pubkey, usize>, add: &[Pubkey], remove: &[Pubkey]) {
+    for pubkey in add {
+        *map.entry(*pubkey).or_default() += 1;
+    }
+    for pubkey in remove {
+        if let Some(count) = map.get_mut(pubkey) {
+            *count -= 1;
+            if *count == 0 {
+                map.remove(pubkey);
+            }
+        }
+    }
+}
+
+impl ChurnSet for RwLock<HashMap<Pubkey, usize>> {
+    fn update(&self, add: &[Pubkey], remove: &[Pubkey]) {
+        let mut map = self.write().expect("poisoned");
+        refcount_update(&mut map, add, remove);
+    }
+
+    fn lookup(&self, pubkeys: &[Pubkey]) -> usize {
+        let map = self.read().expect("poisoned");
+        pubkeys
+            .iter()
+            .filter(|pubkey| map.contains_key(pubkey))
+            .count()
+    }
+}
+
+impl ChurnSet for RwLock<Arc<HashMap<Pubkey, usize>>> {
+    fn update(&self, add: &[Pubkey], remove: &[Pubkey]) {
+        let mut map = HashMap::clone(&self.read().expect("poisoned"));
+        refcount_update(&mut map, add, remove);
+        *self.write().expect("poisoned") = Arc::new(map);
+    }
+
+    fn lookup(&self, pubkeys: &[Pubkey]) -> usize {
+        let map = Arc::clone(&self.read().expect("poisoned"));
+        pubkeys
+            .iter()
+            .filter(|pubkey| map.contains_key(pubkey))
+            .count()
+    }
+}
+
+/// `SharedFilter` with reference counters kept next to it, like `RwLock<HashMap>` key is
+/// inserted by the first subscription and removed with the last one.
+struct RefcountFilter {
+    filter: Arc<dyn SharedFilter>,
+    counts: Mutex<HashMap<Pubkey, usize>>,
+}
+
+impl ChurnSet for RefcountFilter {
+    fn update(&self, add: &[Pubkey], remove: &[Pubkey]) {
+        let mut counts = self.counts.lock().expect("poisoned");
+        let mut inserted = vec![];
+        for pubkey in add {
+            let count = counts.entry(*pubkey).or_default();
+            *count += 1;
+            if *count == 1 {
+                inserted.push(*pubkey);
+            }
+        }
+        let mut removed = vec![];
+        for pubkey in remove {
+            if let Some(count) = counts.get_mut(pubkey) {
+                *count -= 1;
+                if *count == 0 {
+                    counts.remove(pubkey);
+                    removed.push(*pubkey);
+                }
+            }
+        }
+        self.filter.update(&inserted, &removed);
+    }
+
+    fn lookup(&self, pubkeys: &[Pubkey]) -> usize {
+        self.filter.contains_many(pubkeys)
+    }
+}
+
+pub(super) fn bench_churn_rwlock(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+) -> Result<Measurement> {
+    let map: HashMap<Pubkey, usize> = prng
+        .fill_set(blocks, config)
+        .into_iter()
+        .map(|pubkey| (pubkey, 1))
+        .collect();
+    bench_churn(blocks, prng, config, RwLock::new(map))
+}
+
+pub(super) fn bench_churn_rebuild(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+) -> Result<Measurement> {
+    let map: HashMap<Pubkey, usize> = prng
+        .fill_set(blocks, config)
+        .into_iter()
+        .map(|pubkey| (pubkey, 1))
+        .collect();
+    bench_churn(blocks, prng, config, RwLock::new(Arc::new(map)))
+}
+
+/// `HashSet` shared with `strategy`, see `SharedStrategy`, with reference counters as in
+/// `bench_churn_rwlock`.
+pub(super) fn bench_churn_shared(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    strategy: SharedStrategy,
+) -> Result<Measurement> {
+    let set = prng.fill_set(blocks, config);
+    let counts = set.iter().map(|pubkey| (*pubkey, 1)).collect();
+    let filter = RefcountFilter {
+        filter: strategy.shared(set),
+        counts: Mutex::new(counts),
+    };
+    bench_churn(blocks, prng, config, filter)
+}
+
+/// Filter blocks while writer thread adds `churn_rate` subscriptions per second,
+/// every subscription is removed after one second. With `write_ratio` writer does not sleep
+/// and keeps key updates at that fraction of all operations instead, `churn_rate` only limits
+/// number of active subscriptions.
+fn bench_churn<S: ChurnSet>(
+    blocks: &Blocks,
+    prng: PubkeyRng,
+    config: &BenchConfig,
+    set: S,
+) -> Result<Measurement> {
+    let unique = unique_pubkeys(blocks);
+    let mut writer_prng = prng.stream(WRITER_STREAM);
+    let interval = Duration::from_secs_f64(1.0 / config.churn_rate);
+    let lifetime = config.churn_rate.ceil() as usize;
+    let stop = AtomicBool::new(false);
+    let lookups = AtomicUsize::new(0);
+
+    let (measurement, churn) = thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            let mut active = VecDeque::new();
+            let mut churn = ChurnStats {
+                rate: config.churn_rate,
+                keys: config.churn_keys,
+                write_ratio: config.write_ratio,
+                added: 0,
+                removed: 0,
+                writes: 0,
+                update_ns: 0,
+            };
+            let mut update_time = Duration::ZERO;
+            while !stop.load(Ordering::Relaxed) {
+                if let Some(ratio) = config.write_ratio {
+                    let budget = lookups.load(Ordering::Relaxed) as f64 * ratio / (1.0 - ratio);
+                    if churn.writes as f64 >= budget {
+                        thread::yield_now();
+                        continue;
+                    }
+                }
+
+                let ts = SystemTime::now();
+                let subscription = (0..config.churn_keys)
+                    .map(|_| {
+                        if !unique.is_empty() && writer_prng.rng.gen_bool(config.hit_rate) {
+                            unique[writer_prng.rng.gen_range(0..unique.len())]
+                        } else {
+                            writer_prng.next()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let removed = if active.len() >= lifetime {
+                    active.pop_front()
+                } else {
+                    None
+                };
+                set.update(&subscription, removed.as_deref().unwrap_or_default());
+                active.push_back(subscription);
+                churn.added += 1;
+                churn.removed += removed.is_some() as usize;
+                churn.writes += config.churn_keys + removed.map_or(0, |keys| keys.len());
+
+                let elapsed = ts.elapsed().unwrap_or_default();
+                update_time += elapsed;
+                if config.write_ratio.is_none() {
+                    if let Some(sleep) = interval.checked_sub(elapsed) {
+                        thread::sleep(sleep);
+                    }
+                }
+            }
+            churn.update_ns = update_time.as_nanos() as u64;
+            churn
+        });
+
+        let measurement = measure(blocks, &prng, config, |pubkeys| {
+            lookups.fetch_add(pubkeys.len(), Ordering::Relaxed);
+            set.lookup(pubkeys)
+        });
+        stop.store(true, Ordering::Relaxed);
+        (measurement, writer.join().expect("writer thread"))
+    });
+
+    let mut measurement = measurement?;
+    measurement.churn = Some(churn);
+    Ok(measurement)
+}
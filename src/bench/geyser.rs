@@ -0,0 +1,117 @@
+use {
+    super::{
+        measure::{measure, PubkeyRng},
+        BenchConfig, Blocks, Measurement,
+    },
+    crate::{
+        geyser::{GeyserAccount, GeyserFilter},
+        Result,
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::collections::{HashMap, HashSet},
+};
+
+/// Subscribers with owner or data filters, or without account list (match any account, as in
+/// Yellowstone), are checked with `GeyserFilter` in all geyser benchmarks, so they have the same
+/// matches.
+fn other_subscribers<'a>(config: &BenchConfig<'a>) -> impl Fn(&Pubkey) -> usize + 'a {
+    let filter = GeyserFilter::with_subscribers(config.geyser.expect("geyser config"), |filter| {
+        !filter.is_account_list()
+    });
+    let dataset = config.dataset;
+    move |pubkey| {
+        filter.matches(&GeyserAccount {
+            pubkey,
+            owner: dataset.owner(pubkey),
+            data: None,
+        })
+    }
+}
+
+pub(super) fn bench_geyser_per_subscriber(
+    blocks: &Blocks,
+    prng: PubkeyRng,
+    config: &BenchConfig,
+) -> Result<Measurement> {
+    let geyser = config.geyser.expect("geyser config");
+    let other = other_subscribers(config);
+    let filters = geyser
+        .accounts
+        .values()
+        .filter(|filter| filter.is_account_list())
+        .map(|filter| {
+            let account = filter.account.iter().cloned().collect::<HashSet<_>>();
+            let exclude = filter
+                .account_exclude
+                .iter()
+                .cloned()
+                .collect::<HashSet<_>>();
+            (account, exclude)
+        })
+        .collect::<Vec<_>>();
+
+    measure(blocks, &prng, config, |pubkeys| {
+        let mut success = 0;
+        for pubkey in pubkeys.iter() {
+            for (account, exclude) in filters.iter() {
+                if account.contains(pubkey) && !exclude.contains(pubkey) {
+                    success += 1;
+                }
+            }
+            success += other(pubkey);
+        }
+        success
+    })
+}
+
+pub(super) fn bench_geyser_merged(
+    blocks: &Blocks,
+    prng: PubkeyRng,
+    config: &BenchConfig,
+) -> Result<Measurement> {
+    let geyser = config.geyser.expect("geyser config");
+    let other = other_subscribers(config);
+    let mut map = HashMap::<Pubkey, Vec<usize>>::new();
+    for (index, filter) in geyser.accounts.values().enumerate() {
+        if !filter.is_account_list() {
+            continue;
+        }
+        let exclude = filter.account_exclude.iter().collect::<HashSet<_>>();
+        for pubkey in filter.account.iter() {
+            let indexes = map.entry(*pubkey).or_default();
+            // same Pubkey can be listed twice, subscriber still matches it once
+            if !exclude.contains(pubkey) && indexes.last() != Some(&index) {
+                indexes.push(index);
+            }
+        }
+    }
+
+    measure(blocks, &prng, config, |pubkeys| {
+        pubkeys
+            .iter()
+            .map(|pubkey| map.get(pubkey).map_or(0, Vec::len) + other(pubkey))
+            .sum()
+    })
+}
+
+pub(super) fn bench_geyser_composite(
+    blocks: &Blocks,
+    prng: PubkeyRng,
+    config: &BenchConfig,
+) -> Result<Measurement> {
+    let filter = GeyserFilter::new(config.geyser.expect("geyser config"));
+    let dataset = config.dataset;
+
+    measure(blocks, &prng, config, |pubkeys| {
+        pubkeys
+            .iter()
+            .map(|pubkey| {
+                filter.matches(&GeyserAccount {
+                    pubkey,
+                    owner: dataset.owner(pubkey),
+                    data: None,
+                })
+            })
+            .sum()
+    })
+}
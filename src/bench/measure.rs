@@ -0,0 +1,297 @@
+use {
+    super::{BenchConfig, BlockLatency, Blocks, Measurement, TimeBucket},
+    crate::{allocator::AllocStats, AccountsFilter, Result},
+    rand::{
+        seq::{index::sample, SliceRandom},
+        RngCore, SeedableRng,
+    },
+    rand_chacha::ChaCha8Rng,
+    rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{BTreeSet, HashSet},
+        sync::OnceLock,
+        time::{Duration, Instant},
+    },
+};
+
+/// Stream of the churn writer.
+pub(crate) const WRITER_STREAM: u64 = 1;
+/// Keys of block `index` are shuffled with stream `BLOCK_STREAMS + index`.
+const BLOCK_STREAMS: u64 = 1 << 32;
+
+/// Random Pubkeys of one ChaCha stream of the seed, the set is filled from stream `0`. Other
+/// consumers get own streams, derived from the seed and not from the work done before or the
+/// worker running them, so everything random is the same for any number of threads.
+pub(crate) struct PubkeyRng {
+    pub(crate) rng: ChaCha8Rng,
+}
+
+impl PubkeyRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Independent stream `stream` of the same seed, from its start.
+    pub(crate) fn stream(&self, stream: u64) -> Self {
+        let mut rng = ChaCha8Rng::from_seed(self.rng.get_seed());
+        rng.set_stream(stream);
+        Self { rng }
+    }
+
+    pub(crate) fn next(&mut self) -> Pubkey {
+        let mut bytes = [0u8; 32];
+        self.rng.fill_bytes(&mut bytes);
+        Pubkey::new_from_array(bytes)
+    }
+
+    pub(crate) fn fill_set(&mut self, blocks: &Blocks, config: &BenchConfig) -> HashSet<Pubkey> {
+        self.fill(blocks, config, HashSet::with_capacity(config.set_size))
+    }
+
+    /// Fill filter with Pubkeys from the set file or with random and data Pubkeys by hit rate.
+    pub(crate) fn fill<F: AccountsFilter>(
+        &mut self,
+        blocks: &Blocks,
+        config: &BenchConfig,
+        mut filter: F,
+    ) -> F {
+        let _span = tracing::info_span!("fill", set_size = config.set_size).entered();
+        if let Some(pubkeys) = config.set_pubkeys {
+            for pubkey in pubkeys {
+                filter.insert(*pubkey);
+            }
+            return filter;
+        }
+
+        let pubkeys = unique_pubkeys(blocks);
+
+        let hits = ((pubkeys.len() as f64 * config.hit_rate) as usize).min(config.set_size);
+        for index in sample(&mut self.rng, pubkeys.len(), hits) {
+            filter.insert(pubkeys[index]);
+        }
+        while filter.len() < config.set_size {
+            filter.insert(self.next());
+        }
+        filter
+    }
+}
+
+/// Sorted unique Pubkeys of all blocks.
+pub(crate) fn unique_pubkeys(blocks: &Blocks) -> Vec<Pubkey> {
+    let mut pubkeys = blocks
+        .values()
+        .flat_map(|block| block.pubkeys.iter().cloned())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    pubkeys.sort_unstable();
+    pubkeys
+}
+
+pub(crate) fn fill_filter<F: AccountsFilter>(
+    blocks: &Blocks,
+    prng: &mut PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    filter: F,
+) -> Result<F> {
+    let ts = Instant::now();
+    let mut filter = prng.fill(blocks, config, filter);
+    filter.finish();
+    let elapsed = ts.elapsed();
+    tracing::info!(filter = name, len = filter.len(), ?elapsed, "filled");
+    Ok(filter)
+}
+
+/// Run lookups over all blocks until `min_work` (or `iters`) is reached, `lookup` returns number
+/// of matches.
+pub(crate) fn measure(
+    blocks: &Blocks,
+    prng: &PubkeyRng,
+    config: &BenchConfig,
+    mut lookup: impl FnMut(&[Pubkey]) -> usize,
+) -> Result<Measurement> {
+    try_measure(blocks, prng, config, |_index, pubkeys| Ok(lookup(pubkeys)))
+}
+
+/// Same as `measure`, `lookup` also receives index of the block and can fail.
+pub(crate) fn try_measure(
+    blocks: &Blocks,
+    prng: &PubkeyRng,
+    config: &BenchConfig,
+    mut lookup: impl FnMut(usize, &[Pubkey]) -> Result<usize>,
+) -> Result<Measurement> {
+    let slots = blocks.keys().cloned().collect::<Vec<_>>();
+    let (mut buckets, block_buckets) = time_buckets(blocks, config);
+    let timed = config.record_latency || config.bucket_interval.is_some();
+    let mut latencies = vec![];
+    let mut iter = 0;
+    let mut buffer = vec![];
+    let overhead = timed.then(timer_overhead).unwrap_or_default();
+    let mut measurement = measure_blocks(blocks, prng, config, |blocks| {
+        iter += 1;
+        let mut total_ops = 0;
+        let mut success = 0;
+        for (index, pubkeys) in blocks.iter().enumerate() {
+            let ts = timed.then(Instant::now);
+            let pubkeys = config.pubkeys(pubkeys, &mut buffer);
+            let matches = lookup(index, pubkeys)?;
+            total_ops += pubkeys.len();
+            success += matches;
+            if let Some(ts) = ts {
+                let latency_ns = ts.elapsed().saturating_sub(overhead).as_nanos() as u64;
+                if config.record_latency {
+                    latencies.push(BlockLatency {
+                        iter,
+                        slot: slots[index],
+                        keys: pubkeys.len(),
+                        latency_ns,
+                    });
+                }
+                if let Some(bucket) = block_buckets.get(index) {
+                    let bucket = &mut buckets[*bucket];
+                    bucket.total_ops += pubkeys.len();
+                    bucket.success += matches;
+                    bucket.elapsed_ns += latency_ns;
+                }
+            }
+        }
+        Ok((total_ops, success))
+    })?;
+    measurement.latencies = latencies;
+    measurement.buckets = buckets;
+    Ok(measurement)
+}
+
+/// Cost of reading `Instant` and taking elapsed time of it, subtracted from time of every block,
+/// which is close to the cost for small blocks. Calibrated once as median of many reads.
+pub(crate) fn timer_overhead() -> Duration {
+    static OVERHEAD: OnceLock<Duration> = OnceLock::new();
+    *OVERHEAD.get_or_init(|| {
+        let mut samples = (0..1001)
+            .map(|_| Instant::now().elapsed())
+            .collect::<Vec<_>>();
+        samples.sort_unstable();
+        samples[samples.len() / 2]
+    })
+}
+
+/// Buckets of `bucket_interval` by block time and index of the bucket of every block, empty if
+/// not requested.
+pub(crate) fn time_buckets(blocks: &Blocks, config: &BenchConfig) -> (Vec<TimeBucket>, Vec<usize>) {
+    let Some(interval) = config.bucket_interval else {
+        return (vec![], vec![]);
+    };
+    let start_times = blocks
+        .values()
+        .map(|block| block.block_time - block.block_time.rem_euclid(interval.max(1)))
+        .collect::<Vec<_>>();
+    // block time is not monotonic across slots, so buckets are sorted by start time
+    let mut buckets = start_times
+        .iter()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|start_time| TimeBucket {
+            start_time: *start_time,
+            blocks: 0,
+            total_ops: 0,
+            success: 0,
+            elapsed_ns: 0,
+        })
+        .collect::<Vec<_>>();
+    let block_buckets = start_times
+        .iter()
+        .map(|start_time| {
+            let index = buckets
+                .binary_search_by_key(start_time, |bucket| bucket.start_time)
+                .expect("bucket of every block");
+            buckets[index].blocks += 1;
+            index
+        })
+        .collect();
+    (buckets, block_buckets)
+}
+
+/// Run lookups until `min_work` (or `iters`) is reached, `lookup` receives Pubkeys of all blocks
+/// at once and returns number of lookups and matches or fails the benchmark.
+pub(crate) fn measure_blocks(
+    blocks: &Blocks,
+    prng: &PubkeyRng,
+    config: &BenchConfig,
+    mut lookup: impl FnMut(&[&[Pubkey]]) -> Result<(usize, usize)>,
+) -> Result<Measurement> {
+    let span = tracing::info_span!(
+        "measure",
+        iters = tracing::field::Empty,
+        total_ops = tracing::field::Empty
+    )
+    .entered();
+    let mut shuffled = if config.shuffle_keys {
+        blocks
+            .values()
+            .enumerate()
+            .map(|(index, block)| {
+                let prng = prng.stream(BLOCK_STREAMS + index as u64);
+                (block.pubkeys.to_vec(), prng)
+            })
+            .collect::<Vec<_>>()
+    } else {
+        vec![]
+    };
+
+    let mut iters = 0;
+    let mut total_ops = 0;
+    let mut success = 0;
+    let mut elapsed = Duration::ZERO;
+    let mut iters_elapsed = vec![];
+    let mut allocations = AllocStats::now().map(|_| AllocStats::default());
+    while !config.is_finished(iters, elapsed) {
+        iters += 1;
+        let pubkeys = if config.shuffle_keys {
+            shuffled
+                .par_iter_mut()
+                .with_min_len(config.rayon_min_len)
+                .for_each(|(pubkeys, prng)| pubkeys.shuffle(&mut prng.rng));
+            shuffled
+                .iter()
+                .map(|(pubkeys, _prng)| pubkeys.as_slice())
+                .collect::<Vec<_>>()
+        } else {
+            blocks
+                .values()
+                .map(|block| block.pubkeys.as_slice())
+                .collect::<Vec<_>>()
+        };
+
+        let ts = Instant::now();
+        let allocations_start = AllocStats::now();
+        let (ops, matches) = lookup(&pubkeys)?;
+        let iter_elapsed = ts.elapsed();
+        elapsed += iter_elapsed;
+        iters_elapsed.push(iter_elapsed);
+        if let Some(allocations) = allocations.as_mut() {
+            allocations.add(AllocStats::since(allocations_start));
+        }
+        total_ops += ops;
+        success += matches;
+        config.report_iter(iters, ops, iter_elapsed, elapsed);
+    }
+    span.record("iters", iters).record("total_ops", total_ops);
+
+    Ok(Measurement {
+        total_ops,
+        iters,
+        elapsed,
+        iters_elapsed,
+        latencies: vec![],
+        buckets: vec![],
+        filter_memory: None,
+        success,
+        allocations,
+        pipeline: None,
+        churn: None,
+    })
+}
@@ -0,0 +1,388 @@
+//! Benchmarks of accounts filters over the dataset, configured with `BenchConfig::builder`:
+//!
+//! ```no_run
+//! use solana_accounts_filter_bench::{bench, prelude::*};
+//!
+//! let dataset = Dataset::load("data.json".as_ref())?;
+//! let config = BenchConfig::builder(&dataset).set_size(100_000).hit_rate(0.01).build()?;
+//! let measurement = config.run(bench::find("hashset").expect("registered"))?;
+//! println!("iters: {}, elapsed: {:?}", measurement.iters, measurement.elapsed);
+//! # Ok::<_, solana_accounts_filter_bench::Error>(())
+//! ```
+
+use {
+    self::measure::PubkeyRng,
+    crate::{dataset::Block, geyser::GeyserConfig, AccountsFilter, Dataset, Error, Result},
+    rayon::ThreadPoolBuilder,
+    serde::Serialize,
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::Pubkey,
+    },
+    std::{collections::BTreeMap, time::Duration},
+};
+
+mod churn;
+mod geyser;
+mod measure;
+mod registry;
+mod strategy;
+
+pub use {
+    crate::allocator::AllocStats,
+    registry::{find, registry, Bench},
+};
+
+type Blocks = BTreeMap<Slot, Block>;
+
+/// Parameters of one benchmark run.
+#[derive(Clone, Copy)]
+pub struct BenchConfig<'a> {
+    pub dataset: &'a Dataset,
+    pub set_size: usize,
+    pub hit_rate: f64,
+    pub set_pubkeys: Option<&'a [Pubkey]>,
+    pub geyser: Option<&'a GeyserConfig>,
+    pub dedupe_block: bool,
+    pub shuffle_keys: bool,
+    pub channel_capacity: usize,
+    pub rayon_min_len: usize,
+    pub churn_rate: f64,
+    pub churn_keys: usize,
+    pub write_ratio: Option<f64>,
+    pub record_latency: bool,
+    /// Seconds of block time in one bucket of `Measurement::buckets`, not recorded if not set.
+    pub bucket_interval: Option<UnixTimestamp>,
+    pub min_work: Duration,
+    /// Fixed number of iterations instead of `min_work`.
+    pub iters: Option<u32>,
+    /// Number of threads for parallel benchmarks, 0 for number of CPUs.
+    pub threads: usize,
+    pub seed: u64,
+    /// Called after every iteration, for live progress of long runs.
+    pub on_iter: Option<&'a (dyn Fn(IterProgress) + Sync)>,
+}
+
+impl<'a> BenchConfig<'a> {
+    /// Builder with defaults of `safb bench`.
+    pub fn builder(dataset: &'a Dataset) -> BenchConfigBuilder<'a> {
+        BenchConfigBuilder {
+            config: Self {
+                dataset,
+                set_size: 1_000_000,
+                hit_rate: 0.0,
+                set_pubkeys: None,
+                geyser: None,
+                dedupe_block: false,
+                shuffle_keys: false,
+                channel_capacity: 64,
+                rayon_min_len: 1,
+                churn_rate: 100.0,
+                churn_keys: 10,
+                write_ratio: None,
+                record_latency: false,
+                bucket_interval: None,
+                min_work: Duration::from_secs(30),
+                iters: None,
+                threads: 0,
+                seed: 42,
+                on_iter: None,
+            },
+        }
+    }
+
+    /// Run benchmark in thread pool with `threads` threads.
+    pub fn run(&self, bench: &Bench) -> Result<Measurement> {
+        let missing = bench.missing_features();
+        if !missing.is_empty() {
+            return Err(Error::BenchUnavailable {
+                name: bench.name,
+                features: missing,
+            });
+        }
+        if bench.has(Bench::GEYSER) != self.geyser.is_some() {
+            return Err(Error::BenchConfig {
+                name: bench.name,
+                reason: if bench.has(Bench::GEYSER) {
+                    "requires geyser config"
+                } else {
+                    "can not be used with geyser config"
+                }
+                .to_owned(),
+            });
+        }
+        if bench.has(Bench::PIPELINE) && self.shuffle_keys {
+            return Err(Error::BenchConfig {
+                name: bench.name,
+                reason: "can not be used with shuffle keys".to_owned(),
+            });
+        }
+        let span = tracing::info_span!(
+            "bench",
+            name = bench.name,
+            set_size = self.set_size,
+            threads = self.num_threads(),
+            hit_rate = self.hit_rate
+        );
+        let pool = ThreadPoolBuilder::new().num_threads(self.threads).build()?;
+        // lazily decoded blocks are decoded before measurements, once for all benchmarks
+        self.dataset.decode();
+        // span is entered in the pool, so phases of the benchmark are its children
+        pool.install(|| {
+            span.in_scope(|| (bench.run)(&self.dataset.blocks, PubkeyRng::new(self.seed), self))
+        })
+    }
+
+    /// Fill `filter` as benchmarks do: with `set_pubkeys` or with `set_size` random and
+    /// dataset Pubkeys by `hit_rate`.
+    pub fn fill<F: AccountsFilter>(&self, filter: F) -> F {
+        let mut filter = PubkeyRng::new(self.seed).fill(&self.dataset.blocks, self, filter);
+        filter.finish();
+        filter
+    }
+
+    /// Number of threads used by `run`.
+    pub fn num_threads(&self) -> usize {
+        match self.threads {
+            0 => rayon::current_num_threads(),
+            threads => threads,
+        }
+    }
+
+    /// Benchmark ends after `iters` iterations if set, otherwise once `elapsed` reaches
+    /// `min_work`.
+    fn is_finished(&self, iters: u32, elapsed: Duration) -> bool {
+        match self.iters {
+            Some(max_iters) => iters >= max_iters,
+            None => elapsed >= self.min_work,
+        }
+    }
+
+    fn report_iter(&self, iters: u32, ops: usize, iter_elapsed: Duration, elapsed: Duration) {
+        if let Some(on_iter) = self.on_iter {
+            on_iter(IterProgress {
+                iters,
+                ops,
+                iter_elapsed,
+                elapsed,
+            });
+        }
+    }
+
+    /// Pubkeys of the block for lookups, sorted and deduped with `buffer` if requested.
+    fn pubkeys<'b>(&self, pubkeys: &'b [Pubkey], buffer: &'b mut Vec<Pubkey>) -> &'b [Pubkey] {
+        if self.dedupe_block {
+            buffer.clear();
+            buffer.extend_from_slice(pubkeys);
+            buffer.sort_unstable();
+            buffer.dedup();
+            buffer
+        } else {
+            pubkeys
+        }
+    }
+}
+
+/// Builder of `BenchConfig`, see `BenchConfig::builder`.
+pub struct BenchConfigBuilder<'a> {
+    config: BenchConfig<'a>,
+}
+
+impl<'a> BenchConfigBuilder<'a> {
+    /// Number of Pubkeys in the filter set.
+    pub fn set_size(mut self, set_size: usize) -> Self {
+        self.config.set_size = set_size;
+        self
+    }
+
+    /// Fraction of unique Pubkeys from the dataset added to the filter set, rest is random,
+    /// in `[0, 1]`.
+    pub fn hit_rate(mut self, hit_rate: f64) -> Self {
+        self.config.hit_rate = hit_rate;
+        self
+    }
+
+    /// Pubkeys of the filter set, replaces set size and hit rate.
+    pub fn set_pubkeys(mut self, set_pubkeys: &'a [Pubkey]) -> Self {
+        self.config.set_pubkeys = Some(set_pubkeys);
+        self
+    }
+
+    /// Geyser config for `geyser-*` benchmarks.
+    pub fn geyser(mut self, geyser: &'a GeyserConfig) -> Self {
+        self.config.geyser = Some(geyser);
+        self
+    }
+
+    pub fn dedupe_block(mut self, dedupe_block: bool) -> Self {
+        self.config.dedupe_block = dedupe_block;
+        self
+    }
+
+    pub fn shuffle_keys(mut self, shuffle_keys: bool) -> Self {
+        self.config.shuffle_keys = shuffle_keys;
+        self
+    }
+
+    pub fn channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.config.channel_capacity = channel_capacity;
+        self
+    }
+
+    pub fn rayon_min_len(mut self, rayon_min_len: usize) -> Self {
+        self.config.rayon_min_len = rayon_min_len;
+        self
+    }
+
+    /// Subscriptions per second (positive) and Pubkeys per subscription in churn benchmarks.
+    pub fn churn(mut self, rate: f64, keys: usize) -> Self {
+        self.config.churn_rate = rate;
+        self.config.churn_keys = keys;
+        self
+    }
+
+    pub fn write_ratio(mut self, write_ratio: Option<f64>) -> Self {
+        self.config.write_ratio = write_ratio;
+        self
+    }
+
+    pub fn record_latency(mut self, record_latency: bool) -> Self {
+        self.config.record_latency = record_latency;
+        self
+    }
+
+    /// Seconds of block time in one bucket of lookups, per block time of lookups is measured as
+    /// with `record_latency`.
+    pub fn bucket_interval(mut self, bucket_interval: Option<UnixTimestamp>) -> Self {
+        self.config.bucket_interval = bucket_interval;
+        self
+    }
+
+    /// Minimum duration of the benchmark.
+    pub fn duration(mut self, min_work: Duration) -> Self {
+        self.config.min_work = min_work;
+        self
+    }
+
+    /// Fixed number of iterations, replaces minimum duration.
+    pub fn iters(mut self, iters: Option<u32>) -> Self {
+        self.config.iters = iters;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    /// Progress callback, called from the benchmark thread after every iteration.
+    pub fn on_iter(mut self, on_iter: &'a (dyn Fn(IterProgress) + Sync)) -> Self {
+        self.config.on_iter = Some(on_iter);
+        self
+    }
+
+    /// Config with validated parameters.
+    pub fn build(self) -> Result<BenchConfig<'a>> {
+        if !(0.0..=1.0).contains(&self.config.hit_rate) {
+            return Err(Error::InvalidBenchConfig(format!(
+                "hit rate should be in range [0, 1]: {}",
+                self.config.hit_rate
+            )));
+        }
+        if !(self.config.churn_rate.is_finite() && self.config.churn_rate > 0.0) {
+            return Err(Error::InvalidBenchConfig(format!(
+                "churn rate should be positive: {}",
+                self.config.churn_rate
+            )));
+        }
+        if self.config.iters == Some(0) {
+            return Err(Error::InvalidBenchConfig(
+                "iters should be at least 1".to_owned(),
+            ));
+        }
+        Ok(self.config)
+    }
+}
+
+/// Result of one benchmark run.
+pub struct Measurement {
+    pub total_ops: usize,
+    pub iters: u32,
+    pub elapsed: Duration,
+    /// Time of every iteration.
+    pub iters_elapsed: Vec<Duration>,
+    /// Lookup time of every block, if requested and supported by benchmark.
+    pub latencies: Vec<BlockLatency>,
+    /// Lookups by block time, if `bucket_interval` is set and supported by benchmark.
+    pub buckets: Vec<TimeBucket>,
+    /// Memory usage reported by the filter.
+    pub filter_memory: Option<usize>,
+    pub success: usize,
+    pub allocations: Option<AllocStats>,
+    pub pipeline: Option<PipelineStats>,
+    pub churn: Option<ChurnStats>,
+}
+
+/// Progress of the running benchmark passed to `BenchConfig::on_iter`.
+#[derive(Debug, Clone, Copy)]
+pub struct IterProgress {
+    /// Number of finished iterations.
+    pub iters: u32,
+    /// Lookups of the last iteration.
+    pub ops: usize,
+    pub iter_elapsed: Duration,
+    /// Measured time so far, benchmark ends once it reaches `min_work` (unless `iters` is set).
+    pub elapsed: Duration,
+}
+
+/// Backpressure of the producer in pipeline benchmarks.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PipelineStats {
+    pub consumers: usize,
+    pub channel_capacity: usize,
+    /// Number of blocks which were not sent because channel was full.
+    pub blocked: usize,
+    /// Time producer spent waiting on the full channel.
+    pub blocked_ns: u64,
+}
+
+/// Subscription updates applied while filtering in churn benchmarks.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ChurnStats {
+    /// Requested subscriptions per second.
+    pub rate: f64,
+    pub keys: usize,
+    /// Requested fraction of key inserts/removals in all operations.
+    pub write_ratio: Option<f64>,
+    pub added: usize,
+    pub removed: usize,
+    /// Number of key inserts and removals.
+    pub writes: usize,
+    /// Time writer spent on updates, including waiting for the lock.
+    pub update_ns: u64,
+}
+
+/// Lookups of blocks with block time in `[start_time, start_time + bucket_interval)`, for all
+/// iterations.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TimeBucket {
+    pub start_time: UnixTimestamp,
+    pub blocks: usize,
+    pub total_ops: usize,
+    pub success: usize,
+    pub elapsed_ns: u64,
+}
+
+/// Lookup time of one block in one iteration.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BlockLatency {
+    pub iter: u32,
+    pub slot: Slot,
+    pub keys: usize,
+    pub latency_ns: u64,
+}
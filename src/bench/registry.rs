@@ -0,0 +1,302 @@
+use {
+    super::{
+        churn::{bench_churn_rebuild, bench_churn_rwlock, bench_churn_shared},
+        geyser::{bench_geyser_composite, bench_geyser_merged, bench_geyser_per_subscriber},
+        measure::PubkeyRng,
+        strategy::{bench_pipeline, bench_rayon, bench_rayon_blocks, bench_single, bench_tokio},
+        BenchConfig, Blocks, Measurement,
+    },
+    crate::{
+        filter::SharedStrategy,
+        keyset::{BloomFilter, PrefixSet, SortedKeys, XorFilter},
+        Result,
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, sync::OnceLock},
+};
+
+#[cfg(feature = "fst")]
+use crate::filter::FstFilter;
+#[cfg(feature = "gxhash")]
+use crate::filter::GxHashSet;
+
+/// Benchmark with lookup strategy and filter, registered in `register_benches`.
+pub struct Bench {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub features: &'static [&'static str],
+    /// Capability flags, see `Bench::GEYSER`, `Bench::RAYON`, `Bench::MUTATION` and
+    /// `Bench::PIPELINE`.
+    pub caps: u8,
+    /// Documented false positive rate of the filter, `0.0` for exact filters.
+    pub false_positive_rate: f64,
+    pub(crate) run: fn(&Blocks, PubkeyRng, &BenchConfig) -> Result<Measurement>,
+}
+
+impl Bench {
+    /// Benchmark requires geyser config.
+    pub const GEYSER: u8 = 1 << 0;
+    /// Benchmark uses `rayon` and `rayon_min_lens` are swept.
+    pub const RAYON: u8 = 1 << 1;
+    /// Benchmark updates the set and `write_ratios` are swept.
+    pub const MUTATION: u8 = 1 << 2;
+    /// Benchmark sends keys of blocks to consumer threads as borrowed from the dataset, so they
+    /// can not be shuffled.
+    pub const PIPELINE: u8 = 1 << 3;
+
+    pub const fn has(&self, cap: u8) -> bool {
+        self.caps & cap != 0
+    }
+
+    /// Cargo features required by the benchmark but not enabled in this build.
+    pub fn missing_features(&self) -> Vec<&'static str> {
+        self.features
+            .iter()
+            .filter(|feature| !ENABLED_FEATURES.contains(feature))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Optional filter features enabled in this build.
+const ENABLED_FEATURES: &[&str] = &[
+    #[cfg(feature = "flurry")]
+    "flurry",
+    #[cfg(feature = "fst")]
+    "fst",
+    #[cfg(feature = "gxhash")]
+    "gxhash",
+];
+
+/// Register benchmark: `register_bench!(registry, name, description, caps, run)`,
+/// optionally followed by `features = ["feature", ...]` and `false_positive_rate = rate`.
+macro_rules! register_bench {
+    ($registry:ident, $name:expr, $description:expr, $caps:expr, $run:expr $(, features = [$($feature:literal),*])? $(, false_positive_rate = $rate:expr)?) => {
+        $registry.push(Bench {
+            name: $name,
+            description: $description,
+            features: &[$($($feature),*)?],
+            caps: $caps,
+            false_positive_rate: register_bench!(@rate $($rate)?),
+            run: $run,
+        })
+    };
+    (@rate) => {
+        0.0
+    };
+    (@rate $rate:expr) => {
+        $rate
+    };
+}
+
+/// Register every lookup strategy for `AccountsFilter` created by `$new(set_size)`,
+/// benchmarks are named `$name`, `$name-rayon`, `$name-rayon-blocks`, `$name-tokio` and `$name-pipeline`.
+/// Filters behind cargo feature are registered with `feature = "name"` and listed as unavailable without it
+/// (running them returns `Error::BenchUnavailable`), filters with false positives with `false_positive_rate = rate`.
+macro_rules! register_filter {
+    ($registry:ident, $name:literal, $description:literal, $new:expr, feature = $feature:literal) => {
+        #[cfg(feature = $feature)]
+        register_filter!($registry, $name, $description, $new, features = [$feature]);
+        #[cfg(not(feature = $feature))]
+        register_filter!(@strategies $registry, $name, $description, unavailable($feature), features = [$feature]);
+    };
+    ($registry:ident, $name:literal, $description:literal, $new:expr $(, features = [$($feature:literal),*])? $(, false_positive_rate = $rate:expr)?) => {
+        register_filter!(@strategies $registry, $name, $description, run($new) $(, features = [$($feature),*])? $(, false_positive_rate = $rate)?);
+    };
+    (@strategies $registry:ident, $name:literal, $description:literal, $run:ident($arg:expr) $(, features = [$($feature:literal),*])? $(, false_positive_rate = $rate:expr)?) => {
+        register_bench!(
+            $registry,
+            $name,
+            concat!($description, ", lookups in one thread"),
+            0,
+            register_filter!(@$run bench_single, $name, $description, $arg)
+            $(, features = [$($feature),*])?
+            $(, false_positive_rate = $rate)?
+        );
+        register_bench!(
+            $registry,
+            concat!($name, "-rayon"),
+            concat!($description, ", lookups within a block with rayon par_iter"),
+            Bench::RAYON,
+            register_filter!(@$run bench_rayon, concat!($name, "-rayon"), $description, $arg)
+            $(, features = [$($feature),*])?
+            $(, false_positive_rate = $rate)?
+        );
+        register_bench!(
+            $registry,
+            concat!($name, "-rayon-blocks"),
+            concat!($description, ", blocks processed in parallel with rayon par_iter"),
+            Bench::RAYON,
+            register_filter!(@$run bench_rayon_blocks, concat!($name, "-rayon-blocks"), $description, $arg)
+            $(, features = [$($feature),*])?
+            $(, false_positive_rate = $rate)?
+        );
+        register_bench!(
+            $registry,
+            concat!($name, "-tokio"),
+            concat!($description, ", lookups within a block split into tokio tasks"),
+            0,
+            register_filter!(@$run bench_tokio, concat!($name, "-tokio"), $description, $arg)
+            $(, features = [$($feature),*])?
+            $(, false_positive_rate = $rate)?
+        );
+        register_bench!(
+            $registry,
+            concat!($name, "-pipeline"),
+            concat!(
+                $description,
+                ", producer sends blocks over bounded channel to consumer threads"
+            ),
+            Bench::PIPELINE,
+            register_filter!(@$run bench_pipeline, concat!($name, "-pipeline"), $description, $arg)
+            $(, features = [$($feature),*])?
+            $(, false_positive_rate = $rate)?
+        );
+    };
+    (@run $strategy:ident, $name:expr, $description:literal, $new:expr) => {
+        |blocks, prng, config| $strategy(blocks, prng, config, $description, $new)
+    };
+    (@unavailable $strategy:ident, $name:expr, $description:literal, $feature:expr) => {
+        |_blocks, _prng, _config| {
+            Err(crate::Error::BenchUnavailable {
+                name: $name,
+                features: vec![$feature],
+            })
+        }
+    };
+}
+
+/// All benchmarks, see `register_benches`.
+pub fn registry() -> &'static [Bench] {
+    static REGISTRY: OnceLock<Vec<Bench>> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = vec![];
+        register_benches(&mut registry);
+        registry
+    })
+}
+
+/// Benchmark by name.
+pub fn find(name: &str) -> Option<&'static Bench> {
+    registry().iter().find(|bench| bench.name == name)
+}
+
+/// New filters are added here with `register_filter!`.
+fn register_benches(registry: &mut Vec<Bench>) {
+    register_filter!(
+        registry,
+        "hashset",
+        "std HashSet",
+        HashSet::<Pubkey>::with_capacity
+    );
+    register_filter!(
+        registry,
+        "gxhash",
+        "HashSet with gxhash",
+        |capacity| GxHashSet::with_capacity_and_hasher(capacity, Default::default()),
+        feature = "gxhash"
+    );
+    register_filter!(
+        registry,
+        "flurry",
+        "flurry concurrent HashSet",
+        flurry::HashSet::<Pubkey>::with_capacity,
+        feature = "flurry"
+    );
+    register_filter!(
+        registry,
+        "fst",
+        "fst Set",
+        |_capacity| FstFilter::default(),
+        feature = "fst"
+    );
+    register_filter!(
+        registry,
+        "sorted",
+        "sorted array with binary search",
+        |_capacity| SortedKeys::default()
+    );
+    register_filter!(
+        registry,
+        "prefix",
+        "sorted array of 8-byte prefixes",
+        |_capacity| PrefixSet::default()
+    );
+    register_filter!(
+        registry,
+        "bloom",
+        "Bloom filter",
+        BloomFilter::new,
+        false_positive_rate = 0.01
+    );
+    register_filter!(
+        registry,
+        "xor",
+        "xor filter",
+        |_capacity| XorFilter::default(),
+        false_positive_rate = 1.0 / 256.0
+    );
+    register_bench!(
+        registry,
+        "churn-rwlock",
+        "HashMap with subscriptions refcount, updated in place under RwLock",
+        Bench::MUTATION,
+        bench_churn_rwlock
+    );
+    register_bench!(
+        registry,
+        "churn-rebuild",
+        "HashMap with subscriptions refcount, cloned on update and swapped as Arc",
+        Bench::MUTATION,
+        bench_churn_rebuild
+    );
+    register_bench!(
+        registry,
+        "shared-mutex",
+        "HashSet with subscriptions refcount shared as SharedFilter, updated under Mutex",
+        Bench::MUTATION,
+        |blocks, prng, config| bench_churn_shared(blocks, prng, config, SharedStrategy::Mutex)
+    );
+    register_bench!(
+        registry,
+        "shared-rwlock",
+        "HashSet with subscriptions refcount shared as SharedFilter, updated under RwLock",
+        Bench::MUTATION,
+        |blocks, prng, config| bench_churn_shared(blocks, prng, config, SharedStrategy::RwLock)
+    );
+    register_bench!(
+        registry,
+        "shared-arcswap",
+        "HashSet with subscriptions refcount shared as SharedFilter, cloned on update and swapped with ArcSwap",
+        Bench::MUTATION,
+        |blocks, prng, config| bench_churn_shared(blocks, prng, config, SharedStrategy::ArcSwap)
+    );
+    register_bench!(
+        registry,
+        "shared-left-right",
+        "HashSet with subscriptions refcount shared as SharedFilter, two copies updated with left-right",
+        Bench::MUTATION,
+        |blocks, prng, config| bench_churn_shared(blocks, prng, config, SharedStrategy::LeftRight)
+    );
+    register_bench!(
+        registry,
+        "geyser-per-subscriber",
+        "geyser config, HashSet per subscriber",
+        Bench::GEYSER,
+        bench_geyser_per_subscriber
+    );
+    register_bench!(
+        registry,
+        "geyser-merged",
+        "geyser config, one HashMap from Pubkey to subscribers",
+        Bench::GEYSER,
+        bench_geyser_merged
+    );
+    register_bench!(
+        registry,
+        "geyser-composite",
+        "geyser config, GeyserFilter built from config (Yellowstone semantics)",
+        Bench::GEYSER,
+        bench_geyser_composite
+    );
+}
@@ -0,0 +1,310 @@
+use {
+    super::{
+        measure::{
+            fill_filter, measure, measure_blocks, time_buckets, timer_overhead, try_measure,
+            PubkeyRng,
+        },
+        BenchConfig, Blocks, Measurement, PipelineStats,
+    },
+    crate::{allocator::AllocStats, AccountsFilter, Error, Result},
+    crossbeam_channel::TrySendError,
+    futures::future::try_join_all,
+    rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+pub(super) fn bench_single<F: AccountsFilter>(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    new: fn(usize) -> F,
+) -> Result<Measurement> {
+    let set = fill_filter(blocks, &mut prng, config, name, new(config.set_size))?;
+
+    let mut measurement = measure(blocks, &prng, config, |pubkeys| {
+        pubkeys.iter().filter(|pubkey| set.contains(pubkey)).count()
+    })?;
+    measurement.filter_memory = Some(set.memory_usage());
+    Ok(measurement)
+}
+
+pub(super) fn bench_rayon<F: AccountsFilter + Sync>(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    new: fn(usize) -> F,
+) -> Result<Measurement> {
+    let set = fill_filter(blocks, &mut prng, config, name, new(config.set_size))?;
+
+    let mut measurement = measure(blocks, &prng, config, |pubkeys| {
+        pubkeys
+            .par_iter()
+            .with_min_len(config.rayon_min_len)
+            .filter(|pubkey| set.contains(pubkey))
+            .count()
+    })?;
+    measurement.filter_memory = Some(set.memory_usage());
+    Ok(measurement)
+}
+
+pub(super) fn bench_rayon_blocks<F: AccountsFilter + Sync>(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    new: fn(usize) -> F,
+) -> Result<Measurement> {
+    let set = fill_filter(blocks, &mut prng, config, name, new(config.set_size))?;
+
+    let mut measurement = measure_blocks(blocks, &prng, config, |blocks| {
+        Ok(blocks
+            .par_iter()
+            .with_min_len(config.rayon_min_len)
+            .map_init(Vec::new, |buffer, pubkeys| {
+                let pubkeys = config.pubkeys(pubkeys, buffer);
+                let success = pubkeys.iter().filter(|pubkey| set.contains(pubkey)).count();
+                (pubkeys.len(), success)
+            })
+            .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1)))
+    })?;
+    measurement.filter_memory = Some(set.memory_usage());
+    Ok(measurement)
+}
+
+pub(super) fn bench_tokio<F: AccountsFilter + Send + Sync + 'static>(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    new: fn(usize) -> F,
+) -> Result<Measurement> {
+    let set = Arc::new(fill_filter(
+        blocks,
+        &mut prng,
+        config,
+        name,
+        new(config.set_size),
+    )?);
+
+    let workers = rayon::current_num_threads();
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(workers)
+        .build()?;
+
+    // tasks are `'static`, so keys of blocks are copied into `Arc` before the measurement,
+    // shuffled or deduped keys change every iteration and are copied in it
+    let shared = match config.shuffle_keys || config.dedupe_block {
+        true => vec![],
+        false => blocks
+            .values()
+            .map(|block| Arc::<[Pubkey]>::from(block.pubkeys.as_slice()))
+            .collect(),
+    };
+    let mut measurement = try_measure(blocks, &prng, config, |index, pubkeys| {
+        let pubkeys = match shared.get(index) {
+            Some(pubkeys) => Arc::clone(pubkeys),
+            None => Arc::from(pubkeys),
+        };
+        let chunk_size = pubkeys.len().div_ceil(workers).max(1);
+        let tasks = (0..pubkeys.len())
+            .step_by(chunk_size)
+            .map(|start| {
+                let pubkeys = Arc::clone(&pubkeys);
+                let set = Arc::clone(&set);
+                runtime.spawn(async move {
+                    pubkeys[start..(start + chunk_size).min(pubkeys.len())]
+                        .iter()
+                        .filter(|pubkey| set.contains(pubkey))
+                        .count()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let matches = runtime.block_on(try_join_all(tasks))?;
+        Ok(matches.into_iter().sum())
+    })?;
+    measurement.filter_memory = Some(set.memory_usage());
+    Ok(measurement)
+}
+
+pub(super) fn bench_pipeline<F: AccountsFilter + Sync>(
+    blocks: &Blocks,
+    mut prng: PubkeyRng,
+    config: &BenchConfig,
+    name: &str,
+    new: fn(usize) -> F,
+) -> Result<Measurement> {
+    let set = fill_filter(blocks, &mut prng, config, name, new(config.set_size))?;
+    let (buckets, block_buckets) = time_buckets(blocks, config);
+    let overhead = match buckets.is_empty() {
+        true => Duration::ZERO,
+        false => timer_overhead(),
+    };
+    // lookups of one iteration as done by consumers, keys of blocks can be deduped
+    let mut buffer = vec![];
+    let ops = blocks
+        .values()
+        .map(|block| config.pubkeys(&block.pubkeys, &mut buffer).len())
+        .sum::<usize>();
+
+    let consumers = rayon::current_num_threads();
+    let (tx, rx) = crossbeam_channel::bounded::<(u32, usize, &[Pubkey])>(config.channel_capacity);
+    // blocks of every iteration left to consumers, consumer of the last one marks the iteration
+    // as done, so times of iterations include lookups and not only sending of blocks
+    let remaining = Mutex::new(Vec::<usize>::new());
+    let (done_tx, done_rx) = crossbeam_channel::unbounded::<(u32, Instant)>();
+
+    let ts = Instant::now();
+    let allocations = AllocStats::now();
+    let mut iters = 0;
+    let mut total_ops = 0;
+    let mut blocked = 0;
+    let mut blocked_time = Duration::ZERO;
+    let mut clock = IterClock::new(ts, ops, config);
+    let (success, buckets) = thread::scope(|scope| {
+        let handles = (0..consumers)
+            .map(|_| {
+                let (rx, done_tx) = (rx.clone(), done_tx.clone());
+                let (set, block_buckets, remaining) = (&set, &block_buckets, &remaining);
+                let mut buckets = buckets.clone();
+                scope.spawn(move || {
+                    let mut buffer = vec![];
+                    let mut success = 0;
+                    for (iter, index, pubkeys) in rx.iter() {
+                        let ts = block_buckets.get(index).map(|_| Instant::now());
+                        let pubkeys = config.pubkeys(pubkeys, &mut buffer);
+                        let matches = pubkeys.iter().filter(|pubkey| set.contains(pubkey)).count();
+                        success += matches;
+                        if let Some(ts) = ts {
+                            let bucket = &mut buckets[block_buckets[index]];
+                            bucket.total_ops += pubkeys.len();
+                            bucket.success += matches;
+                            bucket.elapsed_ns +=
+                                ts.elapsed().saturating_sub(overhead).as_nanos() as u64;
+                        }
+                        let now = Instant::now();
+                        let mut remaining = remaining.lock().expect("poisoned");
+                        let left = &mut remaining[iter as usize - 1];
+                        *left -= 1;
+                        if *left == 0 {
+                            let _ = done_tx.send((iter, now));
+                        }
+                    }
+                    (success, buckets)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // consumers wait for the channel to be closed, so the sender is dropped before they are
+        // joined even if producer fails
+        let produced = (|| {
+            while !config.is_finished(iters, ts.elapsed()) {
+                iters += 1;
+                remaining.lock().expect("poisoned").push(blocks.len());
+                if blocks.is_empty() {
+                    clock.done(iters, Instant::now());
+                }
+                for (index, block) in blocks.values().enumerate() {
+                    let message = (iters, index, block.pubkeys.as_slice());
+                    if let Err(TrySendError::Full(message)) = tx.try_send(message) {
+                        blocked += 1;
+                        let ts = Instant::now();
+                        tx.send(message).map_err(|_| Error::PipelineDisconnected)?;
+                        blocked_time += ts.elapsed();
+                    }
+                }
+                total_ops += ops;
+                for (iter, ts) in done_rx.try_iter() {
+                    clock.done(iter, ts);
+                }
+            }
+            Ok::<_, Error>(())
+        })();
+        drop(tx);
+        drop(done_tx);
+
+        let mut success = 0;
+        let mut total_buckets = buckets.clone();
+        for handle in handles {
+            let (matches, buckets) = handle.join().expect("consumer thread");
+            success += matches;
+            for (total, bucket) in total_buckets.iter_mut().zip(buckets) {
+                total.total_ops += bucket.total_ops;
+                total.success += bucket.success;
+                total.elapsed_ns += bucket.elapsed_ns;
+            }
+        }
+        // consumers are joined, every iteration is done
+        for (iter, ts) in done_rx.try_iter() {
+            clock.done(iter, ts);
+        }
+        produced.map(|()| (success, total_buckets))
+    })?;
+
+    Ok(Measurement {
+        total_ops,
+        iters,
+        elapsed: ts.elapsed(),
+        iters_elapsed: clock.iters_elapsed,
+        latencies: vec![],
+        buckets,
+        filter_memory: Some(set.memory_usage()),
+        success,
+        allocations: AllocStats::since(allocations),
+        pipeline: Some(PipelineStats {
+            consumers,
+            channel_capacity: config.channel_capacity,
+            blocked,
+            blocked_ns: blocked_time.as_nanos() as u64,
+        }),
+        churn: None,
+    })
+}
+
+/// Times of pipeline iterations from the end of the previous one until consumers are done with
+/// the last block of the iteration, so they sum up to the time of the run. Consumers can finish
+/// iterations out of order, they are reported in order.
+struct IterClock<'a> {
+    start: Instant,
+    last: Instant,
+    next: u32,
+    done: BTreeMap<u32, Instant>,
+    iters_elapsed: Vec<Duration>,
+    ops: usize,
+    config: &'a BenchConfig<'a>,
+}
+
+impl<'a> IterClock<'a> {
+    fn new(start: Instant, ops: usize, config: &'a BenchConfig<'a>) -> Self {
+        Self {
+            start,
+            last: start,
+            next: 1,
+            done: BTreeMap::new(),
+            iters_elapsed: vec![],
+            ops,
+            config,
+        }
+    }
+
+    fn done(&mut self, iter: u32, ts: Instant) {
+        self.done.insert(iter, ts);
+        while let Some(ts) = self.done.remove(&self.next) {
+            let ts = ts.max(self.last);
+            let iter_elapsed = ts - self.last;
+            self.last = ts;
+            self.iters_elapsed.push(iter_elapsed);
+            self.config
+                .report_iter(self.next, self.ops, iter_elapsed, ts - self.start);
+            self.next += 1;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use {
+    crate::{bench, environment::Environment, GlobalArgs},
+    anyhow::Result,
+    std::{convert::Infallible, net::SocketAddr, sync::Arc},
+    tokio::sync::{mpsc, oneshot},
+    tonic::{
+        body::BoxBody,
+        codec::ProstCodec,
+        codegen::{empty_body, http, Body, BoxFuture, Context, Poll, Service, StdError},
+        server::{Grpc, NamedService},
+        transport::Server,
+        Request, Response, Status,
+    },
+};
+
+/// `safb.Agent` service, methods are called by `dispatch`.
+pub const SERVICE_NAME: &str = "safb.Agent";
+pub const RUN_PATH: &str = "/safb.Agent/Run";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RunRequest {
+    /// Suite config of `bench --config` in TOML.
+    #[prost(string, tag = "1")]
+    pub config: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct RunResponse {
+    /// Environment of the agent machine in JSON.
+    #[prost(string, tag = "1")]
+    pub environment: String,
+    /// Results of all suites in JSON, same as `bench --output`.
+    #[prost(string, tag = "2")]
+    pub results: String,
+}
+
+/// Serve benchmarks for `dispatch` over gRPC, suites are run one at a time.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Address of gRPC server.
+    #[clap(long, default_value = "0.0.0.0:10020")]
+    listen: SocketAddr,
+
+    /// Optional `x-token` required from `dispatch`.
+    #[clap(long)]
+    x_token: Option<String>,
+}
+
+/// Config received by the server, benchmarks are run by `run` outside of server tasks.
+struct Job {
+    config: String,
+    reply: oneshot::Sender<Result<String, String>>,
+}
+
+#[derive(Clone)]
+struct AgentServer {
+    jobs: mpsc::Sender<Job>,
+    x_token: Option<Arc<str>>,
+}
+
+impl AgentServer {
+    async fn run(self, request: Request<RunRequest>) -> Result<Response<RunResponse>, Status> {
+        if let Some(x_token) = &self.x_token {
+            let token = request
+                .metadata()
+                .get("x-token")
+                .and_then(|token| token.to_str().ok());
+            if token != Some(x_token) {
+                return Err(Status::unauthenticated("invalid x-token"));
+            }
+        }
+
+        let (reply, result) = oneshot::channel();
+        let job = Job {
+            config: request.into_inner().config,
+            reply,
+        };
+        self.jobs
+            .send(job)
+            .await
+            .map_err(|_| Status::unavailable("agent is stopped"))?;
+        let results = result
+            .await
+            .map_err(|_| Status::unavailable("agent is stopped"))?
+            .map_err(Status::internal)?;
+        let environment = serde_json::to_string(&Environment::collect())
+            .map_err(|error| Status::internal(error.to_string()))?;
+        Ok(Response::new(RunResponse {
+            environment,
+            results,
+        }))
+    }
+}
+
+/// `Agent.Run` handler for `Grpc::unary`.
+struct RunService(AgentServer);
+
+impl Service<Request<RunRequest>> for RunService {
+    type Response = Response<RunResponse>;
+    type Error = Status;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<RunRequest>) -> Self::Future {
+        Box::pin(self.0.clone().run(request))
+    }
+}
+
+impl<B> Service<http::Request<B>> for AgentServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if request.uri().path() != RUN_PATH {
+            return Box::pin(async {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .expect("valid response"))
+            });
+        }
+        let service = RunService(self.clone());
+        Box::pin(async move {
+            let mut grpc = Grpc::new(ProstCodec::default());
+            Ok(grpc.unary(service, request).await)
+        })
+    }
+}
+
+impl NamedService for AgentServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let (jobs, mut received) = mpsc::channel(16);
+    let server = AgentServer {
+        jobs,
+        x_token: args.x_token.as_deref().map(Into::into),
+    };
+    let server = tokio::spawn(Server::builder().add_service(server).serve(args.listen));
+    println!("Agent on {}", args.listen);
+
+    // benchmarks block the thread, so they are run here instead of server tasks
+    while let Some(job) = received.recv().await {
+        let result = bench::run_remote(&job.config, global)
+            .await
+            .map_err(|error| format!("{:#}", error));
+        if let Err(error) = &result {
+            println!("WARNING: bench failed: {}", error);
+        }
+        let _ = job.reply.send(result);
+    }
+    server.await?.map_err(Into::into)
+}
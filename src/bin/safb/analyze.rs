@@ -0,0 +1,102 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    solana_accounts_filter_bench::dataset::{AnalyzeConfig, Distribution},
+    solana_sdk::clock::UnixTimestamp,
+    std::{fs, path::PathBuf},
+};
+
+/// Print statistics of the dataset for sizing a filter.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Seconds in one bucket of unique Pubkeys timeline.
+    #[clap(long, default_value_t = 600)]
+    interval: UnixTimestamp,
+
+    /// Seconds of sliding window for working set size.
+    #[clap(long, default_value_t = 60)]
+    window: UnixTimestamp,
+
+    /// Number of hottest Pubkeys to print.
+    #[clap(long, default_value_t = 20)]
+    top: usize,
+
+    /// Optional output file for statistics in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+fn format_distribution(distribution: &Distribution, precision: usize) -> String {
+    format!(
+        "min: {:.*}, mean: {:.*}, p50: {:.*}, p90: {:.*}, p99: {:.*}, max: {:.*}",
+        precision,
+        distribution.min,
+        precision,
+        distribution.mean,
+        precision,
+        distribution.p50,
+        precision,
+        distribution.p90,
+        precision,
+        distribution.p99,
+        precision,
+        distribution.max
+    )
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    anyhow::ensure!(args.interval > 0, "interval should be positive");
+    let dataset = global.load_dataset(&args.input)?;
+    let stats = dataset.analyze(&AnalyzeConfig {
+        interval: args.interval,
+        window: args.window,
+        top: args.top,
+    });
+
+    println!(
+        "Blocks: {}, total pubkeys: {}, unique pubkeys: {}",
+        stats.blocks, stats.total_keys, stats.unique_keys
+    );
+    println!(
+        "Pubkeys per block: {}",
+        format_distribution(&stats.keys_per_block, 0)
+    );
+    println!(
+        "Overlap with previous block: {}",
+        format_distribution(&stats.overlap, 3)
+    );
+    println!(
+        "Working set in {}s window: mean {:.0}, max {}, hottest pubkeys for 90% of lookups: {}",
+        stats.working_set.window,
+        stats.working_set.mean,
+        stats.working_set.max,
+        stats.working_set.keys_90pct_lookups
+    );
+    if !global.quiet {
+        println!("Unique pubkeys every {}s:", args.interval);
+        for bucket in stats.timeline.iter() {
+            println!(
+                "  {}: blocks {}, pubkeys {}, new {}, unique {}",
+                bucket.start_time, bucket.blocks, bucket.keys, bucket.new_keys, bucket.unique_keys
+            );
+        }
+    }
+    println!("Hottest pubkeys:");
+    for key in stats.hottest.iter() {
+        println!(
+            "  {:<44} {} ({:.2}%)",
+            key.pubkey,
+            key.count,
+            key.fraction * 100.0
+        );
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_string_pretty(&stats)?)?;
+    }
+    Ok(())
+}
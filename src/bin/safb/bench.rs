@@ -0,0 +1,1454 @@
+use {
+    crate::{
+        influx::{InfluxArgs, Point},
+        metrics::{Gauges, Metrics},
+        notify::SuiteSummary,
+        parse_fraction, results, GlobalArgs,
+    },
+    anyhow::Result,
+    serde::{Deserialize, Serialize},
+    solana_accounts_filter_bench::{
+        allocator::{self, AllocStats},
+        bench::{
+            self, registry, Bench, BenchConfig, BlockLatency, ChurnStats, PipelineStats, TimeBucket,
+        },
+        dataset::{DatasetFormat, DatasetManifest, MANIFEST_SUFFIX},
+        geyser::GeyserConfig,
+    },
+    solana_sdk::{clock::UnixTimestamp, pubkey::Pubkey},
+    std::{
+        collections::{BTreeSet, HashMap},
+        fmt, fs,
+        net::SocketAddr,
+        path::{Path, PathBuf},
+        str::FromStr,
+        time::{Duration, SystemTime},
+    },
+};
+
+/// Run benchmarks over the dataset.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data, can be repeated to run benchmarks over every dataset with
+    /// comparison of inputs at the end. Directories are expanded to datasets in them.
+    #[clap(
+        short,
+        long,
+        default_value = "data.json",
+        multiple_occurrences = true,
+        parse(from_os_str),
+        conflicts_with = "config"
+    )]
+    input: Vec<PathBuf>,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42, conflicts_with = "config")]
+    seed: u64,
+
+    /// Minimum seconds for bench.
+    #[clap(short, long, default_value_t = 30, conflicts_with = "config")]
+    min_work: u64,
+
+    /// Fixed number of iterations of every benchmark instead of `--min-work`, e.g. `1` for a
+    /// smoke test of all filters.
+    #[clap(long, parse(try_from_str = parse_iters), conflicts_with = "config")]
+    iters: Option<u32>,
+
+    /// Comma separated benchmarks to run. By default all of them, see `--list`.
+    #[clap(long, use_value_delimiter = true, conflicts_with = "config")]
+    filters: Vec<String>,
+
+    /// Print available benchmarks and exit.
+    #[clap(long)]
+    list: bool,
+
+    /// Number of Pubkeys in the filter set.
+    #[clap(long, default_value_t = 1_000_000, conflicts_with = "config")]
+    set_size: usize,
+
+    /// Number of threads for parallel benchmarks, 0 for number of CPUs.
+    #[clap(long, default_value_t = 0, conflicts_with = "config")]
+    threads: usize,
+
+    /// Fraction of unique Pubkeys from the data added to the filter set, rest is random.
+    #[clap(
+        long,
+        default_value_t = 0.0,
+        parse(try_from_str = parse_fraction),
+        conflicts_with = "config"
+    )]
+    hit_rate: f64,
+
+    /// Optional file with Pubkeys for the filter set, one per line. Replaces set size and hit rate.
+    #[clap(long, parse(from_os_str), conflicts_with = "config")]
+    set_file: Option<PathBuf>,
+
+    /// Optional geyser plugin YAML (or JSON) config with named accounts filters for `geyser-*` benchmarks.
+    #[clap(long, parse(from_os_str), conflicts_with = "config")]
+    geyser_config: Option<PathBuf>,
+
+    /// Dedup Pubkeys of each block before lookups, dedup time is included into results.
+    #[clap(long, conflicts_with = "config")]
+    dedupe_block: bool,
+
+    /// Shuffle Pubkeys within each block before every iteration (not included into results).
+    #[clap(long, conflicts_with = "config")]
+    shuffle_keys: bool,
+
+    /// Channel capacity (in blocks) for pipeline benchmarks.
+    #[clap(long, default_value_t = 64, conflicts_with = "config")]
+    channel_capacity: usize,
+
+    /// Comma separated minimum number of items per rayon job (`with_min_len`), every value is benchmarked.
+    #[clap(
+        long,
+        use_value_delimiter = true,
+        default_value = "1,16,64,256",
+        conflicts_with = "config"
+    )]
+    rayon_min_lens: Vec<usize>,
+
+    /// Subscriptions added (and removed) per second in churn benchmarks.
+    #[clap(long, default_value_t = 100.0, conflicts_with = "config")]
+    churn_rate: f64,
+
+    /// Number of Pubkeys in one subscription in churn benchmarks.
+    #[clap(long, default_value_t = 10, conflicts_with = "config")]
+    churn_keys: usize,
+
+    /// Comma separated fractions of key inserts/removals in all operations of churn benchmarks,
+    /// every value is benchmarked. Updates are paced by `--churn-rate` if not set.
+    #[clap(long, use_value_delimiter = true, conflicts_with = "config")]
+    write_ratio: Vec<f64>,
+
+    /// Warn if coefficient of variation of iteration time exceeds this value.
+    #[clap(long, default_value_t = 0.1, conflicts_with = "config")]
+    max_cv: f64,
+
+    /// Optional baseline results in JSON, compared with results of the suite in `--notify-webhook`.
+    #[clap(long, parse(from_os_str), conflicts_with = "config")]
+    baseline: Option<PathBuf>,
+
+    /// Minimum relative change of mean iteration time versus baseline reported as regression.
+    #[clap(long, default_value_t = 0.05, conflicts_with = "config")]
+    regression_threshold: f64,
+
+    /// Optional output file for per block latency of lookups, CSV or JSON (by extension).
+    #[clap(long, parse(from_os_str), conflicts_with = "config")]
+    latency_output: Option<PathBuf>,
+
+    /// Seconds of block time in one bucket, time per Pubkey and matched Pubkeys are reported for
+    /// every bucket (e.g. `60` for every minute of the capture).
+    #[clap(long, conflicts_with = "config")]
+    bucket_interval: Option<UnixTimestamp>,
+
+    /// Optional output file for results, JSON by default (see `--output-format`).
+    #[clap(short, long, parse(from_os_str), conflicts_with = "config")]
+    output: Option<PathBuf>,
+
+    /// Format of results in output files of all suites: `json`, `gha-benchmark`
+    /// (`customSmallerIsBetter` of github-action-benchmark, time per Pubkey) or `bencher`
+    /// (libtest bench output of `cargo bench`, for cargo-benchcmp).
+    #[clap(long, default_value_t = OutputFormat::Json)]
+    output_format: OutputFormat,
+
+    /// Suite config in TOML, replaces benchmark options above, which can not be passed with it.
+    #[clap(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Show live dashboard of the suite (progress of runs, throughput, percentiles and memory)
+    /// instead of printed output, which is shown in its log pane and printed once it is closed.
+    #[cfg(feature = "tui")]
+    #[clap(long)]
+    tui: bool,
+
+    /// Optional address for Prometheus metrics of the last run on `GET /metrics`.
+    #[clap(long)]
+    metrics_addr: Option<SocketAddr>,
+
+    /// Optional URL, summary of every finished suite is posted to it in JSON, with regressions
+    /// versus baseline of the suite.
+    #[clap(long)]
+    notify_webhook: Option<String>,
+
+    /// Optional destination for output files of every finished suite and manifest with
+    /// checksums: `s3://bucket/prefix` or `gs://bucket/prefix`.
+    #[cfg(feature = "upload")]
+    #[clap(long)]
+    upload: Option<crate::upload::UploadUrl>,
+
+    #[clap(flatten)]
+    influx: InfluxArgs,
+
+    /// Optional SQLite database, the run (environment and config) and its results are appended.
+    #[cfg(feature = "sqlite")]
+    #[clap(long, parse(from_os_str))]
+    results_db: Option<PathBuf>,
+
+    /// Optional Postgres connection string (without TLS), the run and its results are inserted
+    /// with commit and SHA-256 of the dataset.
+    #[cfg(feature = "postgres")]
+    #[clap(long)]
+    results_postgres: Option<String>,
+
+    /// Commit of benchmarked code for `--results-postgres`, `git rev-parse HEAD` by default.
+    #[cfg(feature = "postgres")]
+    #[clap(long)]
+    commit: Option<String>,
+}
+
+/// Number of iterations, at least one.
+fn parse_iters(value: &str) -> Result<u32> {
+    let iters = value.parse()?;
+    anyhow::ensure!(iters >= 1, "should be at least 1: {}", value);
+    Ok(iters)
+}
+
+impl Args {
+    fn load_config(&self) -> Result<Config> {
+        match &self.config {
+            Some(path) => Config::load(path),
+            None => Ok(Config {
+                seed: self.seed,
+                min_work: self.min_work,
+                iters: self.iters,
+                max_cv: self.max_cv,
+                regression_threshold: self.regression_threshold,
+                suites: vec![Suite {
+                    name: None,
+                    inputs: self.input.clone(),
+                    filters: self.filters.clone(),
+                    set_sizes: vec![self.set_size],
+                    threads: vec![self.threads],
+                    hit_rates: vec![self.hit_rate],
+                    set_file: self.set_file.clone(),
+                    geyser_config: self.geyser_config.clone(),
+                    dedupe_block: self.dedupe_block,
+                    shuffle_keys: self.shuffle_keys,
+                    channel_capacity: self.channel_capacity,
+                    rayon_min_lens: self.rayon_min_lens.clone(),
+                    churn_rate: self.churn_rate,
+                    churn_keys: self.churn_keys,
+                    write_ratios: self.write_ratio.clone(),
+                    baseline: self.baseline.clone(),
+                    latency_output: self.latency_output.clone(),
+                    bucket_interval: self.bucket_interval,
+                    output: self.output.clone(),
+                }],
+            }),
+        }
+    }
+}
+
+/// Format of results written to `--output`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    Json,
+    GhaBenchmark,
+    Bencher,
+}
+
+impl OutputFormat {
+    const ALL: [Self; 3] = [Self::Json, Self::GhaBenchmark, Self::Bencher];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::GhaBenchmark => "gha-benchmark",
+            Self::Bencher => "bencher",
+        }
+    }
+
+    fn save(self, path: &Path, results: &[BenchResult]) -> Result<()> {
+        let data = match self {
+            Self::Json => serde_json::to_string_pretty(results)?,
+            Self::GhaBenchmark => serde_json::to_string_pretty(
+                &results
+                    .iter()
+                    .map(BenchResult::gha_benchmark)
+                    .collect::<Vec<_>>(),
+            )?,
+            Self::Bencher => results.iter().map(BenchResult::bencher).collect(),
+        };
+        fs::write(path, data).map_err(Into::into)
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name() == s)
+            .ok_or_else(|| format!("unknown output format: {}", s))
+    }
+}
+
+/// Entry of `customSmallerIsBetter` tool of github-action-benchmark.
+#[derive(Debug, Serialize)]
+struct GhaBenchmarkEntry {
+    name: String,
+    unit: &'static str,
+    value: f64,
+    range: String,
+    extra: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    #[serde(default = "Config::default_seed")]
+    seed: u64,
+    #[serde(default = "Config::default_min_work")]
+    min_work: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    iters: Option<u32>,
+    #[serde(default = "Config::default_max_cv")]
+    max_cv: f64,
+    #[serde(default = "Config::default_regression_threshold")]
+    regression_threshold: f64,
+    #[serde(rename = "suite")]
+    suites: Vec<Suite>,
+}
+
+impl Config {
+    const fn default_seed() -> u64 {
+        42
+    }
+
+    const fn default_min_work() -> u64 {
+        30
+    }
+
+    const fn default_max_cv() -> f64 {
+        0.1
+    }
+
+    const fn default_regression_threshold() -> f64 {
+        0.05
+    }
+
+    fn load(path: &Path) -> Result<Self> {
+        let config = fs::read_to_string(path)?;
+        toml::from_str(&config).map_err(Into::into)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Suite {
+    name: Option<String>,
+    inputs: Vec<PathBuf>,
+    #[serde(default)]
+    filters: Vec<String>,
+    #[serde(default = "Suite::default_set_sizes")]
+    set_sizes: Vec<usize>,
+    #[serde(default = "Suite::default_threads")]
+    threads: Vec<usize>,
+    #[serde(default = "Suite::default_hit_rates")]
+    hit_rates: Vec<f64>,
+    set_file: Option<PathBuf>,
+    geyser_config: Option<PathBuf>,
+    #[serde(default)]
+    dedupe_block: bool,
+    #[serde(default)]
+    shuffle_keys: bool,
+    #[serde(default = "Suite::default_channel_capacity")]
+    channel_capacity: usize,
+    #[serde(default = "Suite::default_rayon_min_lens")]
+    rayon_min_lens: Vec<usize>,
+    #[serde(default = "Suite::default_churn_rate")]
+    churn_rate: f64,
+    #[serde(default = "Suite::default_churn_keys")]
+    churn_keys: usize,
+    #[serde(default)]
+    write_ratios: Vec<f64>,
+    baseline: Option<PathBuf>,
+    latency_output: Option<PathBuf>,
+    bucket_interval: Option<UnixTimestamp>,
+    output: Option<PathBuf>,
+}
+
+impl Suite {
+    fn default_set_sizes() -> Vec<usize> {
+        vec![1_000_000]
+    }
+
+    fn default_threads() -> Vec<usize> {
+        vec![0]
+    }
+
+    fn default_hit_rates() -> Vec<f64> {
+        vec![0.0]
+    }
+
+    const fn default_channel_capacity() -> usize {
+        64
+    }
+
+    fn default_rayon_min_lens() -> Vec<usize> {
+        vec![1, 16, 64, 256]
+    }
+
+    const fn default_churn_rate() -> f64 {
+        100.0
+    }
+
+    const fn default_churn_keys() -> usize {
+        10
+    }
+
+    /// All combinations of benchmarks and their parameters.
+    fn runs(
+        &self,
+        set_pubkeys: Option<&[Pubkey]>,
+        geyser: Option<&GeyserConfig>,
+    ) -> Result<Vec<Run>> {
+        let (set_sizes, hit_rates) = match (set_pubkeys, geyser) {
+            (_, Some(geyser)) => (vec![geyser.accounts_len()], vec![None]),
+            (Some(pubkeys), None) => (vec![pubkeys.len()], vec![None]),
+            (None, None) => (
+                self.set_sizes.clone(),
+                self.hit_rates.iter().cloned().map(Some).collect(),
+            ),
+        };
+
+        anyhow::ensure!(
+            self.churn_rate.is_finite() && self.churn_rate > 0.0,
+            "churn rate should be positive: {}",
+            self.churn_rate
+        );
+        for &rate in self.hit_rates.iter() {
+            anyhow::ensure!(
+                (0.0..=1.0).contains(&rate),
+                "hit rate should be in range [0, 1]: {}",
+                rate
+            );
+        }
+        for &ratio in self.write_ratios.iter() {
+            anyhow::ensure!(
+                (0.0..1.0).contains(&ratio),
+                "write ratio should be in range [0, 1): {}",
+                ratio
+            );
+        }
+
+        let mut runs = vec![];
+        for bench in self.benches()? {
+            let rayon_min_lens = if bench.has(Bench::RAYON) {
+                self.rayon_min_lens.iter().cloned().map(Some).collect()
+            } else {
+                vec![None]
+            };
+            let write_ratios = if bench.has(Bench::MUTATION) && !self.write_ratios.is_empty() {
+                self.write_ratios.iter().cloned().map(Some).collect()
+            } else {
+                vec![None]
+            };
+            for &set_size in set_sizes.iter() {
+                for &threads in self.threads.iter() {
+                    for &hit_rate in hit_rates.iter() {
+                        for &rayon_min_len in rayon_min_lens.iter() {
+                            for &write_ratio in write_ratios.iter() {
+                                runs.push(Run {
+                                    bench,
+                                    set_size,
+                                    threads,
+                                    hit_rate,
+                                    rayon_min_len,
+                                    write_ratio,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(runs)
+    }
+
+    fn benches(&self) -> Result<Vec<&'static Bench>> {
+        let geyser = self.geyser_config.is_some();
+        if self.filters.is_empty() {
+            let (benches, unavailable): (Vec<_>, Vec<_>) = registry()
+                .iter()
+                .filter(|bench| bench.has(Bench::GEYSER) == geyser)
+                .filter(|bench| !(self.shuffle_keys && bench.has(Bench::PIPELINE)))
+                .partition(|bench| bench.missing_features().is_empty());
+            if !unavailable.is_empty() {
+                let names = unavailable
+                    .iter()
+                    .map(|bench| bench.name)
+                    .collect::<Vec<_>>();
+                println!(
+                    "Skip benchmarks unavailable in this build (see `--list`): {}",
+                    names.join(", ")
+                );
+            }
+            return Ok(benches);
+        }
+
+        self.filters
+            .iter()
+            .map(|name| {
+                let bench = bench::find(name)
+                    .ok_or_else(|| anyhow::anyhow!("unknown benchmark: {}, see `--list`", name))?;
+                let missing = bench.missing_features();
+                anyhow::ensure!(
+                    missing.is_empty(),
+                    "benchmark {} is unavailable, build with `--features {}`",
+                    name,
+                    missing.join(",")
+                );
+                anyhow::ensure!(
+                    bench.has(Bench::GEYSER) == geyser,
+                    "benchmark {} {} geyser config",
+                    name,
+                    if bench.has(Bench::GEYSER) {
+                        "requires"
+                    } else {
+                        "can not be used with"
+                    }
+                );
+                anyhow::ensure!(
+                    !(self.shuffle_keys && bench.has(Bench::PIPELINE)),
+                    "benchmark {} can not be used with shuffle keys",
+                    name
+                );
+                Ok(bench)
+            })
+            .collect()
+    }
+}
+
+struct Run {
+    bench: &'static Bench,
+    set_size: usize,
+    threads: usize,
+    hit_rate: Option<f64>,
+    rayon_min_len: Option<usize>,
+    write_ratio: Option<f64>,
+}
+
+impl Run {
+    /// Benchmark with parameters, as in results keys.
+    fn label(&self) -> String {
+        let mut label = format!(
+            "{} set_size={} threads={}",
+            self.bench.name, self.set_size, self.threads
+        );
+        if let Some(hit_rate) = self.hit_rate {
+            label.push_str(&format!(" hit_rate={}", hit_rate));
+        }
+        if let Some(rayon_min_len) = self.rayon_min_len {
+            label.push_str(&format!(" rayon_min_len={}", rayon_min_len));
+        }
+        if let Some(write_ratio) = self.write_ratio {
+            label.push_str(&format!(" write_ratio={}", write_ratio));
+        }
+        label
+    }
+}
+
+fn load_set_file(path: &Path) -> Result<Vec<Pubkey>> {
+    fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_index, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(index, line)| {
+            line.parse().map_err(|error| {
+                anyhow::anyhow!("invalid pubkey {:?} at line {}: {}", line, index + 1, error)
+            })
+        })
+        .collect()
+}
+
+/// Inputs with directories expanded to datasets in them (by extension) in name order. Datasets
+/// with manifest are listed once by the dataset path, without their shards.
+fn expand_inputs(inputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = vec![];
+    for input in inputs {
+        if !input.is_dir() {
+            expanded.push(input.clone());
+            continue;
+        }
+        let mut files = BTreeSet::new();
+        let mut datasets = BTreeSet::new();
+        for entry in fs::read_dir(input)? {
+            let path = entry?.path();
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            match name.strip_suffix(MANIFEST_SUFFIX) {
+                Some(dataset) => {
+                    datasets.insert(input.join(dataset));
+                }
+                None if path.is_file() && DatasetFormat::from_path(&path).is_ok() => {
+                    files.insert(path);
+                }
+                None => {}
+            }
+        }
+        for dataset in datasets.iter() {
+            if let Some(manifest) = DatasetManifest::read(dataset)? {
+                for shard in manifest.shards {
+                    files.remove(&input.join(shard.file));
+                }
+            }
+        }
+        files.extend(datasets);
+        anyhow::ensure!(!files.is_empty(), "no datasets in {:?}", input);
+        expanded.extend(files);
+    }
+    Ok(expanded)
+}
+
+/// Time per Pubkey (with change versus the first input) and matched Pubkeys of every run over
+/// every input.
+/// `results` are in order of inputs, with result of every run for each.
+fn print_inputs_comparison(inputs: &[PathBuf], runs: &[Run], results: &[BenchResult]) {
+    let name = |input: &PathBuf| {
+        input
+            .file_name()
+            .unwrap_or(input.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    };
+    println!(
+        "Comparison of inputs (time per pubkey, change versus {}, matched pubkeys):",
+        name(&inputs[0])
+    );
+    for (index, run) in runs.iter().enumerate() {
+        let results = results[index..].iter().step_by(runs.len());
+        let base = results.clone().next().map_or(0.0, BenchResult::per_op_ns);
+        let columns = inputs
+            .iter()
+            .zip(results)
+            .enumerate()
+            .map(|(input_index, (input, result))| {
+                let mut column = format!("{} {:.2}ns", name(input), result.per_op_ns());
+                if input_index > 0 {
+                    column += &format!(
+                        " ({:+.2}%)",
+                        (result.per_op_ns() / base.max(f64::MIN_POSITIVE) - 1.0) * 100.0
+                    );
+                }
+                column += &format!(
+                    " {:.2}% matched",
+                    result.success as f64 / result.total_ops.max(1) as f64 * 100.0
+                );
+                column
+            })
+            .collect::<Vec<_>>();
+        println!("{}: {}", run.label(), columns.join(", "));
+    }
+}
+
+/// Measured false positive rate of a filter may exceed the documented one on small or skewed
+/// datasets, where a few false positive keys are looked up in many blocks.
+const FALSE_POSITIVE_TOLERANCE: f64 = 4.0;
+const FALSE_POSITIVE_SLACK: f64 = 16.0;
+
+/// Matches per iteration of filters over the same set and lookups of the input (same seed, set
+/// size, hit rate, set file, geyser config, deduplication and shuffling of keys): exact filters
+/// should match the same keys, filters with false positives at least the same keys and not much
+/// more than allowed by their documented rate. Churn and geyser benchmarks have their own sets
+/// and are not checked.
+fn cross_check(input: &Path, runs: &[Run], results: &[BenchResult]) -> Result<()> {
+    let matches = |result: &BenchResult| result.success as f64 / result.iters.max(1) as f64;
+    let checked = runs
+        .iter()
+        .zip(results)
+        .filter(|(run, _result)| !run.bench.has(Bench::GEYSER | Bench::MUTATION))
+        .map(|(run, result)| {
+            let key = (
+                result.set_size,
+                result.hit_rate.map(f64::to_bits),
+                result.set_file.as_deref(),
+                result.geyser_config.as_deref(),
+                result.dedupe_block,
+                result.shuffle_keys,
+            );
+            (key, run, result)
+        });
+
+    let mut exact = HashMap::<_, &BenchResult>::new();
+    for (key, run, result) in checked.clone() {
+        if run.bench.false_positive_rate > 0.0 {
+            continue;
+        }
+        let reference = *exact.entry(key).or_insert(result);
+        anyhow::ensure!(
+            matches(result) == matches(reference),
+            "cross-check of {:?} (set size: {}, hit rate: {}) failed: {} matched {} keys per \
+            iteration, {} matched {}, exact filters over the same set should agree",
+            input,
+            run.set_size,
+            run.hit_rate.map_or("-".to_owned(), |rate| rate.to_string()),
+            reference.bench,
+            matches(reference),
+            result.bench,
+            matches(result)
+        );
+    }
+
+    for (key, run, result) in checked {
+        let rate = run.bench.false_positive_rate;
+        let Some(reference) = exact.get(&key).filter(|_reference| rate > 0.0) else {
+            continue;
+        };
+        let negatives = result.total_ops as f64 / result.iters.max(1) as f64 - matches(reference);
+        let allowed = negatives.max(0.0) * rate * FALSE_POSITIVE_TOLERANCE + FALSE_POSITIVE_SLACK;
+        let false_positives = matches(result) - matches(reference);
+        anyhow::ensure!(
+            (0.0..=allowed).contains(&false_positives),
+            "cross-check of {:?} (set size: {}, hit rate: {}) failed: {} matched {} keys per \
+            iteration, exact {} matched {}, expected no false negatives and at most {:.0} false \
+            positives for documented rate {}",
+            input,
+            run.set_size,
+            run.hit_rate.map_or("-".to_owned(), |rate| rate.to_string()),
+            result.bench,
+            matches(result),
+            reference.bench,
+            matches(reference),
+            allowed,
+            rate
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct BenchResult {
+    bench: &'static str,
+    allocator: &'static str,
+    input: PathBuf,
+    set_size: usize,
+    threads: usize,
+    hit_rate: Option<f64>,
+    rayon_min_len: Option<usize>,
+    set_file: Option<PathBuf>,
+    geyser_config: Option<PathBuf>,
+    dedupe_block: bool,
+    shuffle_keys: bool,
+    slots: usize,
+    total_ops: usize,
+    iters: u32,
+    elapsed_ns: u64,
+    iter_stats: IterStats,
+    success: usize,
+    /// Memory usage reported by the filter, in bytes.
+    filter_memory: Option<usize>,
+    rss_start_kb: Option<u64>,
+    rss_peak_kb: Option<u64>,
+    /// Allocations during lookups, for all iterations.
+    allocations: Option<AllocStats>,
+    pipeline: Option<PipelineStats>,
+    churn: Option<ChurnStats>,
+    /// Lookups by block time, with `--bucket-interval`.
+    buckets: Vec<TimeBucket>,
+}
+
+impl BenchResult {
+    fn print(&self) {
+        let iters = u64::from(self.iters).max(1);
+        let per =
+            |count: usize| Duration::from_nanos(self.elapsed_ns / (iters * count as u64).max(1));
+        println!(
+            "Total slots: {}, total ops: {}, iters: {}, elapsed per blocks: {:?}, per block: {:?}, per pubkey: {:?} (succes: {})",
+            self.slots,
+            self.total_ops,
+            self.iters,
+            per(1),
+            per(self.slots),
+            per(self.total_ops),
+            self.success
+        );
+        println!(
+            "Iteration mean: {:?}, std dev: {:?}, min: {:?}, max: {:?}, cv: {:.2}%",
+            Duration::from_nanos(self.iter_stats.mean_ns),
+            Duration::from_nanos(self.iter_stats.stddev_ns),
+            Duration::from_nanos(self.iter_stats.min_ns),
+            Duration::from_nanos(self.iter_stats.max_ns),
+            self.iter_stats.cv * 100.0
+        );
+        if let Some(filter_memory) = self.filter_memory {
+            println!(
+                "Filter memory usage: {:.2} MiB",
+                filter_memory as f64 / 1024.0 / 1024.0
+            );
+        }
+        if let (Some(start), Some(peak)) = (self.rss_start_kb, self.rss_peak_kb) {
+            println!(
+                "RSS start: {:.2} MiB, peak: {:.2} MiB",
+                start as f64 / 1024.0,
+                peak as f64 / 1024.0
+            );
+        }
+        if let Some(allocations) = &self.allocations {
+            println!(
+                "Allocations per iter: {}, bytes per iter: {}",
+                allocations.allocations / iters,
+                allocations.bytes / iters
+            );
+        }
+        if let Some(pipeline) = &self.pipeline {
+            println!(
+                "Pipeline consumers: {}, channel capacity: {}, producer blocked: {} times, {:?} total",
+                pipeline.consumers,
+                pipeline.channel_capacity,
+                pipeline.blocked,
+                Duration::from_nanos(pipeline.blocked_ns)
+            );
+        }
+        if let Some(churn) = &self.churn {
+            println!(
+                "Churn rate: {}/s, achieved: {:.2}/s, subscriptions added: {}, removed: {}, update time: {:?}",
+                churn.rate,
+                churn.added as f64 / (self.elapsed_ns as f64 / 1e9),
+                churn.added,
+                churn.removed,
+                Duration::from_nanos(churn.update_ns)
+            );
+            println!(
+                "Write ratio: {}, achieved: {:.4}, writes: {}",
+                churn
+                    .write_ratio
+                    .map_or("-".to_owned(), |ratio| ratio.to_string()),
+                churn.writes as f64 / (churn.writes + self.total_ops) as f64,
+                churn.writes
+            );
+        }
+        for bucket in self.buckets.iter() {
+            println!(
+                "Bucket {}: blocks {}, pubkeys per block {:.0}, per pubkey: {:.2}ns, matched: {:.2}%",
+                bucket.start_time,
+                bucket.blocks,
+                bucket.total_ops as f64 / (bucket.blocks as f64 * self.iters as f64).max(1.0),
+                bucket.elapsed_ns as f64 / bucket.total_ops.max(1) as f64,
+                bucket.success as f64 / bucket.total_ops.max(1) as f64 * 100.0
+            );
+        }
+    }
+}
+
+impl BenchResult {
+    /// Mean time per lookup in ns.
+    fn per_op_ns(&self) -> f64 {
+        self.iter_stats.mean_ns as f64 * self.iters as f64 / self.total_ops.max(1) as f64
+    }
+
+    /// Time per Pubkey, named by benchmark and all parameters, so every run is tracked separately.
+    fn gha_benchmark(&self) -> GhaBenchmarkEntry {
+        let input = self.input.file_name().unwrap_or(self.input.as_os_str());
+        let mut name = format!(
+            "{} ({}, set size: {}, threads: {}",
+            self.bench,
+            input.to_string_lossy(),
+            self.set_size,
+            self.threads
+        );
+        if let Some(hit_rate) = self.hit_rate {
+            name += &format!(", hit rate: {}", hit_rate);
+        }
+        if let Some(min_len) = self.rayon_min_len {
+            name += &format!(", rayon min len: {}", min_len);
+        }
+        if let Some(ratio) = self.churn.and_then(|churn| churn.write_ratio) {
+            name += &format!(", write ratio: {}", ratio);
+        }
+        name += ")";
+        let ops_per_iter = self.total_ops as f64 / self.iters.max(1) as f64;
+        GhaBenchmarkEntry {
+            name,
+            unit: "ns/pubkey",
+            value: self.elapsed_ns as f64 / self.total_ops.max(1) as f64,
+            range: format!(
+                "± {:.3}",
+                self.iter_stats.stddev_ns as f64 / ops_per_iter.max(1.0)
+            ),
+            extra: format!(
+                "iters: {}\nallocator: {}\nsuccess: {}",
+                self.iters, self.allocator, self.success
+            ),
+        }
+    }
+
+    /// Line of libtest bench output with mean and standard deviation of iteration time, name
+    /// has benchmark and all parameters separated by `/` (without spaces, as parsers expect).
+    fn bencher(&self) -> String {
+        let input = self.input.file_name().unwrap_or(self.input.as_os_str());
+        let mut name = format!(
+            "{}/{}/set_size={}/threads={}",
+            self.bench,
+            input.to_string_lossy().replace(' ', "_"),
+            self.set_size,
+            self.threads
+        );
+        if let Some(hit_rate) = self.hit_rate {
+            name += &format!("/hit_rate={}", hit_rate);
+        }
+        if let Some(min_len) = self.rayon_min_len {
+            name += &format!("/rayon_min_len={}", min_len);
+        }
+        if let Some(ratio) = self.churn.and_then(|churn| churn.write_ratio) {
+            name += &format!("/write_ratio={}", ratio);
+        }
+        format!(
+            "test {} ... bench: {:>14} ns/iter (+/- {})\n",
+            name,
+            thousands(self.iter_stats.mean_ns),
+            thousands(self.iter_stats.stddev_ns)
+        )
+    }
+
+    #[cfg(any(feature = "sqlite", feature = "postgres"))]
+    fn row<'a>(&'a self, suite: &'a Suite) -> Result<crate::results::ResultRow<'a>> {
+        let input = self.input.file_name().unwrap_or(self.input.as_os_str());
+        Ok(crate::results::ResultRow {
+            suite: suite.name.as_deref(),
+            bench: self.bench,
+            input: input.to_string_lossy().into_owned(),
+            set_size: self.set_size,
+            threads: self.threads,
+            hit_rate: self.hit_rate,
+            rayon_min_len: self.rayon_min_len,
+            write_ratio: self.churn.and_then(|churn| churn.write_ratio),
+            total_ops: self.total_ops,
+            iters: self.iters,
+            elapsed_ns: self.elapsed_ns,
+            mean_ns: self.iter_stats.mean_ns,
+            stddev_ns: self.iter_stats.stddev_ns,
+            cv: self.iter_stats.cv,
+            success: self.success,
+            filter_memory: self.filter_memory,
+            rss_peak_kb: self.rss_peak_kb,
+            result: serde_json::to_string(self)?,
+        })
+    }
+
+    /// Point of `safb_bench` measurement, parameters are tags.
+    fn point(&self, suite: &Suite) -> Point {
+        let input = self.input.file_name().unwrap_or(self.input.as_os_str());
+        let elapsed = self.elapsed_ns as f64;
+        let mut point = Point::new("safb_bench")
+            .tag("bench", self.bench)
+            .tag("allocator", self.allocator)
+            .tag("suite", suite.name.as_deref().unwrap_or_default())
+            .tag("input", input.to_string_lossy())
+            .tag("set_size", self.set_size)
+            .tag("threads", self.threads)
+            .tag(
+                "hit_rate",
+                self.hit_rate.map_or(String::new(), |rate| rate.to_string()),
+            )
+            .tag(
+                "rayon_min_len",
+                self.rayon_min_len
+                    .map_or(String::new(), |len| len.to_string()),
+            )
+            .tag(
+                "write_ratio",
+                self.churn
+                    .and_then(|churn| churn.write_ratio)
+                    .map_or(String::new(), |ratio| ratio.to_string()),
+            )
+            .integer("total_ops", self.total_ops)
+            .integer("iters", self.iters)
+            .integer("elapsed_ns", self.elapsed_ns)
+            .float("per_op_ns", elapsed / self.total_ops.max(1) as f64)
+            .float("ops_per_sec", self.total_ops as f64 / (elapsed / 1e9))
+            .integer("iter_mean_ns", self.iter_stats.mean_ns)
+            .integer("iter_stddev_ns", self.iter_stats.stddev_ns)
+            .float("iter_cv", self.iter_stats.cv)
+            .integer("success", self.success);
+        if let Some(filter_memory) = self.filter_memory {
+            point = point.integer("filter_memory", filter_memory);
+        }
+        if let Some(rss_peak_kb) = self.rss_peak_kb {
+            point = point.integer("rss_peak_kb", rss_peak_kb);
+        }
+        point
+    }
+
+    /// Update metrics with lookups of all iterations and p99 of per block latency if recorded.
+    fn publish(&self, metrics: &Metrics, latencies: &[BlockLatency]) {
+        metrics.add(self.total_ops, self.success);
+        let mut latencies = latencies
+            .iter()
+            .map(|latency| latency.latency_ns)
+            .collect::<Vec<_>>();
+        latencies.sort_unstable();
+        let p99 = latencies
+            .get(latencies.len() * 99 / 100)
+            .map(|ns| *ns as f64 / 1e9);
+        metrics.set(Gauges {
+            bench: Some(self.bench.to_owned()),
+            lookups_per_second: Some(self.total_ops as f64 / (self.elapsed_ns as f64 / 1e9)),
+            hit_ratio: Some(self.success as f64 / self.total_ops.max(1) as f64),
+            lookup_p99_seconds: p99,
+            update_p99_seconds: None,
+            filter_keys: Some(self.set_size),
+            filter_memory_bytes: self.filter_memory,
+        });
+    }
+}
+
+/// Number with `,` between thousands, as printed by libtest.
+fn thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut result = String::with_capacity(digits.len() * 4 / 3);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(digit);
+    }
+    result
+}
+
+/// Resident set size of the process in kB, from `/proc/self/status` (Linux only).
+#[derive(Debug, Clone, Copy)]
+pub struct Rss {
+    pub current: u64,
+    pub peak: u64,
+}
+
+impl Rss {
+    /// Reset peak RSS (`VmHWM`) to the current value, so peak can be measured per benchmark.
+    fn reset_peak() {
+        let _ = fs::write("/proc/self/clear_refs", "5");
+    }
+
+    pub fn read() -> Option<Self> {
+        let status = fs::read_to_string("/proc/self/status").ok()?;
+        let value = |key| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix(key))
+                .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        };
+        Some(Self {
+            current: value("VmRSS:")?,
+            peak: value("VmHWM:")?,
+        })
+    }
+}
+
+/// Latency of block in some run (index in results) of the suite.
+#[derive(Debug, Serialize)]
+struct RunBlockLatency {
+    run: usize,
+    bench: &'static str,
+    #[serde(flatten)]
+    latency: BlockLatency,
+}
+
+fn save_latencies(path: &Path, latencies: &[RunBlockLatency]) -> Result<()> {
+    if path.extension().is_some_and(|ext| ext == "json") {
+        fs::write(path, serde_json::to_string(latencies)?)?;
+    } else {
+        let mut csv = String::from("run,bench,iter,slot,keys,latency_ns\n");
+        for item in latencies {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                item.run,
+                item.bench,
+                item.latency.iter,
+                item.latency.slot,
+                item.latency.keys,
+                item.latency.latency_ns
+            ));
+        }
+        fs::write(path, csv)?;
+    }
+    Ok(())
+}
+
+/// Distribution of iteration time.
+#[derive(Debug, Clone, Copy, Serialize)]
+struct IterStats {
+    mean_ns: u64,
+    stddev_ns: u64,
+    min_ns: u64,
+    max_ns: u64,
+    /// Coefficient of variation, `stddev / mean`.
+    cv: f64,
+}
+
+impl IterStats {
+    fn new(iters: &[Duration]) -> Self {
+        let values = iters
+            .iter()
+            .map(|elapsed| elapsed.as_nanos() as f64)
+            .collect::<Vec<_>>();
+        let mean = values.iter().sum::<f64>() / values.len().max(1) as f64;
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / values.len().max(1) as f64;
+        let stddev = variance.sqrt();
+        Self {
+            mean_ns: mean as u64,
+            stddev_ns: stddev as u64,
+            min_ns: values.iter().cloned().fold(f64::INFINITY, f64::min) as u64,
+            max_ns: values.iter().cloned().fold(0.0, f64::max) as u64,
+            cv: if mean > 0.0 { stddev / mean } else { 0.0 },
+        }
+    }
+}
+
+fn print_benches() {
+    for bench in registry() {
+        let missing = bench.missing_features();
+        let features = if !missing.is_empty() {
+            format!(
+                " (unavailable, build with `--features {}`)",
+                missing.join(",")
+            )
+        } else if !bench.features.is_empty() {
+            format!(" (requires features: {})", bench.features.join(", "))
+        } else {
+            String::new()
+        };
+        let geyser = if bench.has(Bench::GEYSER) {
+            " (requires --geyser-config)"
+        } else {
+            ""
+        };
+        println!(
+            "{:<24} {}{}{}",
+            bench.name, bench.description, features, geyser
+        );
+    }
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    if args.list {
+        print_benches();
+        return Ok(());
+    }
+    let config = args.load_config()?;
+    run_config(&args, &config, global).await?;
+    Ok(())
+}
+
+/// Suite config of `dispatch` in TOML run by `agent`, returns results of all suites in JSON.
+/// Paths of outputs and baselines are of the coordinator, so they are ignored.
+#[cfg(feature = "orchestrate")]
+pub async fn run_remote(config: &str, global: &GlobalArgs) -> Result<String> {
+    // defaults of all flags, sinks of results are not used by agents
+    #[derive(clap::Parser)]
+    struct Defaults {
+        #[clap(flatten)]
+        args: Args,
+    }
+
+    let mut config = toml::from_str::<Config>(config)?;
+    for suite in config.suites.iter_mut() {
+        suite.baseline = None;
+        suite.latency_output = None;
+        suite.output = None;
+    }
+    let args = <Defaults as clap::Parser>::try_parse_from(["bench"])?.args;
+    let results = run_config(&args, &config, global).await?;
+    serde_json::to_string(&results).map_err(Into::into)
+}
+
+/// Run suites of `config`, results of all suites are returned.
+async fn run_config(args: &Args, config: &Config, global: &GlobalArgs) -> Result<Vec<BenchResult>> {
+    anyhow::ensure!(
+        config.iters != Some(0),
+        "iters should be at least 1, set 1 for a smoke test"
+    );
+    // started first, so all output of the run is captured into its log
+    #[cfg(feature = "tui")]
+    let dashboard = match args.tui {
+        true => Some(crate::tui::Dashboard::start(Duration::from_secs(
+            config.min_work,
+        ))?),
+        false => None,
+    };
+    #[cfg(feature = "tui")]
+    let on_iter = |progress| {
+        if let Some(dashboard) = &dashboard {
+            dashboard.iter(progress);
+        }
+    };
+    if !global.quiet {
+        println!("Allocator: {}", allocator::NAME);
+    }
+    let metrics = args.metrics_addr.map(Metrics::serve).transpose()?;
+    #[cfg(feature = "upload")]
+    let mut uploader = args
+        .upload
+        .clone()
+        .map(|url| crate::upload::Uploader::new(url, "bench"))
+        .transpose()?;
+    let influx = args.influx.client();
+    #[cfg(feature = "sqlite")]
+    let results_db = match &args.results_db {
+        Some(path) => Some(crate::results_db::ResultsDb::open(
+            path,
+            allocator::NAME,
+            &serde_json::to_string(config)?,
+        )?),
+        None => None,
+    };
+    #[cfg(feature = "postgres")]
+    let results_pg = match &args.results_postgres {
+        Some(url) => Some(
+            crate::results_pg::ResultsPg::connect(
+                url,
+                args.commit.clone(),
+                allocator::NAME,
+                serde_json::to_value(config)?,
+            )
+            .await?,
+        ),
+        None => None,
+    };
+
+    let mut all_results = vec![];
+    let mut cross_check_failures = 0;
+    for suite in config.suites.iter() {
+        if let Some(name) = &suite.name {
+            println!("Suite: {}", name);
+        }
+        let set_pubkeys = suite.set_file.as_deref().map(load_set_file).transpose()?;
+        let geyser = suite
+            .geyser_config
+            .as_deref()
+            .map(GeyserConfig::load)
+            .transpose()?;
+        let runs = suite.runs(set_pubkeys.as_deref(), geyser.as_ref())?;
+        // loaded before the run, so invalid baseline does not fail the suite at the end
+        let baseline = suite
+            .baseline
+            .as_deref()
+            .map(results::load_results)
+            .transpose()?;
+        let suite_start = SystemTime::now();
+
+        let mut results = vec![];
+        let mut latencies = vec![];
+        let inputs = expand_inputs(&suite.inputs)?;
+        for input in inputs.iter() {
+            let ts = SystemTime::now();
+            let allocations = AllocStats::now();
+            let dataset = global.load_dataset(input)?;
+            #[cfg(feature = "postgres")]
+            let dataset_hash = match results_pg {
+                Some(_) => {
+                    use solana_accounts_filter_bench::dataset::{sha256_file, DatasetManifest};
+                    // manifest lists checksums of all shards
+                    let manifest = DatasetManifest::path(input);
+                    sha256_file(if manifest.exists() { &manifest } else { input })?
+                }
+                None => String::new(),
+            };
+            if !global.quiet {
+                println!(
+                    "Total slots: {}, elapsed: {:?}",
+                    dataset.blocks.len(),
+                    ts.elapsed()?
+                );
+            }
+            if let Some(allocations) = AllocStats::since(allocations) {
+                println!(
+                    "Load allocations: {}, bytes: {}",
+                    allocations.allocations, allocations.bytes
+                );
+            }
+
+            #[cfg(feature = "tui")]
+            if let Some(dashboard) = &dashboard {
+                dashboard.runs(
+                    suite.name.as_deref(),
+                    input,
+                    runs.iter().map(Run::label).collect(),
+                );
+            }
+            let input_results = results.len();
+            for run in runs.iter() {
+                #[cfg(feature = "tui")]
+                if let Some(dashboard) = &dashboard {
+                    dashboard.start_run();
+                }
+                println!(
+                    "Bench {}, set size: {}, threads: {}, hit rate: {}{}{}",
+                    run.bench.name,
+                    run.set_size,
+                    run.threads,
+                    run.hit_rate.map_or("-".to_owned(), |rate| rate.to_string()),
+                    run.rayon_min_len.map_or(String::new(), |min_len| format!(
+                        ", rayon min len: {}",
+                        min_len
+                    )),
+                    run.write_ratio
+                        .map_or(String::new(), |ratio| format!(", write ratio: {}", ratio))
+                );
+                let mut builder = BenchConfig::builder(&dataset)
+                    .set_size(run.set_size)
+                    .hit_rate(run.hit_rate.unwrap_or_default())
+                    .dedupe_block(suite.dedupe_block)
+                    .shuffle_keys(suite.shuffle_keys)
+                    .channel_capacity(suite.channel_capacity)
+                    .rayon_min_len(run.rayon_min_len.unwrap_or(1))
+                    .churn(suite.churn_rate, suite.churn_keys)
+                    .write_ratio(run.write_ratio)
+                    .record_latency(suite.latency_output.is_some())
+                    .bucket_interval(suite.bucket_interval)
+                    .duration(Duration::from_secs(config.min_work))
+                    .iters(config.iters)
+                    .threads(run.threads)
+                    .seed(config.seed);
+                if let Some(set_pubkeys) = &set_pubkeys {
+                    builder = builder.set_pubkeys(set_pubkeys);
+                }
+                if let Some(geyser) = &geyser {
+                    builder = builder.geyser(geyser);
+                }
+                #[cfg(feature = "tui")]
+                if dashboard.is_some() {
+                    builder = builder.on_iter(&on_iter);
+                }
+                let bench_config = builder.build()?;
+
+                Rss::reset_peak();
+                let rss_start = Rss::read();
+                let measurement = bench_config.run(run.bench)?;
+                let rss_end = Rss::read();
+                if bench_config.record_latency && measurement.latencies.is_empty() {
+                    println!("Per block latency is not supported by {}", run.bench.name);
+                }
+                if bench_config.bucket_interval.is_some() && measurement.buckets.is_empty() {
+                    println!("Time buckets are not supported by {}", run.bench.name);
+                }
+                latencies.extend(measurement.latencies.iter().map(|latency| RunBlockLatency {
+                    run: results.len(),
+                    bench: run.bench.name,
+                    latency: *latency,
+                }));
+
+                let result = BenchResult {
+                    bench: run.bench.name,
+                    allocator: allocator::NAME,
+                    input: input.clone(),
+                    set_size: run.set_size,
+                    threads: bench_config.num_threads(),
+                    hit_rate: run.hit_rate,
+                    rayon_min_len: run.rayon_min_len,
+                    set_file: suite.set_file.clone(),
+                    geyser_config: suite.geyser_config.clone(),
+                    dedupe_block: suite.dedupe_block,
+                    shuffle_keys: suite.shuffle_keys,
+                    slots: dataset.blocks.len(),
+                    total_ops: measurement.total_ops,
+                    iters: measurement.iters,
+                    elapsed_ns: measurement.elapsed.as_nanos() as u64,
+                    iter_stats: IterStats::new(&measurement.iters_elapsed),
+                    success: measurement.success,
+                    filter_memory: measurement.filter_memory,
+                    rss_start_kb: rss_start.map(|rss| rss.current),
+                    rss_peak_kb: rss_end.map(|rss| rss.peak),
+                    allocations: measurement.allocations,
+                    pipeline: measurement.pipeline,
+                    churn: measurement.churn,
+                    buckets: measurement.buckets,
+                };
+                result.print();
+                #[cfg(feature = "tui")]
+                if let Some(dashboard) = &dashboard {
+                    dashboard.finish_run(
+                        result.elapsed_ns as f64 / result.total_ops.max(1) as f64,
+                        result.filter_memory,
+                    );
+                }
+                if let Some(metrics) = &metrics {
+                    result.publish(metrics, &measurement.latencies);
+                }
+                if let Some(influx) = &influx {
+                    influx.write_or_warn(&[result.point(suite)]).await;
+                }
+                #[cfg(feature = "sqlite")]
+                if let Some(results_db) = &results_db {
+                    results_db.insert(&result.row(suite)?)?;
+                }
+                #[cfg(feature = "postgres")]
+                if let Some(results_pg) = &results_pg {
+                    results_pg
+                        .insert(&dataset_hash, &result.row(suite)?)
+                        .await?;
+                }
+                if result.iter_stats.cv > config.max_cv {
+                    println!(
+                        "WARNING: iteration time cv {:.2}% exceeds {:.2}%, machine probably was noisy",
+                        result.iter_stats.cv * 100.0,
+                        config.max_cv * 100.0
+                    );
+                }
+                results.push(result);
+            }
+            if let Err(error) = cross_check(input, &runs, &results[input_results..]) {
+                println!("ERROR: {}", error);
+                cross_check_failures += 1;
+            }
+        }
+        if inputs.len() > 1 {
+            print_inputs_comparison(&inputs, &runs, &results);
+        }
+
+        if let Some(output) = &suite.output {
+            args.output_format.save(output, &results)?;
+        }
+        if let Some(output) = &suite.latency_output {
+            save_latencies(output, &latencies)?;
+        }
+        #[cfg(feature = "upload")]
+        if let Some(uploader) = &mut uploader {
+            for output in [&suite.output, &suite.latency_output].into_iter().flatten() {
+                uploader.upload(output).await?;
+            }
+        }
+        if let Some(url) = &args.notify_webhook {
+            let results = serde_json::from_value::<Vec<results::BenchResult>>(
+                serde_json::to_value(&results)?,
+            )?;
+            SuiteSummary::new(
+                suite.name.as_deref(),
+                suite_start.elapsed()?,
+                &results,
+                baseline.as_deref(),
+                config.regression_threshold,
+            )
+            .post(url)
+            .await;
+        }
+        all_results.extend(results);
+    }
+
+    #[cfg(feature = "sqlite")]
+    if let Some(results_db) = &results_db {
+        results_db.finish()?;
+    }
+    #[cfg(feature = "postgres")]
+    if let Some(results_pg) = &results_pg {
+        results_pg.finish().await?;
+    }
+    // results are saved before failure of the cross-check is reported
+    anyhow::ensure!(
+        cross_check_failures == 0,
+        "cross-check failed for {} inputs, see errors above",
+        cross_check_failures
+    );
+    Ok(all_results)
+}
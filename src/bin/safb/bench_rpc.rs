@@ -0,0 +1,230 @@
+use {
+    crate::{latency::LatencyStats, GlobalArgs},
+    anyhow::Result,
+    futures::stream::{self, StreamExt},
+    rand::{seq::SliceRandom, SeedableRng},
+    rand_chacha::ChaCha8Rng,
+    serde::Serialize,
+    solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig},
+    solana_client::{
+        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        fs,
+        path::PathBuf,
+        str::FromStr,
+        time::{Duration, Instant},
+    },
+};
+
+/// Measure server-side filtering: `getProgramAccounts` with filters by Pubkeys of the dataset.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Program of the accounts.
+    #[clap(long, default_value_t = spl_token())]
+    program: Pubkey,
+
+    /// Offset of Pubkey of the dataset in account data for `memcmp` filter, by default owner of
+    /// token account.
+    #[clap(long, default_value_t = 32)]
+    offset: usize,
+
+    /// Optional `dataSize` filter, e.g. 165 for token accounts.
+    #[clap(long)]
+    datasize: Option<u64>,
+
+    /// Number of requests, every request is filtered by another Pubkey of the dataset.
+    #[clap(long, default_value_t = 100)]
+    samples: usize,
+
+    /// Take the most referenced Pubkeys of the dataset instead of random.
+    #[clap(long)]
+    hottest: bool,
+
+    /// Request accounts without data (empty `dataSlice`), to measure filtering only.
+    #[clap(long)]
+    no_data: bool,
+
+    /// Number of concurrent requests.
+    #[clap(short = 't', long, default_value_t = 1)]
+    concurrency: usize,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Optional output file for results in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+fn spl_token() -> Pubkey {
+    Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").expect("valid pubkey")
+}
+
+/// Result of one `getProgramAccounts` request.
+#[derive(Debug, Serialize)]
+struct RequestResult {
+    pubkey: String,
+    latency_ns: u64,
+    accounts: usize,
+    /// Bytes of account data in the response.
+    data_bytes: usize,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BenchRpcReport {
+    program: String,
+    offset: usize,
+    datasize: Option<u64>,
+    no_data: bool,
+    concurrency: usize,
+    elapsed_ns: u64,
+    requests: usize,
+    errors: usize,
+    /// Requests without matched accounts.
+    empty: usize,
+    accounts: usize,
+    data_bytes: usize,
+    latency: Option<LatencyStats>,
+    results: Vec<RequestResult>,
+}
+
+impl Args {
+    /// Pubkeys compared with account data, one per request.
+    fn pubkeys(&self, global: &GlobalArgs) -> Result<Vec<Pubkey>> {
+        let dataset = global.load_dataset(&self.input)?;
+        let mut counts = dataset.key_counts().into_iter().collect::<Vec<_>>();
+        // sorted for determinism, `HashMap` order is random
+        counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let mut pubkeys = counts
+            .into_iter()
+            .map(|(pubkey, _count)| pubkey)
+            .collect::<Vec<_>>();
+        if !self.hottest {
+            pubkeys.shuffle(&mut ChaCha8Rng::seed_from_u64(self.seed));
+        }
+        pubkeys.truncate(self.samples);
+        Ok(pubkeys)
+    }
+
+    fn config(&self, pubkey: &Pubkey) -> RpcProgramAccountsConfig {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp {
+            offset: self.offset,
+            bytes: MemcmpEncodedBytes::Base58(pubkey.to_string()),
+            encoding: None,
+        })];
+        if let Some(datasize) = self.datasize {
+            filters.push(RpcFilterType::DataSize(datasize));
+        }
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: self.no_data.then_some(UiDataSliceConfig {
+                    offset: 0,
+                    length: 0,
+                }),
+                commitment: None,
+                min_context_slot: None,
+            },
+            with_context: None,
+        }
+    }
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let pubkeys = args.pubkeys(global)?;
+    let rpc = global.rpc_client()?;
+
+    let start = Instant::now();
+    let requests = stream::iter(pubkeys.iter().map(|pubkey| {
+        let rpc = &rpc;
+        let config = args.config(pubkey);
+        async move {
+            let start = Instant::now();
+            let response = rpc
+                .get_program_accounts_with_config(&args.program, config)
+                .await;
+            let latency_ns = start.elapsed().as_nanos() as u64;
+            match response {
+                Ok(accounts) => RequestResult {
+                    pubkey: pubkey.to_string(),
+                    latency_ns,
+                    accounts: accounts.len(),
+                    data_bytes: accounts
+                        .iter()
+                        .map(|(_pubkey, account)| account.data.len())
+                        .sum(),
+                    error: None,
+                },
+                Err(error) => RequestResult {
+                    pubkey: pubkey.to_string(),
+                    latency_ns,
+                    accounts: 0,
+                    data_bytes: 0,
+                    error: Some(error.to_string()),
+                },
+            }
+        }
+    }))
+    .buffered(args.concurrency.max(1));
+
+    let mut results = Vec::with_capacity(pubkeys.len());
+    futures::pin_mut!(requests);
+    while let Some(result) = requests.next().await {
+        if !global.quiet {
+            match &result.error {
+                Some(error) => println!("{}: error: {}", result.pubkey, error),
+                None => println!(
+                    "{}: {} accounts, {} bytes in {:?}",
+                    result.pubkey,
+                    result.accounts,
+                    result.data_bytes,
+                    Duration::from_nanos(result.latency_ns)
+                ),
+            }
+        }
+        results.push(result);
+    }
+
+    let ok = || results.iter().filter(|result| result.error.is_none());
+    let report = BenchRpcReport {
+        program: args.program.to_string(),
+        offset: args.offset,
+        datasize: args.datasize,
+        no_data: args.no_data,
+        concurrency: args.concurrency,
+        elapsed_ns: start.elapsed().as_nanos() as u64,
+        requests: results.len(),
+        errors: results.len() - ok().count(),
+        empty: ok().filter(|result| result.accounts == 0).count(),
+        accounts: ok().map(|result| result.accounts).sum(),
+        data_bytes: ok().map(|result| result.data_bytes).sum(),
+        latency: LatencyStats::new(ok().map(|result| result.latency_ns).collect()),
+        results,
+    };
+    println!(
+        "Requests: {}, errors: {}, empty: {}, accounts: {}, data: {:.2} MiB",
+        report.requests,
+        report.errors,
+        report.empty,
+        report.accounts,
+        report.data_bytes as f64 / 1024.0 / 1024.0
+    );
+    if let Some(latency) = &report.latency {
+        println!("Latency {}", latency.format());
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use {
+    crate::{
+        results::{self, BenchResult},
+        GlobalArgs,
+    },
+    anyhow::Result,
+    std::{
+        collections::BTreeMap,
+        fmt,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Compare two results files of `bench --output` (from different commits or machines).
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Baseline results in JSON.
+    #[clap(parse(from_os_str))]
+    base: PathBuf,
+
+    /// New results in JSON, compared with baseline.
+    #[clap(parse(from_os_str))]
+    new: PathBuf,
+
+    /// Minimum relative change of mean iteration time reported as regression or improvement.
+    #[clap(long, default_value_t = 0.05)]
+    threshold: f64,
+
+    /// Exit with error if any benchmark regressed.
+    #[clap(long)]
+    fail_on_regression: bool,
+}
+
+fn load_results(path: &Path) -> Result<BTreeMap<String, BenchResult>> {
+    Ok(results::load_results(path)?
+        .into_iter()
+        .map(|result| (result.key(), result))
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Insignificant,
+    Regression,
+    Improvement,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Insignificant => "~",
+            Self::Regression => "REGRESSION",
+            Self::Improvement => "improvement",
+        })
+    }
+}
+
+/// Change of mean iteration time is significant if it exceeds `threshold` and twice the
+/// standard error of the difference (~95% for normal distribution of iterations).
+pub fn change(base: &BenchResult, new: &BenchResult, threshold: f64) -> Change {
+    let base_mean = base.iter_stats.mean_ns as f64;
+    let new_mean = new.iter_stats.mean_ns as f64;
+    let delta = (new_mean - base_mean) / base_mean.max(1.0);
+    let stderr = ((base.iter_stats.stddev_ns as f64).powi(2) / base.iters.max(1) as f64
+        + (new.iter_stats.stddev_ns as f64).powi(2) / new.iters.max(1) as f64)
+        .sqrt();
+    if delta.abs() < threshold || (new_mean - base_mean).abs() < 2.0 * stderr {
+        Change::Insignificant
+    } else if delta > 0.0 {
+        Change::Regression
+    } else {
+        Change::Improvement
+    }
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let base = load_results(&args.base)?;
+    let new = load_results(&args.new)?;
+
+    let mut regressions = 0;
+    for (key, new_result) in new.iter() {
+        let base_result = match base.get(key) {
+            Some(base_result) => base_result,
+            None => {
+                if !global.quiet {
+                    println!("{}: only in {}", key, args.new.display());
+                }
+                continue;
+            }
+        };
+        let change = change(base_result, new_result, args.threshold);
+        if change == Change::Regression {
+            regressions += 1;
+        }
+        if global.quiet && change == Change::Insignificant {
+            continue;
+        }
+        println!(
+            "{}: iter {:.3}ms -> {:.3}ms ({:+.2}%), per pubkey {:.2}ns -> {:.2}ns {}",
+            key,
+            base_result.iter_stats.mean_ns as f64 / 1e6,
+            new_result.iter_stats.mean_ns as f64 / 1e6,
+            (new_result.iter_stats.mean_ns as f64 / base_result.iter_stats.mean_ns.max(1) as f64
+                - 1.0)
+                * 100.0,
+            base_result.per_op_ns(),
+            new_result.per_op_ns(),
+            change
+        );
+    }
+    if !global.quiet {
+        for key in base.keys().filter(|key| !new.contains_key(*key)) {
+            println!("{}: only in {}", key, args.base.display());
+        }
+    }
+
+    println!(
+        "Compared {} benchmarks, regressions: {}",
+        new.keys().filter(|key| base.contains_key(*key)).count(),
+        regressions
+    );
+    anyhow::ensure!(
+        !args.fail_on_regression || regressions == 0,
+        "{} benchmarks regressed",
+        regressions
+    );
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use {
+    crate::{Cli, GlobalArgs},
+    anyhow::Result,
+    clap::CommandFactory,
+    clap_complete::Shell,
+    std::io,
+};
+
+/// Print shell completions of the CLI.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Shell: bash, elvish, fish, powershell or zsh.
+    #[clap(arg_enum)]
+    shell: Shell,
+}
+
+pub fn run(args: Args, _global: &GlobalArgs) -> Result<()> {
+    clap_complete::generate(
+        args.shell,
+        &mut Cli::command(),
+        env!("CARGO_BIN_NAME"),
+        &mut io::stdout(),
+    );
+    Ok(())
+}
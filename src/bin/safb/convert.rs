@@ -0,0 +1,49 @@
+use {
+    crate::{dataset_size, GlobalArgs},
+    anyhow::Result,
+    solana_accounts_filter_bench::dataset::DatasetFormat,
+    std::{path::PathBuf, time::SystemTime},
+};
+
+/// Convert dataset to another format, e.g. old JSON captures to compressed bincode.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data, format by extension or global `--format`.
+    #[clap(short, long, parse(from_os_str))]
+    input: PathBuf,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, parse(from_os_str))]
+    output: PathBuf,
+
+    /// Format of the out file instead of detection by file extension (e.g. `msgpack.zst`).
+    #[clap(long)]
+    output_format: Option<DatasetFormat>,
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let ts = SystemTime::now();
+    let dataset = global.load_dataset(&args.input)?;
+    if !global.quiet {
+        println!(
+            "Loaded {} blocks in: {:?}",
+            dataset.blocks.len(),
+            ts.elapsed()?
+        );
+    }
+
+    let ts = SystemTime::now();
+    global.save_dataset_as(&dataset, &args.output, args.output_format)?;
+    if !global.quiet {
+        println!("Saved in: {:?}", ts.elapsed()?);
+    }
+
+    println!(
+        "Converted {} ({} bytes) to {} ({} bytes)",
+        args.input.display(),
+        dataset_size(&args.input)?,
+        args.output.display(),
+        dataset_size(&args.output)?
+    );
+    Ok(())
+}
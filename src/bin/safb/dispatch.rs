@@ -0,0 +1,147 @@
+use {
+    crate::{
+        agent::{RunRequest, RunResponse, RUN_PATH},
+        results::BenchResult,
+        GlobalArgs,
+    },
+    anyhow::Result,
+    futures::future::join_all,
+    serde::Serialize,
+    std::{collections::BTreeMap, fs, path::PathBuf},
+    tonic::{
+        client::Grpc,
+        codec::ProstCodec,
+        codegen::http::uri::PathAndQuery,
+        transport::{ClientTlsConfig, Endpoint},
+        Request,
+    },
+};
+
+/// Run suite config on remote `agent` machines in parallel and compare their results.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Suite config in TOML (see `bench --config`), inputs are paths on the agents.
+    #[clap(long, parse(from_os_str))]
+    config: PathBuf,
+
+    /// gRPC endpoint of `agent`, e.g. `http://10.0.0.2:10020`, once for every machine.
+    #[clap(long = "agent", required = true)]
+    agents: Vec<String>,
+
+    /// Optional `x-token` for authentication.
+    #[clap(long)]
+    x_token: Option<String>,
+
+    /// Optional output file for environment and results of every agent in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+/// Environment and results of one agent in `--output`.
+#[derive(Debug, Serialize)]
+struct AgentResults {
+    agent: String,
+    environment: serde_json::Value,
+    results: serde_json::Value,
+}
+
+impl AgentResults {
+    /// Host of the agent machine, endpoint if unknown.
+    fn host(&self) -> &str {
+        self.environment["host"].as_str().unwrap_or(&self.agent)
+    }
+}
+
+async fn run_agent(agent: &str, config: &str, x_token: Option<&str>) -> Result<AgentResults> {
+    let mut endpoint = Endpoint::from_shared(agent.to_owned())?;
+    if agent.starts_with("https") {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+    }
+    let mut grpc = Grpc::new(endpoint.connect().await?);
+    let mut request = Request::new(RunRequest {
+        config: config.to_owned(),
+    });
+    if let Some(x_token) = x_token {
+        request.metadata_mut().insert("x-token", x_token.parse()?);
+    }
+    grpc.ready().await?;
+    let response: RunResponse = grpc
+        .unary(
+            request,
+            PathAndQuery::from_static(RUN_PATH),
+            ProstCodec::default(),
+        )
+        .await
+        .map_err(|status| anyhow::anyhow!("{:?}: {}", status.code(), status.message()))?
+        .into_inner();
+    Ok(AgentResults {
+        agent: agent.to_owned(),
+        environment: serde_json::from_str(&response.environment)?,
+        results: serde_json::from_str(&response.results)?,
+    })
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let config = fs::read_to_string(&args.config)?;
+    println!(
+        "Dispatch {} to {} agents",
+        args.config.display(),
+        args.agents.len()
+    );
+    let responses = join_all(
+        args.agents
+            .iter()
+            .map(|agent| run_agent(agent, &config, args.x_token.as_deref())),
+    )
+    .await;
+
+    let mut agents = vec![];
+    for (agent, response) in args.agents.iter().zip(responses) {
+        match response {
+            Ok(response) => agents.push(response),
+            Err(error) => println!("WARNING: agent {} failed: {:#}", agent, error),
+        }
+    }
+    anyhow::ensure!(!agents.is_empty(), "all agents failed");
+
+    // time per Pubkey of every agent by benchmark and parameters
+    let mut per_op_ns = BTreeMap::<String, Vec<Option<f64>>>::new();
+    for (index, agent) in agents.iter().enumerate() {
+        if !global.quiet {
+            println!(
+                "Agent {}: {}, {}, cpus: {}",
+                agent.agent,
+                agent.host(),
+                agent.environment["cpu"].as_str().unwrap_or("unknown cpu"),
+                agent.environment["cpus"]
+            );
+        }
+        for result in serde_json::from_value::<Vec<BenchResult>>(agent.results.clone())? {
+            per_op_ns
+                .entry(result.key())
+                .or_insert_with(|| vec![None; agents.len()])[index] = Some(result.per_op_ns());
+        }
+    }
+    for (key, values) in per_op_ns.iter() {
+        let base = values.iter().flatten().next().copied().unwrap_or_default();
+        let values = agents
+            .iter()
+            .zip(values)
+            .map(|(agent, value)| match value {
+                Some(value) => format!(
+                    "{:.2}ns ({}, {:+.2}%)",
+                    value,
+                    agent.host(),
+                    (value / base.max(f64::MIN_POSITIVE) - 1.0) * 100.0
+                ),
+                None => format!("- ({})", agent.host()),
+            })
+            .collect::<Vec<_>>();
+        println!("{}: per pubkey {}", key, values.join(" / "));
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_vec_pretty(&agents)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,352 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    futures::future::try_join_all,
+    solana_accounts_filter_bench::{dataset::Block, Dataset},
+    solana_client::{
+        client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+        nonblocking::rpc_client::RpcClient,
+        rpc_custom_error::{
+            JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+            JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED,
+            JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+            JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
+        },
+        rpc_request::RpcError,
+    },
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        commitment_config::CommitmentConfig,
+        message::VersionedMessage,
+    },
+    solana_transaction_status::UiTransactionEncoding,
+    std::{
+        collections::{BTreeMap, HashSet},
+        future::Future,
+        path::PathBuf,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+    tokio::{
+        sync::Mutex,
+        time::{sleep, Duration},
+    },
+    tracing::{info_span, Instrument},
+};
+
+/// Download blocks and extract Pubkeys into the dataset.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Optional slot from where collect Pubkeys, backwise. By default latest finalized slot.
+    #[clap(short, long)]
+    from: Option<Slot>,
+
+    /// Number of concurrent downloads of blocks.
+    #[clap(short = 't', long, default_value_t = 3)]
+    concurrency: Slot,
+
+    /// Number of seconds for collecting Pubkeys from the slots.
+    #[clap(short, long, default_value_t = 900)] // 15min.
+    count: UnixTimestamp,
+
+    /// Seconds between retries of failed requests, every request is retried 5 times.
+    #[clap(long, default_value_t = 10)]
+    retry_delay: u64,
+
+    /// Optional archival RPC for blocks out of history of `--rpc` (cleaned up, missing in
+    /// long-term storage or minimum context slot not reached), requested without retries first.
+    #[clap(long)]
+    archive_rpc: Option<String>,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    out: PathBuf,
+
+    /// Optional destination for the data and manifest with checksums: `s3://bucket/prefix` or
+    /// `gs://bucket/prefix`.
+    #[cfg(feature = "upload")]
+    #[clap(long)]
+    upload: Option<crate::upload::UploadUrl>,
+}
+
+/// Errors of blocks pruned from the node, older blocks are pruned too.
+const PRUNED_ERRORS: [i64; 3] = [
+    JSON_RPC_SERVER_ERROR_BLOCK_CLEANED_UP,
+    JSON_RPC_SERVER_ERROR_LONG_TERM_STORAGE_SLOT_SKIPPED,
+    JSON_RPC_SERVER_ERROR_TRANSACTION_HISTORY_NOT_AVAILABLE,
+];
+
+fn response_code(error: &ClientError) -> Option<i64> {
+    match error.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => Some(*code),
+        _ => None,
+    }
+}
+
+/// RPC with retries of failed requests and fallback to the archival node for blocks out of
+/// history of the main node.
+struct Rpc {
+    client: Arc<RpcClient>,
+    archive: Option<Arc<RpcClient>>,
+    /// Slots below are pruned from the main node and requested only from the archival node.
+    pruned_below: AtomicU64,
+    retry_delay: Duration,
+    quiet: bool,
+}
+
+impl Rpc {
+    async fn request<T, F, Fut>(&self, slot: Slot, what: &str, request: F) -> Result<T>
+    where
+        F: Fn(Arc<RpcClient>) -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut attempts = 5;
+        let mut archive = false;
+        loop {
+            archive |= slot < self.pruned_below.load(Ordering::Relaxed);
+            let client = match &self.archive {
+                Some(client) if archive => client,
+                _ => &self.client,
+            };
+            let error = match request(Arc::clone(client)).await {
+                Ok(value) => break Ok(value),
+                Err(error) => error,
+            };
+
+            let code = response_code(&error);
+            let pruned = code.is_some_and(|code| PRUNED_ERRORS.contains(&code));
+            let behind = code == Some(JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED);
+            if (pruned || behind) && !archive && self.archive.is_some() {
+                if pruned {
+                    self.pruned_below.fetch_max(slot + 1, Ordering::Relaxed);
+                }
+                archive = true;
+                tracing::warn!(%error, "request {} from archive", what);
+                if !self.quiet {
+                    println!("request {} from archive: {}", what, error);
+                }
+                continue;
+            }
+
+            if attempts == 0 {
+                let error = anyhow::Error::from(error);
+                break Err(if (pruned || behind) && self.archive.is_none() {
+                    error.context(format!(
+                        "failed to {}: out of history of the node, set --archive-rpc",
+                        what
+                    ))
+                } else {
+                    error
+                });
+            }
+            attempts -= 1;
+            tracing::warn!(%error, "failed to {}", what);
+            if !self.quiet {
+                println!("failed to {}: {}", what, error);
+            }
+            sleep(self.retry_delay).await;
+        }
+    }
+}
+
+struct SlotsInner {
+    slots: Vec<Slot>,
+    end_slot: Option<Slot>,
+}
+
+struct Slots {
+    rpc: Arc<Rpc>,
+    inner: Mutex<SlotsInner>,
+    block_time_stop: UnixTimestamp,
+    quiet: bool,
+}
+
+impl Slots {
+    fn new(rpc: Arc<Rpc>, end_slot: Slot, block_time_stop: UnixTimestamp, quiet: bool) -> Self {
+        Self {
+            rpc,
+            inner: Mutex::new(SlotsInner {
+                slots: vec![],
+                end_slot: Some(end_slot),
+            }),
+            block_time_stop,
+            quiet,
+        }
+    }
+
+    async fn next(&self) -> Result<Option<Slot>> {
+        let mut inner = self.inner.lock().await;
+        if let Some(slot) = inner.slots.pop() {
+            return Ok(Some(slot));
+        }
+
+        Ok(match inner.end_slot {
+            Some(end_slot) => {
+                // slots close to genesis are requested down to the slot 0
+                let start_slot = end_slot.saturating_sub(1_000);
+                if !self.quiet {
+                    println!("Request slots [{}, {}]", start_slot, end_slot);
+                }
+
+                let slots = self
+                    .rpc
+                    .request(start_slot, "get slots", |rpc| async move {
+                        rpc.get_blocks(start_slot, Some(end_slot)).await
+                    })
+                    .instrument(info_span!("get_blocks", start_slot, end_slot))
+                    .await?;
+
+                inner.slots = slots;
+                inner.end_slot = match inner.slots.first() {
+                    Some(0) | None => None,
+                    Some(slot) => Some(*slot - 1),
+                };
+                inner.slots.pop()
+            }
+            None => None,
+        })
+    }
+
+    async fn remove_by_block_time(&self, slot: Slot, block_time: UnixTimestamp) {
+        if block_time < self.block_time_stop {
+            let mut inner = self.inner.lock().await;
+            inner.slots = inner
+                .slots
+                .iter()
+                .filter(|islot| **islot >= slot)
+                .cloned()
+                .collect();
+            inner.end_slot = None;
+        }
+    }
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    // credentials are checked before the download
+    #[cfg(feature = "upload")]
+    let mut uploader = args
+        .upload
+        .clone()
+        .map(|url| crate::upload::Uploader::new(url, "download"))
+        .transpose()?;
+    let rpc = Arc::new(Rpc {
+        client: Arc::new(global.rpc_client()?),
+        archive: args.archive_rpc.map(|url| {
+            Arc::new(RpcClient::new_with_commitment(
+                url,
+                CommitmentConfig::finalized(),
+            ))
+        }),
+        pruned_below: AtomicU64::new(0),
+        retry_delay: Duration::from_secs(args.retry_delay),
+        quiet: global.quiet,
+    });
+    let slot = match args.from {
+        Some(slot) => slot,
+        None => rpc.client.get_slot().await?,
+    };
+    let block_time_start = rpc
+        .request(slot, "get block time", |rpc| async move {
+            rpc.get_block_time(slot).await
+        })
+        .await?;
+
+    let slots = Arc::new(Slots::new(
+        Arc::clone(&rpc),
+        slot,
+        block_time_start - args.count,
+        global.quiet,
+    ));
+    let blocks = Arc::new(Mutex::new(BTreeMap::new()));
+
+    try_join_all((0..args.concurrency).map(|worker| {
+        let rpc = Arc::clone(&rpc);
+        let slots = Arc::clone(&slots);
+        let blocks = Arc::clone(&blocks);
+        async move {
+            while let Some(slot) = slots.next().await? {
+                let what = format!("get block {}", slot);
+                let block = rpc
+                    .request(slot, &what, |rpc| async move {
+                        let encoding = UiTransactionEncoding::Base64;
+                        rpc.get_block_with_encoding(slot, encoding).await
+                    })
+                    .instrument(info_span!("get_block", slot))
+                    .await?;
+
+                let block_time = match block.block_time {
+                    Some(block_time) => block_time,
+                    None => continue,
+                };
+                if !global.quiet {
+                    println!(
+                        "Download block {} with time {}, stop time {}, left {}",
+                        slot,
+                        block_time,
+                        slots.block_time_stop,
+                        block_time - slots.block_time_stop
+                    );
+                }
+
+                if block_time < slots.block_time_stop {
+                    slots.remove_by_block_time(slot, block_time).await;
+                    continue;
+                }
+
+                // collect accounts
+                let pubkeys = block
+                    .transactions
+                    .iter()
+                    .flat_map(|transaction| {
+                        transaction
+                            .transaction
+                            .decode()
+                            .map(|transaction| match transaction.message {
+                                VersionedMessage::Legacy(message) => message.account_keys,
+                                VersionedMessage::V0(message) => message.account_keys,
+                            })
+                            .unwrap_or_default()
+                    })
+                    .collect::<HashSet<_>>()
+                    .into_iter()
+                    .collect();
+
+                let mut blocks = blocks.lock().await;
+                blocks.insert(
+                    slot,
+                    Block {
+                        block_time,
+                        pubkeys,
+                    },
+                );
+            }
+            Ok::<(), anyhow::Error>(())
+        }
+        .instrument(info_span!("download_worker", worker))
+    }))
+    .await?;
+
+    let dataset = Dataset {
+        blocks: Arc::try_unwrap(blocks).expect("one ref").into_inner(),
+        ..Default::default()
+    };
+    #[cfg_attr(not(feature = "upload"), allow(unused_variables))]
+    let files = info_span!("save_dataset", blocks = dataset.blocks.len())
+        .in_scope(|| global.save_dataset(&dataset, &args.out))?;
+
+    println!(
+        "Total {} blocks, with {} pubkeys",
+        dataset.blocks.len(),
+        dataset.unique_pubkeys()
+    );
+    #[cfg(feature = "upload")]
+    if let Some(uploader) = &mut uploader {
+        for file in &files {
+            uploader.upload(file).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use {
+    crate::{latency::LatencyStats, parse_fraction, GlobalArgs},
+    anyhow::Result,
+    serde::Serialize,
+    solana_accounts_filter_bench::{
+        bench::BenchConfig,
+        geyser::GeyserConfig,
+        grpc::{
+            proto::{SubscribeRequest, SubscribeRequestFilterAccounts, UpdateOneof},
+            GeyserClient,
+        },
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashSet,
+        fs,
+        path::PathBuf,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    tokio::time::{timeout_at, Instant},
+};
+
+/// Subscribe to replay server (or geyser endpoint) and measure end-to-end latency of updates.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// gRPC endpoint of `replay` or Yellowstone geyser plugin.
+    #[clap(long, default_value = "http://127.0.0.1:10000")]
+    endpoint: String,
+
+    /// Optional `x-token` for authentication.
+    #[clap(long)]
+    x_token: Option<String>,
+
+    /// Geyser plugin YAML (or JSON) config with named accounts filters to subscribe.
+    #[clap(long, parse(from_os_str))]
+    geyser_config: Option<PathBuf>,
+
+    /// Input file with the data for filter of `--set-size` Pubkeys, if geyser config is not set.
+    #[clap(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Number of Pubkeys in the filter.
+    #[clap(long, default_value_t = 1_000)]
+    set_size: usize,
+
+    /// Fraction of unique Pubkeys from the data added to the filter, rest is random.
+    #[clap(long, default_value_t = 1.0, parse(try_from_str = parse_fraction))]
+    hit_rate: f64,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Subscribe to slot updates.
+    #[clap(long)]
+    slots: bool,
+
+    /// Number of concurrent subscriptions with the same filters.
+    #[clap(long, default_value_t = 1)]
+    subscribers: usize,
+
+    /// Stop after given seconds, by default when stream is finished.
+    #[clap(long)]
+    duration: Option<u64>,
+
+    /// Optional output file for results in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+/// Counters of one or all subscriptions.
+#[derive(Debug, Default)]
+struct DriveStats {
+    updates: usize,
+    accounts: usize,
+    slots: usize,
+    /// Updates missing by gaps in `write_version`, sequential only with `replay`.
+    dropped: u64,
+    /// Time from `created_at` of the update to receive, in ns.
+    latencies: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct DriveReport {
+    endpoint: String,
+    subscribers: usize,
+    elapsed_ns: u64,
+    updates: usize,
+    accounts: usize,
+    slots: usize,
+    dropped: u64,
+    latency: Option<LatencyStats>,
+}
+
+impl Args {
+    fn subscribe_request(&self, global: &GlobalArgs) -> Result<SubscribeRequest> {
+        let mut request = match (&self.geyser_config, &self.input) {
+            (Some(path), _) => SubscribeRequest::from(&GeyserConfig::load(path)?),
+            (None, Some(input)) => {
+                let dataset = global.load_dataset(input)?;
+                let set = BenchConfig::builder(&dataset)
+                    .set_size(self.set_size)
+                    .hit_rate(self.hit_rate)
+                    .seed(self.seed)
+                    .build()?
+                    .fill(HashSet::<Pubkey>::with_capacity(self.set_size));
+                let mut request = SubscribeRequest::default();
+                request.accounts.insert(
+                    "safb".to_owned(),
+                    SubscribeRequestFilterAccounts {
+                        account: set.iter().map(ToString::to_string).collect(),
+                        ..Default::default()
+                    },
+                );
+                request
+            }
+            (None, None) => anyhow::bail!("filters are required: --geyser-config or --input"),
+        };
+        if self.slots {
+            request.slots.insert("safb".to_owned(), Default::default());
+        }
+        Ok(request)
+    }
+}
+
+async fn drive(
+    mut client: GeyserClient,
+    request: SubscribeRequest,
+    deadline: Option<Instant>,
+) -> Result<DriveStats> {
+    let mut updates = client.subscribe(request).await?;
+    let mut stats = DriveStats::default();
+    let mut write_version = 0;
+    loop {
+        let update = match deadline {
+            Some(deadline) => match timeout_at(deadline, updates.message()).await {
+                Ok(update) => update?,
+                Err(_elapsed) => break,
+            },
+            None => updates.message().await?,
+        };
+        let update = match update {
+            Some(update) => update,
+            None => break,
+        };
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        if let Some(created_at) = update.created_at {
+            let created_at = Duration::new(created_at.seconds as u64, created_at.nanos as u32);
+            // clocks of remote server can be ahead
+            stats
+                .latencies
+                .push(now.saturating_sub(created_at).as_nanos() as u64);
+        }
+        stats.updates += 1;
+        match update.update_oneof {
+            Some(UpdateOneof::Account(account)) => {
+                stats.accounts += 1;
+                if let Some(info) = account.account {
+                    stats.dropped += info.write_version.saturating_sub(write_version + 1);
+                    write_version = info.write_version;
+                }
+            }
+            Some(UpdateOneof::Slot(_)) => stats.slots += 1,
+            None => {}
+        }
+    }
+    Ok(stats)
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    anyhow::ensure!(args.subscribers > 0, "subscribers should be positive");
+    let request = args.subscribe_request(global)?;
+    let client = GeyserClient::connect(&args.endpoint, args.x_token.as_deref()).await?;
+
+    let start = Instant::now();
+    let deadline = args.duration.map(|secs| start + Duration::from_secs(secs));
+    let tasks = (0..args.subscribers)
+        .map(|_| tokio::spawn(drive(client.clone(), request.clone(), deadline)))
+        .collect::<Vec<_>>();
+    let mut stats = DriveStats::default();
+    for task in tasks {
+        let subscriber = task.await??;
+        if !global.quiet {
+            println!(
+                "Subscriber updates: {}, accounts: {}, slots: {}, dropped: {}",
+                subscriber.updates, subscriber.accounts, subscriber.slots, subscriber.dropped
+            );
+        }
+        stats.updates += subscriber.updates;
+        stats.accounts += subscriber.accounts;
+        stats.slots += subscriber.slots;
+        stats.dropped += subscriber.dropped;
+        stats.latencies.extend(subscriber.latencies);
+    }
+
+    let report = DriveReport {
+        endpoint: args.endpoint.clone(),
+        subscribers: args.subscribers,
+        elapsed_ns: start.elapsed().as_nanos() as u64,
+        updates: stats.updates,
+        accounts: stats.accounts,
+        slots: stats.slots,
+        dropped: stats.dropped,
+        latency: LatencyStats::new(stats.latencies),
+    };
+    println!(
+        "Subscribers: {}, elapsed: {:?}, updates: {}, accounts: {}, slots: {}, dropped: {}",
+        report.subscribers,
+        Duration::from_nanos(report.elapsed_ns),
+        report.updates,
+        report.accounts,
+        report.slots,
+        report.dropped
+    );
+    if let Some(latency) = &report.latency {
+        println!("End-to-end latency {}", latency.format());
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
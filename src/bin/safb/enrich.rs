@@ -0,0 +1,107 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    futures::stream::{self, StreamExt, TryStreamExt},
+    solana_accounts_filter_bench::dataset::AccountOwner,
+    solana_client::nonblocking::rpc_client::RpcClient,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::BTreeMap, path::PathBuf},
+};
+
+/// Maximum number of accounts in one `getMultipleAccounts` request.
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Add owners of accounts to the dataset, for benchmarks of owner filters.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Number of concurrent `getMultipleAccounts` requests.
+    #[clap(short = 't', long, default_value_t = 3)]
+    concurrency: usize,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+/// Current owners of `pubkeys` requested with `getMultipleAccounts`, accounts which do not
+/// exist are skipped.
+pub async fn fetch_owners(
+    rpc: &RpcClient,
+    pubkeys: &[Pubkey],
+    concurrency: usize,
+    quiet: bool,
+) -> Result<BTreeMap<Pubkey, AccountOwner>> {
+    let batches = stream::iter(
+        pubkeys
+            .chunks(MAX_MULTIPLE_ACCOUNTS)
+            .map(|chunk| async move {
+                let accounts = rpc.get_multiple_accounts(chunk).await?;
+                let owners = chunk
+                    .iter()
+                    .zip(accounts)
+                    .filter_map(|(pubkey, account)| {
+                        account.map(|account| {
+                            let owner = AccountOwner {
+                                owner: account.owner,
+                                executable: account.executable,
+                            };
+                            (*pubkey, owner)
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                Ok::<_, anyhow::Error>((chunk.len(), owners))
+            }),
+    )
+    .buffered(concurrency.max(1));
+
+    let mut owners = BTreeMap::new();
+    let mut requested = 0;
+    futures::pin_mut!(batches);
+    for index in 1.. {
+        let (len, batch) = match batches.try_next().await? {
+            Some(batch) => batch,
+            None => break,
+        };
+        owners.extend(batch);
+        requested += len;
+        if !quiet && (index % 100 == 0 || requested == pubkeys.len()) {
+            println!(
+                "Requested owners of {} / {} pubkeys",
+                requested,
+                pubkeys.len()
+            );
+        }
+    }
+    Ok(owners)
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let mut dataset = global.load_dataset(&args.input)?;
+    let pubkeys = dataset.key_counts().into_keys().collect::<Vec<_>>();
+
+    let rpc = global.rpc_client()?;
+    dataset.accounts = fetch_owners(&rpc, &pubkeys, args.concurrency, global.quiet).await?;
+    global.save_dataset(&dataset, &args.out)?;
+
+    let executable = dataset
+        .accounts
+        .values()
+        .filter(|account| account.executable)
+        .count();
+    let mut owners = BTreeMap::<Pubkey, usize>::new();
+    for account in dataset.accounts.values() {
+        *owners.entry(account.owner).or_default() += 1;
+    }
+    println!(
+        "Total {} pubkeys, {} accounts exist ({} executable) with {} owners",
+        pubkeys.len(),
+        dataset.accounts.len(),
+        executable,
+        owners.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use {serde::Serialize, std::fs};
+
+/// Environment of the machine running benchmarks, from `/proc` on Linux.
+#[derive(Debug, Clone, Serialize)]
+pub struct Environment {
+    pub version: &'static str,
+    pub host: Option<String>,
+    /// OS with kernel release and architecture.
+    pub os: String,
+    pub cpu: Option<String>,
+    pub cpus: Option<usize>,
+    pub memory: Option<String>,
+}
+
+impl Environment {
+    pub fn collect() -> Self {
+        let os = match fs::read_to_string("/proc/sys/kernel/osrelease") {
+            Ok(release) => format!("{} {}", std::env::consts::OS, release.trim()),
+            Err(_) => std::env::consts::OS.to_owned(),
+        };
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            host: fs::read_to_string("/proc/sys/kernel/hostname")
+                .ok()
+                .map(|hostname| hostname.trim().to_owned()),
+            os: format!("{} ({})", os, std::env::consts::ARCH),
+            cpu: proc_value("/proc/cpuinfo", "model name"),
+            cpus: std::thread::available_parallelism()
+                .ok()
+                .map(|cpus| cpus.get()),
+            memory: proc_value("/proc/meminfo", "MemTotal"),
+        }
+    }
+}
+
+/// Value of `key` in `/proc` file with `key: value` lines.
+fn proc_value(path: &str, key: &str) -> Option<String> {
+    fs::read_to_string(path).ok()?.lines().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        (name.trim() == key).then(|| value.trim().to_owned())
+    })
+}
@@ -0,0 +1,165 @@
+use {
+    crate::{parse_fraction, GlobalArgs},
+    anyhow::Result,
+    rand::{RngCore, SeedableRng},
+    rand_chacha::ChaCha8Rng,
+    solana_accounts_filter_bench::{
+        bench::BenchConfig,
+        keyset::{BloomFilter, KeySet, XorFilter},
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, f64::consts::LN_2, path::PathBuf},
+};
+
+/// Fingerprint bits of `XorFilter`.
+const XOR_FINGERPRINT_BITS: u32 = 8;
+
+/// Slots of xor filter per key.
+const XOR_SLOTS_PER_KEY: f64 = 1.23;
+
+/// Recommend bloom and xor filter parameters for target false positive rate.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Target false positive rate.
+    #[clap(long, default_value_t = 0.01)]
+    fpr: f64,
+
+    /// Number of keys in the filter, by default number of unique Pubkeys of the dataset.
+    #[clap(long)]
+    set_size: Option<usize>,
+
+    /// Validate estimate: build filters and count false positives on Pubkeys not in the set.
+    #[clap(long)]
+    validate: bool,
+
+    /// Fraction of unique Pubkeys from the data added to the set for validation, rest is random.
+    #[clap(long, default_value_t = 1.0, parse(try_from_str = parse_fraction))]
+    hit_rate: f64,
+
+    /// Minimum number of negative lookups for validation, dataset Pubkeys not in the set are
+    /// complemented with random Pubkeys.
+    #[clap(long, default_value_t = 1_000_000)]
+    samples: usize,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+}
+
+/// Expected false positive rate of bloom filter with `bits_per_key` bits and `hashes` hashes.
+fn bloom_fpr(bits_per_key: usize, hashes: usize) -> f64 {
+    (1.0 - (-(hashes as f64) / bits_per_key as f64).exp()).powi(hashes as i32)
+}
+
+/// Fraction of `negatives` reported by `filter` as present.
+fn measure_fpr<F: KeySet>(filter: &F, negatives: &[Pubkey]) -> f64 {
+    let false_positives = negatives
+        .iter()
+        .filter(|pubkey| filter.contains(&pubkey.to_bytes()))
+        .count();
+    false_positives as f64 / negatives.len().max(1) as f64
+}
+
+fn format_bytes(bytes: f64) -> String {
+    format!("{:.2} MiB", bytes / 1024.0 / 1024.0)
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    anyhow::ensure!(
+        args.fpr > 0.0 && args.fpr < 1.0,
+        "false positive rate should be in range (0, 1)"
+    );
+    let dataset = global.load_dataset(&args.input)?;
+    let set_size = args.set_size.unwrap_or_else(|| dataset.unique_pubkeys());
+
+    // optimal bloom filter: `-ln(p) / ln(2)^2` bits and `bits * ln(2)` hashes per key,
+    // `BloomFilter` derives hashes from integer bits per key the same way
+    let bits_per_key = (-args.fpr.ln() / (LN_2 * LN_2)).ceil() as usize;
+    let hashes = ((bits_per_key * 693 + 500) / 1000).max(1);
+    let bloom_bytes = (set_size * bits_per_key).div_ceil(64) as f64 * 8.0;
+    println!(
+        "Set size: {}, target false positive rate: {}",
+        set_size, args.fpr
+    );
+    println!(
+        "Bloom: bits per key: {}, hashes: {}, expected fpr: {:.6}, memory: {}",
+        bits_per_key,
+        hashes,
+        bloom_fpr(bits_per_key, hashes),
+        format_bytes(bloom_bytes)
+    );
+
+    let xor_fpr = 0.5f64.powi(XOR_FINGERPRINT_BITS as i32);
+    let xor_bytes = set_size as f64 * XOR_SLOTS_PER_KEY * XOR_FINGERPRINT_BITS as f64 / 8.0;
+    println!(
+        "Xor: fingerprint bits: {}, expected fpr: {:.6}, memory: {}",
+        XOR_FINGERPRINT_BITS,
+        xor_fpr,
+        format_bytes(xor_bytes)
+    );
+    if xor_fpr > args.fpr {
+        let bits = (-args.fpr.log2()).ceil();
+        println!(
+            "Xor with {}-bit fingerprints (not implemented) is required for target rate, memory: {}",
+            bits,
+            format_bytes(set_size as f64 * XOR_SLOTS_PER_KEY * bits / 8.0)
+        );
+    }
+
+    if !args.validate {
+        return Ok(());
+    }
+
+    let set = BenchConfig::builder(&dataset)
+        .set_size(set_size)
+        .hit_rate(args.hit_rate)
+        .seed(args.seed)
+        .build()?
+        .fill(HashSet::<Pubkey>::with_capacity(set_size));
+    let mut negatives = dataset
+        .keys()
+        .map(|(_slot, pubkey)| *pubkey)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .filter(|pubkey| !set.contains(pubkey))
+        .collect::<Vec<_>>();
+    let mut rng = ChaCha8Rng::seed_from_u64(args.seed ^ 0x5eed);
+    while negatives.len() < args.samples {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        let pubkey = Pubkey::new_from_array(bytes);
+        if !set.contains(&pubkey) {
+            negatives.push(pubkey);
+        }
+    }
+
+    let mut bloom = BloomFilter::with_bits_per_key(set.len(), bits_per_key);
+    let xor = XorFilter::from_keys(set.iter().map(|pubkey| pubkey.to_bytes()));
+    for pubkey in set.iter() {
+        KeySet::<32>::insert(&mut bloom, pubkey.to_bytes());
+    }
+    anyhow::ensure!(
+        set.iter().all(|pubkey| {
+            let key = pubkey.to_bytes();
+            bloom.contains(&key) && xor.contains(&key)
+        }),
+        "filters have false negatives"
+    );
+
+    println!("Validation on {} negative lookups:", negatives.len());
+    println!(
+        "  bloom: measured fpr: {:.6}, memory: {}",
+        measure_fpr::<BloomFilter>(&bloom, &negatives),
+        format_bytes(KeySet::<32>::memory_usage(&bloom) as f64)
+    );
+    println!(
+        "  xor: measured fpr: {:.6}, memory: {}",
+        measure_fpr(&xor, &negatives),
+        format_bytes(xor.memory_usage() as f64)
+    );
+    Ok(())
+}
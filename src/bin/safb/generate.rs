@@ -0,0 +1,80 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    solana_accounts_filter_bench::dataset::{GenerateConfig, Popularity},
+    solana_sdk::clock::{Slot, UnixTimestamp},
+    std::{ops::RangeInclusive, path::PathBuf, time::SystemTime},
+};
+
+/// Generate synthetic dataset, without RPC access.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Number of blocks (consecutive slots).
+    #[clap(long, default_value_t = 1_000)]
+    blocks: usize,
+
+    /// Unique Pubkeys per block, number or range (e.g. `500-1500`).
+    #[clap(long, default_value = "1000", parse(try_from_str = parse_range))]
+    keys_per_block: RangeInclusive<usize>,
+
+    /// Number of Pubkeys in the pool from which blocks are filled.
+    #[clap(long, default_value_t = 1_000_000)]
+    unique_keys: usize,
+
+    /// Zipf exponent of Pubkeys popularity (e.g. `1.0`), uniform if not set.
+    #[clap(long)]
+    zipf: Option<f64>,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Slot of the first block.
+    #[clap(long, default_value_t = 0)]
+    start_slot: Slot,
+
+    /// Unix timestamp of the first block.
+    #[clap(long, default_value_t = 1_650_000_000)]
+    start_time: UnixTimestamp,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    out: PathBuf,
+}
+
+fn parse_range(value: &str) -> Result<RangeInclusive<usize>> {
+    let (start, end) = value.split_once('-').unwrap_or((value, value));
+    let range = start.trim().parse()?..=end.trim().parse()?;
+    anyhow::ensure!(!range.is_empty(), "empty range: {}", value);
+    Ok(range)
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let config = GenerateConfig {
+        blocks: args.blocks,
+        keys_per_block: args.keys_per_block,
+        unique_keys: args.unique_keys,
+        popularity: match args.zipf {
+            Some(exponent) => Popularity::Zipf { exponent },
+            None => Popularity::Uniform,
+        },
+        seed: args.seed,
+        start_slot: args.start_slot,
+        start_time: args.start_time,
+    };
+
+    let ts = SystemTime::now();
+    let dataset = config.generate();
+    if !global.quiet {
+        println!("Generated in: {:?}", ts.elapsed()?);
+    }
+    global.save_dataset(&dataset, &args.out)?;
+
+    println!(
+        "Total {} blocks, with {} pubkeys",
+        dataset.blocks.len(),
+        dataset.unique_pubkeys()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use {
+    anyhow::Result,
+    std::{
+        fmt::Write,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// Options of InfluxDB export, shared by subcommands.
+#[derive(Debug, clap::Args)]
+pub struct InfluxArgs {
+    /// Optional InfluxDB URL for results in line protocol, e.g. `http://127.0.0.1:8086`.
+    #[clap(long, requires = "influx-bucket")]
+    influx_url: Option<String>,
+
+    /// InfluxDB bucket (`database/retention-policy` for InfluxDB 1.8).
+    #[clap(long, requires = "influx-url")]
+    influx_bucket: Option<String>,
+
+    /// Optional InfluxDB organization.
+    #[clap(long)]
+    influx_org: Option<String>,
+
+    /// Optional InfluxDB API token (`user:password` for InfluxDB 1.8).
+    #[clap(long)]
+    influx_token: Option<String>,
+}
+
+impl InfluxArgs {
+    pub fn client(&self) -> Option<Influx> {
+        let (url, bucket) = (self.influx_url.as_ref()?, self.influx_bucket.as_ref()?);
+        let mut query = vec![("bucket", bucket.clone()), ("precision", "ns".to_owned())];
+        if let Some(org) = &self.influx_org {
+            query.push(("org", org.clone()));
+        }
+        Some(Influx {
+            client: reqwest::Client::new(),
+            url: format!("{}/api/v2/write", url.trim_end_matches('/')),
+            query,
+            token: self.influx_token.clone(),
+        })
+    }
+}
+
+/// Writer of points to InfluxDB v2 write API (also supported by InfluxDB 1.8).
+#[derive(Debug, Clone)]
+pub struct Influx {
+    client: reqwest::Client,
+    url: String,
+    query: Vec<(&'static str, String)>,
+    token: Option<String>,
+}
+
+impl Influx {
+    /// Write points, points without fields (all values were not finite or out of range) are
+    /// invalid in line protocol and skipped.
+    pub async fn write(&self, points: &[Point]) -> Result<()> {
+        let lines = points.iter().filter_map(Point::line).collect::<Vec<_>>();
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let body = lines.join("\n");
+        let mut request = self.client.post(&self.url).query(&self.query).body(body);
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("Token {}", token));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|error| anyhow::anyhow!("failed to write to InfluxDB: {}", error))?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("failed to write to InfluxDB: {}, {}", status, text.trim());
+        }
+        Ok(())
+    }
+
+    /// Write points, failures are printed, so long runs are not interrupted by the database.
+    pub async fn write_or_warn(&self, points: &[Point]) {
+        if let Err(error) = self.write(points).await {
+            println!("WARNING: {}", error);
+        }
+    }
+}
+
+/// Point of line protocol: `measurement,tag=value field=value timestamp`.
+#[derive(Debug)]
+pub struct Point {
+    measurement: &'static str,
+    tags: Vec<(&'static str, String)>,
+    fields: Vec<(&'static str, Field)>,
+    /// Nanoseconds since Unix epoch.
+    timestamp: u128,
+}
+
+#[derive(Debug)]
+enum Field {
+    Float(f64),
+    Integer(i64),
+}
+
+impl Point {
+    /// Point with the current time.
+    pub fn new(measurement: &'static str) -> Self {
+        Self {
+            measurement,
+            tags: vec![],
+            fields: vec![],
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        }
+    }
+
+    pub fn tag(mut self, key: &'static str, value: impl ToString) -> Self {
+        self.tags.push((key, value.to_string()));
+        self
+    }
+
+    pub fn float(mut self, key: &'static str, value: f64) -> Self {
+        if value.is_finite() {
+            self.fields.push((key, Field::Float(value)));
+        }
+        self
+    }
+
+    pub fn integer(mut self, key: &'static str, value: impl TryInto<i64>) -> Self {
+        if let Ok(value) = value.try_into() {
+            self.fields.push((key, Field::Integer(value)));
+        }
+        self
+    }
+
+    /// Line of the point, `None` without fields.
+    fn line(&self) -> Option<String> {
+        if self.fields.is_empty() {
+            return None;
+        }
+        let mut line = escape(self.measurement, &[',', ' ']);
+        for (key, value) in self.tags.iter().filter(|(_, value)| !value.is_empty()) {
+            let _ = write!(line, ",{}={}", key, escape(value, &[',', '=', ' ']));
+        }
+        for (index, (key, value)) in self.fields.iter().enumerate() {
+            let separator = if index == 0 { ' ' } else { ',' };
+            let _ = match value {
+                Field::Float(value) => write!(line, "{}{}={}", separator, key, value),
+                Field::Integer(value) => write!(line, "{}{}={}i", separator, key, value),
+            };
+        }
+        let _ = write!(line, " {}", self.timestamp);
+        Some(line)
+    }
+}
+
+fn escape(value: &str, chars: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        if ch == '\\' || chars.contains(&ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
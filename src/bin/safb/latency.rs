@@ -0,0 +1,43 @@
+use {serde::Serialize, std::time::Duration};
+
+/// Distribution of latencies in ns.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LatencyStats {
+    pub min_ns: u64,
+    pub mean_ns: u64,
+    pub p50_ns: u64,
+    pub p90_ns: u64,
+    pub p99_ns: u64,
+    pub max_ns: u64,
+}
+
+impl LatencyStats {
+    pub fn new(mut latencies: Vec<u64>) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        latencies.sort_unstable();
+        let percentile = |p: f64| latencies[((latencies.len() - 1) as f64 * p).round() as usize];
+        Some(Self {
+            min_ns: latencies[0],
+            mean_ns: latencies.iter().sum::<u64>() / latencies.len() as u64,
+            p50_ns: percentile(0.5),
+            p90_ns: percentile(0.9),
+            p99_ns: percentile(0.99),
+            max_ns: latencies[latencies.len() - 1],
+        })
+    }
+
+    /// `min: .., mean: .., p50: .., p90: .., p99: .., max: ..` with durations.
+    pub fn format(&self) -> String {
+        format!(
+            "min: {:?}, mean: {:?}, p50: {:?}, p90: {:?}, p99: {:?}, max: {:?}",
+            Duration::from_nanos(self.min_ns),
+            Duration::from_nanos(self.mean_ns),
+            Duration::from_nanos(self.p50_ns),
+            Duration::from_nanos(self.p90_ns),
+            Duration::from_nanos(self.p99_ns),
+            Duration::from_nanos(self.max_ns)
+        )
+    }
+}
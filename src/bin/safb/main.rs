@@ -0,0 +1,305 @@
+use {
+    anyhow::Result,
+    clap::{Parser, Subcommand},
+    solana_accounts_filter_bench::{
+        dataset::{DatasetFormat, DatasetManifest, ManifestConfig},
+        Dataset,
+    },
+    solana_cli_config::{Config, CONFIG_FILE},
+    solana_client::nonblocking::rpc_client::RpcClient,
+    solana_sdk::{
+        commitment_config::CommitmentConfig, pubkey::Pubkey, signature::read_keypair_file,
+    },
+    std::{
+        fs, io,
+        path::{Path, PathBuf},
+    },
+};
+
+#[cfg(feature = "orchestrate")]
+mod agent;
+mod analyze;
+mod bench;
+mod bench_rpc;
+mod compare;
+mod completions;
+mod convert;
+#[cfg(feature = "orchestrate")]
+mod dispatch;
+mod download;
+mod drive;
+mod enrich;
+mod environment;
+mod estimate;
+mod generate;
+mod influx;
+mod latency;
+mod merge;
+mod metrics;
+mod notify;
+#[cfg(feature = "otlp")]
+mod otlp;
+#[cfg(feature = "plot")]
+mod plot;
+mod prune;
+mod replay;
+#[cfg(feature = "plot")]
+mod report;
+mod results;
+#[cfg(feature = "sqlite")]
+mod results_db;
+#[cfg(feature = "postgres")]
+mod results_pg;
+mod serve;
+#[cfg(feature = "sqlite")]
+mod serve_results;
+mod subsample;
+mod top;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "upload")]
+mod upload;
+mod watch;
+
+#[cfg(feature = "alloc-counter")]
+#[global_allocator]
+static GLOBAL: solana_accounts_filter_bench::allocator::CountingAllocator =
+    solana_accounts_filter_bench::allocator::CountingAllocator;
+
+#[cfg(all(
+    not(feature = "alloc-counter"),
+    any(feature = "jemalloc", feature = "mimalloc")
+))]
+#[global_allocator]
+static GLOBAL: solana_accounts_filter_bench::allocator::Allocator =
+    solana_accounts_filter_bench::allocator::Allocator;
+
+#[derive(Debug, Parser)]
+#[clap(author, version, about)]
+struct Cli {
+    #[clap(flatten)]
+    global: GlobalArgs,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+// Flags shared by all subcommands, doc comment would replace `about` of the CLI.
+#[derive(Debug, clap::Args)]
+pub struct GlobalArgs {
+    /// Optional Json Rpc Url. By default value from `config.yml`.
+    #[clap(short, long, global = true)]
+    rpc: Option<String>,
+
+    /// Dataset format instead of detection by file extension: `json`, `jsonl`, `bin`,
+    /// `msgpack`, `safb` or `arrow`, optionally with `.gz` or `.zst` (e.g. `jsonl.zst`).
+    #[clap(long, global = true)]
+    format: Option<DatasetFormat>,
+
+    /// Write `<out>.manifest.json` with sizes, SHA-256 and slots of written dataset files,
+    /// datasets with manifest are verified on load.
+    #[clap(long, global = true)]
+    manifest: bool,
+
+    /// Split written datasets into files of this number of blocks, listed in the manifest.
+    #[clap(long, global = true)]
+    shard_blocks: Option<usize>,
+
+    /// Sign manifest of written datasets with the keypair file (e.g. `~/.config/solana/id.json`).
+    #[clap(long, global = true, parse(from_os_str))]
+    manifest_keypair: Option<PathBuf>,
+
+    /// Load only datasets with manifest signed by this Pubkey.
+    #[clap(long, global = true)]
+    manifest_signer: Option<Pubkey>,
+
+    /// Print only errors and results, without progress.
+    #[clap(short, long, global = true)]
+    quiet: bool,
+
+    /// Optional OTLP gRPC endpoint for tracing spans of downloads and benchmarks, e.g.
+    /// `http://127.0.0.1:4317` of Jaeger or Tempo.
+    #[cfg(feature = "otlp")]
+    #[clap(long, global = true)]
+    otlp_endpoint: Option<String>,
+}
+
+impl GlobalArgs {
+    pub fn rpc_client(&self) -> Result<RpcClient> {
+        let json_rpc_url = match &self.rpc {
+            Some(rpc) => rpc.clone(),
+            None => {
+                let config_file = CONFIG_FILE
+                    .as_ref()
+                    .ok_or_else(|| anyhow::anyhow!("unable to get config file path"))?;
+                Config::load(config_file)?.json_rpc_url
+            }
+        };
+        Ok(RpcClient::new_with_commitment(
+            json_rpc_url,
+            CommitmentConfig::finalized(),
+        ))
+    }
+
+    pub fn load_dataset(&self, path: &Path) -> Result<Dataset> {
+        if let Some(signer) = self.manifest_signer {
+            // the verified manifest is used for loading, so it is read once
+            let manifest = DatasetManifest::read(path)?
+                .filter(|manifest| manifest.signer() == Some(signer))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("dataset {:?} has no manifest signed by {}", path, signer)
+                })?;
+            return manifest.load(path).map_err(Into::into);
+        }
+        Ok(match self.format {
+            Some(format) => Dataset::load_with(path, format)?,
+            None => Dataset::load(path)?,
+        })
+    }
+
+    /// Save dataset in global `--format` (or by extension), returns written files.
+    pub fn save_dataset(&self, dataset: &Dataset, path: &Path) -> Result<Vec<PathBuf>> {
+        self.save_dataset_as(dataset, path, self.format)
+    }
+
+    /// Save dataset in `format` (or by extension) with manifest and shards if requested.
+    pub fn save_dataset_as(
+        &self,
+        dataset: &Dataset,
+        path: &Path,
+        format: Option<DatasetFormat>,
+    ) -> Result<Vec<PathBuf>> {
+        let format = match format {
+            Some(format) => format,
+            None => DatasetFormat::from_path(path)?,
+        };
+        let manifest_path = DatasetManifest::path(path);
+        if !self.manifest && self.shard_blocks.is_none() && self.manifest_keypair.is_none() {
+            dataset.save_with(path, format)?;
+            // manifest of the previous dataset at the same path would fail the load
+            match fs::remove_file(&manifest_path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error.into()),
+                _ => {}
+            }
+            return Ok(vec![path.to_owned()]);
+        }
+
+        let keypair = self
+            .manifest_keypair
+            .as_deref()
+            .map(|keypair| {
+                read_keypair_file(keypair).map_err(|error| {
+                    anyhow::anyhow!("failed to read keypair {:?}: {}", keypair, error)
+                })
+            })
+            .transpose()?;
+        let config = ManifestConfig {
+            shard_blocks: self.shard_blocks,
+            keypair: keypair.as_ref(),
+        };
+        let manifest = dataset.save_with_manifest(path, format, config)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut files = manifest
+            .shards
+            .iter()
+            .map(|shard| dir.join(&shard.file))
+            .collect::<Vec<_>>();
+        files.push(manifest_path);
+        Ok(files)
+    }
+}
+
+/// Fraction in `[0, 1]`, e.g. hit rate.
+pub fn parse_fraction(value: &str) -> Result<f64> {
+    let fraction = value.parse()?;
+    anyhow::ensure!(
+        (0.0..=1.0).contains(&fraction),
+        "should be in range [0, 1]: {}",
+        value
+    );
+    Ok(fraction)
+}
+
+/// Size of the dataset at `path` in all its shards.
+pub fn dataset_size(path: &Path) -> Result<u64> {
+    Ok(match DatasetManifest::read(path)? {
+        Some(manifest) => manifest.shards.iter().map(|shard| shard.size).sum(),
+        None => fs::metadata(path)?.len(),
+    })
+}
+
+// parsed once, size of the variants does not matter
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Subcommand)]
+enum Command {
+    Download(download::Args),
+    Generate(generate::Args),
+    Bench(bench::Args),
+    BenchRpc(bench_rpc::Args),
+    Analyze(analyze::Args),
+    Top(top::Args),
+    Estimate(estimate::Args),
+    Convert(convert::Args),
+    Merge(merge::Args),
+    Subsample(subsample::Args),
+    Prune(prune::Args),
+    Enrich(enrich::Args),
+    Compare(compare::Args),
+    #[cfg(feature = "plot")]
+    Plot(plot::Args),
+    #[cfg(feature = "plot")]
+    Report(report::Args),
+    Serve(serve::Args),
+    #[cfg(feature = "sqlite")]
+    ServeResults(serve_results::Args),
+    Replay(replay::Args),
+    Drive(drive::Args),
+    Watch(watch::Args),
+    #[cfg(feature = "orchestrate")]
+    Agent(agent::Args),
+    #[cfg(feature = "orchestrate")]
+    Dispatch(dispatch::Args),
+    #[clap(hide = true)]
+    Completions(completions::Args),
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let Cli { global, command } = Cli::parse();
+    #[cfg(feature = "otlp")]
+    let _otlp = global
+        .otlp_endpoint
+        .as_deref()
+        .map(otlp::init)
+        .transpose()?;
+    match command {
+        Command::Download(args) => download::run(args, &global).await,
+        Command::Generate(args) => generate::run(args, &global),
+        Command::Bench(args) => bench::run(args, &global).await,
+        Command::BenchRpc(args) => bench_rpc::run(args, &global).await,
+        Command::Analyze(args) => analyze::run(args, &global),
+        Command::Top(args) => top::run(args, &global),
+        Command::Estimate(args) => estimate::run(args, &global),
+        Command::Convert(args) => convert::run(args, &global),
+        Command::Merge(args) => merge::run(args, &global),
+        Command::Subsample(args) => subsample::run(args, &global),
+        Command::Prune(args) => prune::run(args, &global).await,
+        Command::Enrich(args) => enrich::run(args, &global).await,
+        Command::Compare(args) => compare::run(args, &global),
+        #[cfg(feature = "plot")]
+        Command::Plot(args) => plot::run(args, &global),
+        #[cfg(feature = "plot")]
+        Command::Report(args) => report::run(args, &global),
+        Command::Serve(args) => serve::run(args, &global).await,
+        #[cfg(feature = "sqlite")]
+        Command::ServeResults(args) => serve_results::run(args, &global).await,
+        Command::Replay(args) => replay::run(args, &global).await,
+        Command::Drive(args) => drive::run(args, &global).await,
+        Command::Watch(args) => watch::run(args, &global).await,
+        #[cfg(feature = "orchestrate")]
+        Command::Agent(args) => agent::run(args, &global).await,
+        #[cfg(feature = "orchestrate")]
+        Command::Dispatch(args) => dispatch::run(args, &global).await,
+        Command::Completions(args) => completions::run(args, &global),
+    }
+}
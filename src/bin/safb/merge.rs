@@ -0,0 +1,71 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    solana_accounts_filter_bench::{
+        dataset::{MergeStats, Overlap},
+        Dataset,
+    },
+    std::path::PathBuf,
+};
+
+/// Merge datasets (different windows or shards) into one.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input files with the data, merged in the given order.
+    #[clap(
+        short,
+        long,
+        required = true,
+        multiple_values = true,
+        parse(from_os_str)
+    )]
+    input: Vec<PathBuf>,
+
+    /// Resolution of slots present in several inputs: `first`, `last` or `union` of Pubkeys.
+    #[clap(long, default_value_t = Overlap::First)]
+    overlap: Overlap,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let mut dataset = Dataset::default();
+    let mut stats = MergeStats::default();
+    for input in args.input.iter() {
+        let other = global.load_dataset(input)?;
+        let blocks = other.blocks.len();
+        let input_stats = dataset.merge(other, args.overlap);
+        if !global.quiet {
+            println!(
+                "Merged {}: blocks {}, added {}, overlapping {}, conflicts {}",
+                input.display(),
+                blocks,
+                input_stats.added,
+                input_stats.overlapping,
+                input_stats.conflicts
+            );
+        }
+        stats.extend(input_stats);
+    }
+    global.save_dataset(&dataset, &args.out)?;
+
+    println!(
+        "Total {} blocks, with {} pubkeys, overlapping slots: {}, conflicts: {}",
+        dataset.blocks.len(),
+        dataset.unique_pubkeys(),
+        stats.overlapping,
+        stats.conflicts
+    );
+    if let (Some((first, first_block)), Some((last, last_block))) = (
+        dataset.blocks.iter().next(),
+        dataset.blocks.iter().next_back(),
+    ) {
+        println!(
+            "Slots: {}..={}, block time: {}..={}",
+            first, last, first_block.block_time, last_block.block_time
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use {
+    crate::bench::Rss,
+    anyhow::Result,
+    hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, Server, StatusCode,
+    },
+    std::{
+        convert::Infallible,
+        fmt::Write,
+        net::SocketAddr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    },
+};
+
+/// Values published by `--metrics-addr` in Prometheus text format, updated by `bench` after
+/// every run and by `watch` every interval.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    lookups: AtomicU64,
+    matched: AtomicU64,
+    gauges: Mutex<Gauges>,
+}
+
+/// Gauges of the last run or interval, absent values are not published.
+#[derive(Debug, Default, Clone)]
+pub struct Gauges {
+    /// Benchmark of the last run, label of all gauges.
+    pub bench: Option<String>,
+    pub lookups_per_second: Option<f64>,
+    /// Fraction of lookups matched by the filter.
+    pub hit_ratio: Option<f64>,
+    pub lookup_p99_seconds: Option<f64>,
+    /// End-to-end latency of updates in `watch`.
+    pub update_p99_seconds: Option<f64>,
+    pub filter_keys: Option<usize>,
+    pub filter_memory_bytes: Option<usize>,
+}
+
+impl Metrics {
+    /// Serve metrics on `GET /metrics` in the background.
+    pub fn serve(addr: SocketAddr) -> Result<Arc<Self>> {
+        let metrics = Arc::new(Self::default());
+        let make_service = make_service_fn({
+            let metrics = Arc::clone(&metrics);
+            move |_conn| {
+                let metrics = Arc::clone(&metrics);
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request| {
+                        let metrics = Arc::clone(&metrics);
+                        async move { Ok::<_, Infallible>(metrics.handle(request)) }
+                    }))
+                }
+            }
+        });
+        let server = Server::try_bind(&addr)?.serve(make_service);
+        println!("Metrics on http://{}/metrics", server.local_addr());
+        tokio::spawn(async move {
+            if let Err(error) = server.await {
+                println!("Metrics server failed: {}", error);
+            }
+        });
+        Ok(metrics)
+    }
+
+    /// Add lookups and matched lookups to the counters.
+    pub fn add(&self, lookups: usize, matched: usize) {
+        self.lookups.fetch_add(lookups as u64, Ordering::Relaxed);
+        self.matched.fetch_add(matched as u64, Ordering::Relaxed);
+    }
+
+    pub fn set(&self, gauges: Gauges) {
+        *self.gauges.lock().expect("poisoned") = gauges;
+    }
+
+    fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.method() == Method::GET && request.uri().path() == "/metrics" {
+            Response::builder()
+                .header("content-type", "text/plain; version=0.0.4")
+                .body(Body::from(self.render()))
+                .expect("valid response")
+        } else {
+            let mut response = Response::new(Body::from("not found, use `GET /metrics`\n"));
+            *response.status_mut() = StatusCode::NOT_FOUND;
+            response
+        }
+    }
+
+    fn render(&self) -> String {
+        let gauges = self.gauges.lock().expect("poisoned").clone();
+        let labels = match &gauges.bench {
+            Some(bench) => format!("{{bench=\"{}\"}}", bench),
+            None => String::new(),
+        };
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, labels: &str, value: Option<f64>| {
+            if let Some(value) = value {
+                let _ = writeln!(text, "# HELP {} {}", name, help);
+                let _ = writeln!(text, "# TYPE {} {}", name, kind);
+                let _ = writeln!(text, "{}{} {}", name, labels, value);
+            }
+        };
+        metric(
+            "safb_lookups_total",
+            "counter",
+            "Lookups in the filter.",
+            "",
+            Some(self.lookups.load(Ordering::Relaxed) as f64),
+        );
+        metric(
+            "safb_matched_total",
+            "counter",
+            "Lookups matched by the filter.",
+            "",
+            Some(self.matched.load(Ordering::Relaxed) as f64),
+        );
+        metric(
+            "safb_lookups_per_second",
+            "gauge",
+            "Lookups per second of the last run or interval.",
+            &labels,
+            gauges.lookups_per_second,
+        );
+        metric(
+            "safb_hit_ratio",
+            "gauge",
+            "Fraction of lookups matched by the filter in the last run or interval.",
+            &labels,
+            gauges.hit_ratio,
+        );
+        metric(
+            "safb_lookup_latency_p99_seconds",
+            "gauge",
+            "99th percentile of lookup time (per block in bench, per update in watch).",
+            &labels,
+            gauges.lookup_p99_seconds,
+        );
+        metric(
+            "safb_update_latency_p99_seconds",
+            "gauge",
+            "99th percentile of end-to-end latency of updates.",
+            &labels,
+            gauges.update_p99_seconds,
+        );
+        metric(
+            "safb_filter_keys",
+            "gauge",
+            "Number of keys in the filter.",
+            &labels,
+            gauges.filter_keys.map(|keys| keys as f64),
+        );
+        metric(
+            "safb_filter_memory_bytes",
+            "gauge",
+            "Memory usage reported by the filter.",
+            &labels,
+            gauges.filter_memory_bytes.map(|bytes| bytes as f64),
+        );
+        metric(
+            "safb_rss_bytes",
+            "gauge",
+            "Resident set size of the process.",
+            "",
+            Rss::read().map(|rss| (rss.current * 1024) as f64),
+        );
+        text
+    }
+}
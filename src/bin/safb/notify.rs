@@ -0,0 +1,143 @@
+use {
+    crate::{
+        compare::{change, Change},
+        results::BenchResult,
+    },
+    anyhow::Result,
+    serde::Serialize,
+    std::{collections::HashMap, time::Duration},
+};
+
+/// Summary of finished suite posted to `--notify-webhook`, `text` is shown by Slack and
+/// Mattermost incoming webhooks, other fields are for custom receivers.
+#[derive(Debug, Serialize)]
+pub struct SuiteSummary {
+    pub text: String,
+    pub suite: Option<String>,
+    pub host: Option<String>,
+    pub elapsed_secs: u64,
+    pub benchmarks: usize,
+    /// Fastest benchmark by time per Pubkey.
+    pub fastest: Option<BenchSummary>,
+    /// Significant changes versus baseline beyond the threshold, empty without baseline.
+    pub regressions: Vec<BenchChange>,
+    pub improvements: Vec<BenchChange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchSummary {
+    pub key: String,
+    pub per_op_ns: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchChange {
+    pub key: String,
+    pub base_per_op_ns: f64,
+    pub per_op_ns: f64,
+    /// Relative change of mean iteration time, in percents.
+    pub change_pct: f64,
+}
+
+impl SuiteSummary {
+    pub fn new(
+        suite: Option<&str>,
+        elapsed: Duration,
+        results: &[BenchResult],
+        baseline: Option<&[BenchResult]>,
+        threshold: f64,
+    ) -> Self {
+        let base = baseline
+            .unwrap_or_default()
+            .iter()
+            .map(|result| (result.key(), result))
+            .collect::<HashMap<_, _>>();
+        let mut regressions = vec![];
+        let mut improvements = vec![];
+        for result in results {
+            let key = result.key();
+            let base_result = match base.get(&key) {
+                Some(base_result) => base_result,
+                None => continue,
+            };
+            let changes = match change(base_result, result, threshold) {
+                Change::Insignificant => continue,
+                Change::Regression => &mut regressions,
+                Change::Improvement => &mut improvements,
+            };
+            changes.push(BenchChange {
+                key,
+                base_per_op_ns: base_result.per_op_ns(),
+                per_op_ns: result.per_op_ns(),
+                change_pct: (result.iter_stats.mean_ns as f64
+                    / base_result.iter_stats.mean_ns.max(1) as f64
+                    - 1.0)
+                    * 100.0,
+            });
+        }
+        let fastest = results
+            .iter()
+            .min_by(|a, b| a.per_op_ns().total_cmp(&b.per_op_ns()))
+            .map(|result| BenchSummary {
+                key: result.key(),
+                per_op_ns: result.per_op_ns(),
+            });
+        let host = crate::environment::Environment::collect().host;
+
+        let mut text = format!(
+            "safb suite {}finished on {} in {:?}: {} benchmarks",
+            suite.map_or(String::new(), |name| format!("`{}` ", name)),
+            host.as_deref().unwrap_or("unknown host"),
+            Duration::from_secs(elapsed.as_secs()),
+            results.len()
+        );
+        if let Some(fastest) = &fastest {
+            text += &format!(
+                ", fastest `{}` ({:.2}ns per pubkey)",
+                fastest.key, fastest.per_op_ns
+            );
+        }
+        if baseline.is_some() {
+            text += &format!(
+                ", regressions: {}, improvements: {} (threshold {}%)",
+                regressions.len(),
+                improvements.len(),
+                threshold * 100.0
+            );
+            for regression in regressions.iter() {
+                text += &format!(
+                    "\nREGRESSION `{}`: {:.2}ns -> {:.2}ns per pubkey ({:+.2}%)",
+                    regression.key,
+                    regression.base_per_op_ns,
+                    regression.per_op_ns,
+                    regression.change_pct
+                );
+            }
+        }
+
+        Self {
+            text,
+            suite: suite.map(ToOwned::to_owned),
+            host,
+            elapsed_secs: elapsed.as_secs(),
+            benchmarks: results.len(),
+            fastest,
+            regressions,
+            improvements,
+        }
+    }
+
+    /// POST summary as JSON, failures are printed, so results of the run are still saved.
+    pub async fn post(&self, url: &str) {
+        if let Err(error) = self.try_post(url).await {
+            println!("WARNING: failed to notify webhook: {}", error);
+        }
+    }
+
+    async fn try_post(&self, url: &str) -> Result<()> {
+        let response = reqwest::Client::new().post(url).json(self).send().await?;
+        let status = response.status();
+        anyhow::ensure!(status.is_success(), "{}", status);
+        Ok(())
+    }
+}
@@ -0,0 +1,39 @@
+use {
+    anyhow::Result,
+    opentelemetry::{
+        global,
+        sdk::{trace, Resource},
+        KeyValue,
+    },
+    opentelemetry_otlp::WithExportConfig,
+    tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt},
+};
+
+/// Flushes batched spans on drop.
+pub struct OtlpGuard;
+
+impl Drop for OtlpGuard {
+    fn drop(&mut self) {
+        global::shutdown_tracer_provider();
+    }
+}
+
+/// Export tracing spans over OTLP gRPC to `endpoint`, spans are batched and sent in background.
+pub fn init(endpoint: &str) -> Result<OtlpGuard> {
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new([KeyValue::new(
+            "service.name",
+            env!("CARGO_BIN_NAME"),
+        )])))
+        .install_batch(opentelemetry::runtime::Tokio)?;
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(tracer))
+        .try_init()?;
+    Ok(OtlpGuard)
+}
@@ -0,0 +1,269 @@
+use {
+    crate::{
+        results::{load_results, BenchResult},
+        GlobalArgs,
+    },
+    anyhow::Result,
+    plotters::{coord::Shift, prelude::*},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        fmt,
+        path::PathBuf,
+        str::FromStr,
+    },
+};
+
+/// Plot throughput of benchmarks from results of parameter sweeps.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Results in JSON of one or more `bench --output` runs.
+    #[clap(required = true, parse(from_os_str))]
+    results: Vec<PathBuf>,
+
+    /// Parameter on X axis: `set-size`, `threads` or `hit-rate`.
+    #[clap(short, long, default_value_t = Axis::SetSize)]
+    x: Axis,
+
+    /// Comma separated benchmarks to plot, by default all of them.
+    #[clap(short, long, use_value_delimiter = true)]
+    filters: Vec<String>,
+
+    /// Chart title.
+    #[clap(long)]
+    title: Option<String>,
+
+    /// Chart size in pixels.
+    #[clap(long, default_value_t = 1280)]
+    width: u32,
+
+    #[clap(long, default_value_t = 800)]
+    height: u32,
+
+    /// Out file, format by extension: `.svg` or `.png`.
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    SetSize,
+    Threads,
+    HitRate,
+}
+
+impl Axis {
+    pub const ALL: [Self; 3] = [Self::SetSize, Self::Threads, Self::HitRate];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::SetSize => "set-size",
+            Self::Threads => "threads",
+            Self::HitRate => "hit-rate",
+        }
+    }
+
+    /// Sweeps of set size and threads are geometric, so they are plotted in log scale.
+    const fn log_scale(self) -> bool {
+        matches!(self, Self::SetSize | Self::Threads)
+    }
+}
+
+impl fmt::Display for Axis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Axis {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|axis| axis.name() == s)
+            .ok_or_else(|| format!("unknown axis: {}", s))
+    }
+}
+
+impl Axis {
+    pub fn x(self, result: &BenchResult) -> Option<f64> {
+        match self {
+            Self::SetSize => Some(result.set_size as f64),
+            Self::Threads => Some(result.threads as f64),
+            Self::HitRate => result.hit_rate,
+        }
+    }
+
+    /// Parameters of the result other than the axis which separate series, as `name=value`.
+    fn params(self, result: &BenchResult) -> Vec<String> {
+        let mut params = vec![format!("input={}", result.input_name())];
+        if self != Self::SetSize {
+            params.push(format!("set_size={}", result.set_size));
+        }
+        if self != Self::Threads {
+            params.push(format!("threads={}", result.threads));
+        }
+        if let (false, Some(hit_rate)) = (self == Self::HitRate, result.hit_rate) {
+            params.push(format!("hit_rate={}", hit_rate));
+        }
+        params
+    }
+}
+
+/// Points of every series keyed by label: benchmark with parameters which are not the same in
+/// all results.
+pub fn series(results: &[BenchResult], axis: Axis) -> BTreeMap<String, Vec<(f64, f64)>> {
+    let params = results
+        .iter()
+        .map(|result| axis.params(result))
+        .collect::<Vec<_>>();
+    let varying = params
+        .iter()
+        .flatten()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter(|param| !params.iter().all(|params| params.contains(param)))
+        .collect::<BTreeSet<_>>();
+
+    let mut series = BTreeMap::<String, Vec<(f64, f64)>>::new();
+    for (result, params) in results.iter().zip(params.iter()) {
+        let x = match axis.x(result) {
+            Some(x) => x,
+            None => continue,
+        };
+        let label = std::iter::once(result.bench.clone())
+            .chain(
+                params
+                    .iter()
+                    .filter(|param| varying.contains(param))
+                    .cloned(),
+            )
+            .collect::<Vec<_>>()
+            .join(" ");
+        series
+            .entry(label)
+            .or_default()
+            .push((x, result.throughput()));
+    }
+    for points in series.values_mut() {
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    series
+}
+
+pub fn draw<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    title: &str,
+    axis: Axis,
+    series: &BTreeMap<String, Vec<(f64, f64)>>,
+) -> Result<()>
+where
+    DB::ErrorType: 'static,
+{
+    // log scale is drawn as linear over log10 with labels of original values
+    let scale = |x: f64| if axis.log_scale() { x.log10() } else { x };
+    let points = series.values().flatten();
+    let (x_min, x_max) = points
+        .clone()
+        .map(|(x, _y)| scale(*x))
+        .fold((f64::MAX, f64::MIN), |(min, max), x| {
+            (min.min(x), max.max(x))
+        });
+    let y_max = points.map(|(_x, y)| *y).fold(0.0, f64::max);
+    let x_pad = ((x_max - x_min) * 0.05).max(0.05);
+
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(70)
+        .build_cartesian_2d(
+            (x_min - x_pad)..(x_max + x_pad),
+            0.0..(y_max * 1.1).max(1.0),
+        )?;
+    let format_x = |x: &f64| {
+        if axis.log_scale() {
+            format!("{:.0}", 10f64.powf(*x))
+        } else {
+            format!("{:.2}", x)
+        }
+    };
+    chart
+        .configure_mesh()
+        .x_desc(axis.name())
+        .y_desc("Mlookups/s")
+        .x_label_formatter(&format_x)
+        .draw()?;
+
+    for (index, (label, points)) in series.iter().enumerate() {
+        let color = Palette99::pick(index).mix(0.9);
+        let points = points
+            .iter()
+            .map(|(x, y)| (scale(*x), *y))
+            .collect::<Vec<_>>();
+        chart
+            .draw_series(LineSeries::new(points.clone(), color.stroke_width(2)))?
+            .label(label)
+            .legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+        chart.draw_series(
+            points
+                .into_iter()
+                .map(|point| Circle::new(point, 3, color.filled())),
+        )?;
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperRight)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let mut results = Vec::new();
+    for path in args.results.iter() {
+        results.extend(load_results(path)?);
+    }
+    if !args.filters.is_empty() {
+        results.retain(|result| args.filters.contains(&result.bench));
+    }
+    let series = series(&results, args.x);
+    anyhow::ensure!(!series.is_empty(), "no results to plot over {}", args.x);
+
+    let title = args
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("Throughput vs {}", args.x));
+    let size = (args.width, args.height);
+    match args.out.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => draw(
+            SVGBackend::new(&args.out, size).into_drawing_area(),
+            &title,
+            args.x,
+            &series,
+        )?,
+        Some("png") => draw(
+            BitMapBackend::new(&args.out, size).into_drawing_area(),
+            &title,
+            args.x,
+            &series,
+        )?,
+        _ => anyhow::bail!(
+            "unknown chart format of {:?}, expected .svg or .png",
+            args.out
+        ),
+    }
+
+    if !global.quiet {
+        for (label, points) in series.iter() {
+            println!("{}: {} points", label, points.len());
+        }
+    }
+    println!("Chart saved to {}", args.out.display());
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use {
+    crate::{enrich::fetch_owners, GlobalArgs},
+    anyhow::Result,
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::HashSet, path::PathBuf},
+};
+
+/// Filter Pubkeys of the dataset by criteria, for focused benchmarks.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Keep Pubkeys referenced by at least this number of blocks.
+    #[clap(long, default_value_t = 1)]
+    min_count: usize,
+
+    /// Comma separated owner programs, keep only accounts owned by them (current owners are
+    /// requested from RPC with `getMultipleAccounts`).
+    #[clap(long, use_value_delimiter = true)]
+    owners: Vec<Pubkey>,
+
+    /// Number of concurrent `getMultipleAccounts` requests.
+    #[clap(short = 't', long, default_value_t = 3)]
+    concurrency: usize,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let mut dataset = global.load_dataset(&args.input)?;
+    let total = dataset.unique_pubkeys();
+
+    let mut keep = dataset
+        .key_counts()
+        .into_iter()
+        .filter(|(_pubkey, count)| *count >= args.min_count)
+        .map(|(pubkey, _count)| pubkey)
+        .collect::<HashSet<_>>();
+    if !args.owners.is_empty() {
+        let rpc = global.rpc_client()?;
+        let owners = args.owners.iter().cloned().collect::<HashSet<_>>();
+        let pubkeys = keep.into_iter().collect::<Vec<_>>();
+        keep = fetch_owners(&rpc, &pubkeys, args.concurrency, global.quiet)
+            .await?
+            .into_iter()
+            .filter(|(_pubkey, account)| owners.contains(&account.owner))
+            .map(|(pubkey, _account)| pubkey)
+            .collect();
+    }
+
+    dataset.retain_keys(|pubkey| keep.contains(pubkey));
+    global.save_dataset(&dataset, &args.out)?;
+
+    println!(
+        "Total {} blocks, with {} of {} pubkeys",
+        dataset.blocks.len(),
+        dataset.unique_pubkeys(),
+        total
+    );
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    solana_accounts_filter_bench::grpc::{ReplayConfig, ReplayServer, ReplaySpeed},
+    std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration},
+};
+
+/// Stream dataset over Yellowstone compatible gRPC, for load tests of filtering services.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Address of gRPC server.
+    #[clap(long, default_value = "127.0.0.1:10000")]
+    listen: SocketAddr,
+
+    /// Replay speed relative to slot time: `1x`, `10x`, ... or `max` (as fast as subscriber reads).
+    #[clap(long, default_value_t = ReplaySpeed::Realtime(1.0))]
+    speed: ReplaySpeed,
+
+    /// Updates buffered for every subscriber, updates are dropped when it is full (except `max` speed).
+    #[clap(long, default_value_t = 100_000)]
+    channel_capacity: usize,
+
+    /// Seconds between printed counters of sent and dropped updates.
+    #[clap(long, default_value_t = 10)]
+    stats_interval: u64,
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let dataset = global.load_dataset(&args.input)?;
+    let server = ReplayServer::new(
+        Arc::new(dataset),
+        ReplayConfig {
+            speed: args.speed,
+            channel_capacity: args.channel_capacity,
+        },
+    );
+
+    if !global.quiet && args.stats_interval > 0 {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(args.stats_interval));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let stats = server.stats();
+                println!(
+                    "Subscriptions: {}, updates sent: {}, dropped: {}",
+                    stats.subscriptions, stats.sent, stats.dropped
+                );
+            }
+        });
+    }
+
+    println!("Replay on {} with speed {}", args.listen, args.speed);
+    server.serve(args.listen).await.map_err(Into::into)
+}
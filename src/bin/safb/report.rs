@@ -0,0 +1,422 @@
+use {
+    crate::{
+        compare::{change, Change},
+        environment::Environment,
+        plot::{self, Axis},
+        results::{load_results, BenchResult},
+        GlobalArgs,
+    },
+    anyhow::Result,
+    plotters::prelude::*,
+    solana_accounts_filter_bench::{
+        dataset::{AnalyzeConfig, Distribution},
+        known::known_name,
+    },
+    std::{
+        collections::{BTreeMap, BTreeSet, HashMap},
+        fmt::Write,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Size of charts in the report, in pixels.
+const CHART_SIZE: (u32, u32) = (960, 540);
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em auto;max-width:1100px;color:#222}\
+table{border-collapse:collapse;margin:1em 0;font-size:14px}\
+th,td{border:1px solid #ccc;padding:4px 8px;text-align:right}\
+th:first-child,td:first-child{text-align:left}\
+th{background:#f0f0f0}.best{font-weight:bold;background:#e6f4ea}\
+.regression{color:#c5221f}.improvement{color:#188038}";
+
+/// Generate single-file HTML report of benchmark results.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Results in JSON of one or more `bench --output` runs.
+    #[clap(required = true, parse(from_os_str))]
+    results: Vec<PathBuf>,
+
+    /// Optional baseline results in JSON, compared with results as in `compare`.
+    #[clap(long, parse(from_os_str))]
+    baseline: Option<PathBuf>,
+
+    /// Minimum relative change of mean iteration time reported as regression or improvement.
+    #[clap(long, default_value_t = 0.05)]
+    threshold: f64,
+
+    /// Optional dataset of the run for dataset statistics.
+    #[clap(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Report title.
+    #[clap(long, default_value = "Accounts filter benchmark")]
+    title: String,
+
+    /// Out file for the report in HTML.
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Environment of the machine generating the report, run it where benchmarks were run.
+fn environment(results: &[BenchResult]) -> Vec<(&'static str, String)> {
+    let mut environment = vec![(
+        "Generated",
+        chrono::Utc::now()
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string(),
+    )];
+    let machine = Environment::collect();
+    environment.push(("safb", machine.version.to_owned()));
+    if let Some(host) = machine.host {
+        environment.push(("Host", host));
+    }
+    environment.push(("OS", machine.os));
+    if let Some(cpu) = machine.cpu {
+        environment.push(("CPU", cpu));
+    }
+    if let Some(cpus) = machine.cpus {
+        environment.push(("CPUs", cpus.to_string()));
+    }
+    if let Some(memory) = machine.memory {
+        environment.push(("Memory", memory));
+    }
+    let allocators = results
+        .iter()
+        .map(|result| result.allocator.as_str())
+        .filter(|allocator| !allocator.is_empty())
+        .collect::<BTreeSet<_>>();
+    if !allocators.is_empty() {
+        let allocators = allocators.into_iter().collect::<Vec<_>>();
+        environment.push(("Allocator", allocators.join(", ")));
+    }
+    environment
+}
+
+fn write_table(html: &mut String, header: &[&str], rows: &[Vec<String>]) -> Result<()> {
+    html.push_str("<table><tr>");
+    for name in header {
+        write!(html, "<th>{}</th>", escape(name))?;
+    }
+    html.push_str("</tr>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            // cells are escaped by callers, some of them have markup
+            write!(html, "<td{}</td>", cell)?;
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+    Ok(())
+}
+
+/// Cell with escaped text and optional class.
+fn cell(text: impl AsRef<str>, class: Option<&str>) -> String {
+    match class {
+        Some(class) => format!(" class=\"{}\">{}", class, escape(text.as_ref())),
+        None => format!(">{}", escape(text.as_ref())),
+    }
+}
+
+/// Time per Pubkey of every benchmark (columns) for every combination of parameters (rows),
+/// the fastest benchmark of the row is highlighted.
+fn write_summary(html: &mut String, results: &[BenchResult]) -> Result<()> {
+    let benches = results
+        .iter()
+        .map(|result| result.bench.as_str())
+        .collect::<BTreeSet<_>>();
+    let mut rows = BTreeMap::<String, HashMap<&str, f64>>::new();
+    for result in results {
+        rows.entry(result.params())
+            .or_default()
+            .insert(&result.bench, result.per_op_ns());
+    }
+
+    let header = std::iter::once("parameters")
+        .chain(benches.iter().copied())
+        .collect::<Vec<_>>();
+    let rows = rows
+        .iter()
+        .map(|(params, values)| {
+            let best = values.values().cloned().fold(f64::MAX, f64::min);
+            std::iter::once(cell(params, None))
+                .chain(benches.iter().map(|bench| match values.get(bench) {
+                    Some(value) => cell(
+                        format!("{:.2}", value),
+                        (*value == best && values.len() > 1).then_some("best"),
+                    ),
+                    None => cell("-", None),
+                }))
+                .collect()
+        })
+        .collect::<Vec<_>>();
+    html.push_str("<h2>Time per Pubkey, ns</h2>\n");
+    write_table(html, &header, &rows)
+}
+
+fn write_details(html: &mut String, results: &[BenchResult]) -> Result<()> {
+    let mut results = results.iter().collect::<Vec<_>>();
+    results.sort_by_key(|result| result.key());
+    let rows = results
+        .into_iter()
+        .map(|result| {
+            vec![
+                cell(&result.bench, None),
+                cell(result.params(), None),
+                cell(result.iters.to_string(), None),
+                cell(
+                    format!("{:.3}", result.iter_stats.mean_ns as f64 / 1e6),
+                    None,
+                ),
+                cell(format!("{:.2}", result.iter_stats.cv * 100.0), None),
+                cell(format!("{:.2}", result.per_op_ns()), None),
+                cell(format!("{:.2}", result.throughput()), None),
+                cell(
+                    result
+                        .filter_memory
+                        .map(|memory| format!("{:.2}", memory as f64 / 1024.0 / 1024.0))
+                        .unwrap_or_else(|| "-".to_owned()),
+                    None,
+                ),
+            ]
+        })
+        .collect::<Vec<_>>();
+    html.push_str("<h2>Results</h2>\n");
+    write_table(
+        html,
+        &[
+            "bench",
+            "parameters",
+            "iters",
+            "iteration, ms",
+            "cv, %",
+            "per Pubkey, ns",
+            "Mlookups/s",
+            "filter memory, MiB",
+        ],
+        &rows,
+    )
+}
+
+fn write_comparison(
+    html: &mut String,
+    baseline: &[BenchResult],
+    results: &[BenchResult],
+    threshold: f64,
+) -> Result<()> {
+    let baseline = baseline
+        .iter()
+        .map(|result| (result.key(), result))
+        .collect::<HashMap<_, _>>();
+    let mut rows = results
+        .iter()
+        .filter_map(|result| {
+            let key = result.key();
+            let base = baseline.get(&key)?;
+            let change = change(base, result, threshold);
+            let class = match change {
+                Change::Insignificant => None,
+                Change::Regression => Some("regression"),
+                Change::Improvement => Some("improvement"),
+            };
+            Some(vec![
+                cell(key, None),
+                cell(format!("{:.2}", base.per_op_ns()), None),
+                cell(format!("{:.2}", result.per_op_ns()), None),
+                cell(
+                    format!(
+                        "{:+.2}",
+                        (result.iter_stats.mean_ns as f64 / base.iter_stats.mean_ns.max(1) as f64
+                            - 1.0)
+                            * 100.0
+                    ),
+                    class,
+                ),
+                cell(change.to_string(), class),
+            ])
+        })
+        .collect::<Vec<_>>();
+    rows.sort();
+    html.push_str("<h2>Comparison with baseline</h2>\n");
+    write_table(
+        html,
+        &[
+            "benchmark",
+            "base per Pubkey, ns",
+            "per Pubkey, ns",
+            "iteration change, %",
+            "",
+        ],
+        &rows,
+    )
+}
+
+/// Throughput charts over every parameter with more than one value in results.
+fn write_charts(html: &mut String, results: &[BenchResult]) -> Result<()> {
+    for axis in Axis::ALL {
+        let values = results
+            .iter()
+            .filter_map(|result| axis.x(result).map(f64::to_bits))
+            .collect::<BTreeSet<_>>();
+        if values.len() < 2 {
+            continue;
+        }
+        let mut svg = String::new();
+        plot::draw(
+            SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area(),
+            &format!("Throughput vs {}", axis),
+            axis,
+            &plot::series(results, axis),
+        )?;
+        html.push_str(&svg);
+        html.push('\n');
+    }
+    Ok(())
+}
+
+fn format_distribution(distribution: &Distribution, precision: usize) -> Vec<String> {
+    [
+        distribution.min,
+        distribution.mean,
+        distribution.p50,
+        distribution.p90,
+        distribution.p99,
+        distribution.max,
+    ]
+    .iter()
+    .map(|value| cell(format!("{:.*}", precision, value), None))
+    .collect()
+}
+
+fn write_dataset(html: &mut String, path: &Path, global: &GlobalArgs) -> Result<()> {
+    let dataset = global.load_dataset(path)?;
+    let stats = dataset.analyze(&AnalyzeConfig {
+        top: 10,
+        ..Default::default()
+    });
+
+    writeln!(
+        html,
+        "<h2>Dataset {}</h2>",
+        escape(&path.display().to_string())
+    )?;
+    let slots = match (dataset.blocks.keys().next(), dataset.blocks.keys().last()) {
+        (Some(first), Some(last)) => format!("{}..={}", first, last),
+        _ => "-".to_owned(),
+    };
+    write_table(
+        html,
+        &[
+            "slots",
+            "blocks",
+            "total Pubkeys",
+            "unique Pubkeys",
+            "working set",
+            "Pubkeys for 90% lookups",
+        ],
+        &[vec![
+            cell(slots, None),
+            cell(stats.blocks.to_string(), None),
+            cell(stats.total_keys.to_string(), None),
+            cell(stats.unique_keys.to_string(), None),
+            cell(
+                format!(
+                    "{:.0} (max {}) in {}s",
+                    stats.working_set.mean, stats.working_set.max, stats.working_set.window
+                ),
+                None,
+            ),
+            cell(stats.working_set.keys_90pct_lookups.to_string(), None),
+        ]],
+    )?;
+    let mut keys_per_block = vec![cell("Pubkeys per block", None)];
+    keys_per_block.extend(format_distribution(&stats.keys_per_block, 0));
+    let mut overlap = vec![cell("overlap with previous block", None)];
+    overlap.extend(format_distribution(&stats.overlap, 3));
+    write_table(
+        html,
+        &["", "min", "mean", "p50", "p90", "p99", "max"],
+        &[keys_per_block, overlap],
+    )?;
+    let hottest = stats
+        .hottest
+        .iter()
+        .map(|key| {
+            let name = key
+                .pubkey
+                .parse()
+                .ok()
+                .and_then(|pubkey| known_name(&pubkey))
+                .unwrap_or_default();
+            vec![
+                cell(&key.pubkey, None),
+                cell(name, None),
+                cell(key.count.to_string(), None),
+                cell(format!("{:.2}", key.fraction * 100.0), None),
+            ]
+        })
+        .collect::<Vec<_>>();
+    write_table(
+        html,
+        &["hottest Pubkey", "name", "blocks", "blocks, %"],
+        &hottest,
+    )
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let mut results = Vec::new();
+    for path in args.results.iter() {
+        results.extend(load_results(path)?);
+    }
+    anyhow::ensure!(!results.is_empty(), "no results for the report");
+
+    let mut html = String::new();
+    write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{title}</title>\
+        <style>{}</style></head><body>\n<h1>{title}</h1>\n",
+        STYLE,
+        title = escape(&args.title)
+    )?;
+
+    html.push_str("<h2>Environment</h2>\n");
+    let mut environment = environment(&results)
+        .into_iter()
+        .map(|(name, value)| vec![cell(name, None), cell(value, None)])
+        .collect::<Vec<_>>();
+    for path in args.results.iter() {
+        environment.push(vec![
+            cell("Results", None),
+            cell(path.display().to_string(), None),
+        ]);
+    }
+    write_table(&mut html, &["", ""], &environment)?;
+
+    write_summary(&mut html, &results)?;
+    if let Some(baseline) = &args.baseline {
+        write_comparison(
+            &mut html,
+            &load_results(baseline)?,
+            &results,
+            args.threshold,
+        )?;
+    }
+    write_charts(&mut html, &results)?;
+    write_details(&mut html, &results)?;
+    if let Some(input) = &args.input {
+        write_dataset(&mut html, input, global)?;
+    }
+    html.push_str("</body></html>\n");
+
+    fs::write(&args.out, html)?;
+    println!("Report saved to {}", args.out.display());
+    Ok(())
+}
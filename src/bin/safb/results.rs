@@ -0,0 +1,119 @@
+// `plot` and `report` use all fields, `compare` only some of them
+#![cfg_attr(not(feature = "plot"), allow(dead_code))]
+
+use {
+    anyhow::Result,
+    serde::Deserialize,
+    std::{
+        borrow::Cow,
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Result of one benchmark written by `bench` to results databases, `result` is the full
+/// result in JSON.
+#[cfg(any(feature = "sqlite", feature = "postgres"))]
+#[derive(Debug)]
+pub struct ResultRow<'a> {
+    pub suite: Option<&'a str>,
+    pub bench: &'a str,
+    pub input: String,
+    pub set_size: usize,
+    pub threads: usize,
+    pub hit_rate: Option<f64>,
+    pub rayon_min_len: Option<usize>,
+    pub write_ratio: Option<f64>,
+    pub total_ops: usize,
+    pub iters: u32,
+    pub elapsed_ns: u64,
+    pub mean_ns: u64,
+    pub stddev_ns: u64,
+    pub cv: f64,
+    pub success: usize,
+    pub filter_memory: Option<usize>,
+    pub rss_peak_kb: Option<u64>,
+    pub result: String,
+}
+
+/// Fields of `bench --output` results read by `compare`, `plot` and `report`.
+#[derive(Debug, Deserialize)]
+pub struct BenchResult {
+    pub bench: String,
+    #[serde(default)]
+    pub allocator: String,
+    pub input: PathBuf,
+    pub set_size: usize,
+    pub threads: usize,
+    pub hit_rate: Option<f64>,
+    pub rayon_min_len: Option<usize>,
+    pub total_ops: usize,
+    pub iters: u32,
+    pub iter_stats: IterStats,
+    pub filter_memory: Option<usize>,
+    pub churn: Option<Churn>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IterStats {
+    pub mean_ns: u64,
+    pub stddev_ns: u64,
+    pub cv: f64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Churn {
+    pub write_ratio: Option<f64>,
+}
+
+impl BenchResult {
+    /// Input only by file name because machines can keep data in different directories.
+    pub fn input_name(&self) -> Cow<'_, str> {
+        self.input
+            .file_name()
+            .unwrap_or(self.input.as_os_str())
+            .to_string_lossy()
+    }
+
+    /// Parameters identifying the run, without benchmark name.
+    pub fn params(&self) -> String {
+        let mut params = format!(
+            "input={} set_size={} threads={}",
+            self.input_name(),
+            self.set_size,
+            self.threads
+        );
+        if let Some(hit_rate) = self.hit_rate {
+            params.push_str(&format!(" hit_rate={}", hit_rate));
+        }
+        if let Some(min_len) = self.rayon_min_len {
+            params.push_str(&format!(" rayon_min_len={}", min_len));
+        }
+        if let Some(ratio) = self.churn.as_ref().and_then(|churn| churn.write_ratio) {
+            params.push_str(&format!(" write_ratio={}", ratio));
+        }
+        params
+    }
+
+    /// Benchmark and parameters identifying the run.
+    pub fn key(&self) -> String {
+        format!("{} {}", self.bench, self.params())
+    }
+
+    /// Mean time per lookup in ns.
+    pub fn per_op_ns(&self) -> f64 {
+        self.iter_stats.mean_ns as f64 * self.iters as f64 / self.total_ops.max(1) as f64
+    }
+
+    /// Millions of lookups per second.
+    pub fn throughput(&self) -> f64 {
+        self.total_ops as f64 * 1e3 / (self.iter_stats.mean_ns as f64 * self.iters as f64).max(1.0)
+    }
+}
+
+pub fn load_results(path: &Path) -> Result<Vec<BenchResult>> {
+    let data = fs::read_to_string(path)
+        .map_err(|error| anyhow::anyhow!("failed to read results {:?}: {}", path, error))?;
+    serde_json::from_str(&data)
+        .map_err(|error| anyhow::anyhow!("invalid results {:?}: {}", path, error))
+}
@@ -0,0 +1,121 @@
+use {
+    crate::{environment::Environment, results::ResultRow},
+    anyhow::Result,
+    rusqlite::{params, Connection},
+    std::path::Path,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    started_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at TEXT,
+    version TEXT NOT NULL,
+    host TEXT,
+    os TEXT NOT NULL,
+    cpu TEXT,
+    cpus INTEGER,
+    memory TEXT,
+    allocator TEXT NOT NULL,
+    config TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS results (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    suite TEXT,
+    bench TEXT NOT NULL,
+    input TEXT NOT NULL,
+    set_size INTEGER NOT NULL,
+    threads INTEGER NOT NULL,
+    hit_rate REAL,
+    rayon_min_len INTEGER,
+    write_ratio REAL,
+    total_ops INTEGER NOT NULL,
+    iters INTEGER NOT NULL,
+    elapsed_ns INTEGER NOT NULL,
+    mean_ns INTEGER NOT NULL,
+    stddev_ns INTEGER NOT NULL,
+    cv REAL NOT NULL,
+    per_op_ns REAL NOT NULL,
+    success INTEGER NOT NULL,
+    filter_memory INTEGER,
+    rss_peak_kb INTEGER,
+    result TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS results_bench ON results (bench, set_size, threads);
+";
+
+/// SQLite database with history of benchmark runs: one row in `runs` per `bench` invocation
+/// (environment and config in JSON) and one row in `results` per benchmark, appended as soon
+/// as benchmark is finished, so interrupted runs keep finished results.
+pub struct ResultsDb {
+    connection: Connection,
+    run_id: i64,
+}
+
+impl ResultsDb {
+    pub fn open(path: &Path, allocator: &str, config: &str) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        let environment = Environment::collect();
+        connection.execute(
+            "INSERT INTO runs (version, host, os, cpu, cpus, memory, allocator, config)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                environment.version,
+                environment.host,
+                environment.os,
+                environment.cpu,
+                environment.cpus,
+                environment.memory,
+                allocator,
+                config
+            ],
+        )?;
+        let run_id = connection.last_insert_rowid();
+        Ok(Self { connection, run_id })
+    }
+
+    pub fn insert(&self, row: &ResultRow) -> Result<()> {
+        self.connection.execute(
+            "INSERT INTO results (run_id, suite, bench, input, set_size, threads, hit_rate,
+            rayon_min_len, write_ratio, total_ops, iters, elapsed_ns, mean_ns, stddev_ns, cv,
+            per_op_ns, success, filter_memory, rss_peak_kb, result)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17,
+            ?18, ?19, ?20)",
+            params![
+                self.run_id,
+                row.suite,
+                row.bench,
+                row.input,
+                row.set_size,
+                row.threads,
+                row.hit_rate,
+                row.rayon_min_len,
+                row.write_ratio,
+                row.total_ops,
+                row.iters,
+                row.elapsed_ns,
+                row.mean_ns,
+                row.stddev_ns,
+                row.cv,
+                row.elapsed_ns as f64 / row.total_ops.max(1) as f64,
+                row.success,
+                row.filter_memory,
+                row.rss_peak_kb,
+                row.result
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Set `finished_at` of the run, not set if run failed or was interrupted.
+    pub fn finish(&self) -> Result<()> {
+        self.connection.execute(
+            "UPDATE runs SET finished_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            params![self.run_id],
+        )?;
+        Ok(())
+    }
+}
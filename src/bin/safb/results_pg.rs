@@ -0,0 +1,168 @@
+use {
+    crate::{environment::Environment, results::ResultRow},
+    anyhow::Result,
+    std::process::Command,
+    tokio_postgres::{Client, NoTls},
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS safb_runs (
+    id BIGSERIAL PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ,
+    commit TEXT NOT NULL,
+    version TEXT NOT NULL,
+    host TEXT,
+    os TEXT NOT NULL,
+    cpu TEXT,
+    cpus BIGINT,
+    memory TEXT,
+    allocator TEXT NOT NULL,
+    config JSONB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS safb_results (
+    id BIGSERIAL PRIMARY KEY,
+    run_id BIGINT NOT NULL REFERENCES safb_runs (id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    commit TEXT NOT NULL,
+    dataset_hash TEXT NOT NULL,
+    host TEXT,
+    suite TEXT,
+    bench TEXT NOT NULL,
+    input TEXT NOT NULL,
+    set_size BIGINT NOT NULL,
+    threads BIGINT NOT NULL,
+    hit_rate DOUBLE PRECISION,
+    rayon_min_len BIGINT,
+    write_ratio DOUBLE PRECISION,
+    total_ops BIGINT NOT NULL,
+    iters BIGINT NOT NULL,
+    elapsed_ns BIGINT NOT NULL,
+    mean_ns BIGINT NOT NULL,
+    stddev_ns BIGINT NOT NULL,
+    cv DOUBLE PRECISION NOT NULL,
+    per_op_ns DOUBLE PRECISION NOT NULL,
+    success BIGINT NOT NULL,
+    filter_memory BIGINT,
+    rss_peak_kb BIGINT,
+    result JSONB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS safb_results_commit_dataset ON safb_results (commit, dataset_hash);
+";
+
+/// Central Postgres database of results from multiple machines, same layout as `--results-db`
+/// with results keyed by commit of the benchmarked code and SHA-256 of the dataset file.
+pub struct ResultsPg {
+    client: Client,
+    run_id: i64,
+    commit: String,
+    host: Option<String>,
+}
+
+impl ResultsPg {
+    /// Connect with connection string (`postgresql://user@host/db` or `host=... user=...`),
+    /// without TLS. Commit is `git rev-parse HEAD` of the current directory if not set.
+    pub async fn connect(
+        url: &str,
+        commit: Option<String>,
+        allocator: &str,
+        config: serde_json::Value,
+    ) -> Result<Self> {
+        let commit = match commit {
+            Some(commit) => commit,
+            None => git_commit()?,
+        };
+        let (client, connection) = tokio_postgres::connect(url, NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(error) = connection.await {
+                println!("Postgres connection failed: {}", error);
+            }
+        });
+        client.batch_execute(SCHEMA).await?;
+
+        let environment = Environment::collect();
+        let row = client
+            .query_one(
+                "INSERT INTO safb_runs (commit, version, host, os, cpu, cpus, memory, allocator, config)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id",
+                &[
+                    &commit,
+                    &environment.version,
+                    &environment.host,
+                    &environment.os,
+                    &environment.cpu,
+                    &environment.cpus.map(|cpus| cpus as i64),
+                    &environment.memory,
+                    &allocator,
+                    &config,
+                ],
+            )
+            .await?;
+        Ok(Self {
+            client,
+            run_id: row.get(0),
+            commit,
+            host: environment.host,
+        })
+    }
+
+    pub async fn insert(&self, dataset_hash: &str, row: &ResultRow<'_>) -> Result<()> {
+        let result: serde_json::Value = serde_json::from_str(&row.result)?;
+        self.client
+            .execute(
+                "INSERT INTO safb_results (run_id, commit, dataset_hash, host, suite, bench, input,
+                set_size, threads, hit_rate, rayon_min_len, write_ratio, total_ops, iters,
+                elapsed_ns, mean_ns, stddev_ns, cv, per_op_ns, success, filter_memory,
+                rss_peak_kb, result)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16,
+                $17, $18, $19, $20, $21, $22, $23)",
+                &[
+                    &self.run_id,
+                    &self.commit,
+                    &dataset_hash,
+                    &self.host,
+                    &row.suite,
+                    &row.bench,
+                    &row.input,
+                    &(row.set_size as i64),
+                    &(row.threads as i64),
+                    &row.hit_rate,
+                    &row.rayon_min_len.map(|min_len| min_len as i64),
+                    &row.write_ratio,
+                    &(row.total_ops as i64),
+                    &i64::from(row.iters),
+                    &(row.elapsed_ns as i64),
+                    &(row.mean_ns as i64),
+                    &(row.stddev_ns as i64),
+                    &row.cv,
+                    &(row.elapsed_ns as f64 / row.total_ops.max(1) as f64),
+                    &(row.success as i64),
+                    &row.filter_memory.map(|memory| memory as i64),
+                    &row.rss_peak_kb.map(|kb| kb as i64),
+                    &result,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Set `finished_at` of the run, not set if run failed or was interrupted.
+    pub async fn finish(&self) -> Result<()> {
+        self.client
+            .execute(
+                "UPDATE safb_runs SET finished_at = now() WHERE id = $1",
+                &[&self.run_id],
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+fn git_commit() -> Result<String> {
+    let output = Command::new("git").args(["rev-parse", "HEAD"]).output()?;
+    anyhow::ensure!(
+        output.status.success(),
+        "failed to get commit with `git rev-parse HEAD`, set `--commit`"
+    );
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
@@ -0,0 +1,230 @@
+use {
+    crate::{parse_fraction, GlobalArgs},
+    anyhow::Result,
+    hyper::{
+        body::HttpBody,
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, Server, StatusCode,
+    },
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    solana_accounts_filter_bench::{
+        bench::BenchConfig,
+        filter::{SharedFilter, SharedStrategy},
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::HashSet, convert::Infallible, net::SocketAddr, path::PathBuf, sync::Arc,
+        time::Instant,
+    },
+};
+
+/// Serve filter built from the dataset over HTTP, for latency tests from other services.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Address of HTTP server.
+    #[clap(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Number of Pubkeys in the filter set.
+    #[clap(long, default_value_t = 1_000_000)]
+    set_size: usize,
+
+    /// Fraction of unique Pubkeys from the data added to the filter set, rest is random.
+    #[clap(long, default_value_t = 0.0, parse(try_from_str = parse_fraction))]
+    hit_rate: f64,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Shared filter strategy: `mutex`, `rwlock`, `arcswap` or `left-right`.
+    #[clap(long, default_value_t = SharedStrategy::RwLock)]
+    strategy: SharedStrategy,
+
+    /// Maximum size of request body in bytes, larger requests are rejected with `413`.
+    #[clap(long, default_value_t = 16 * 1024 * 1024)]
+    max_body: usize,
+}
+
+/// Body of `POST /contains`.
+#[derive(Debug, Deserialize)]
+struct ContainsRequest {
+    pubkeys: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ContainsResponse {
+    matched: usize,
+    /// Time of lookups in the filter, without parsing and network.
+    elapsed_ns: u64,
+}
+
+/// Body of `POST /subscribe`.
+#[derive(Debug, Deserialize)]
+struct SubscribeRequest {
+    #[serde(default)]
+    insert: Vec<String>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct SubscribeResponse {
+    len: usize,
+    /// Time of the update, including waiting for readers.
+    elapsed_ns: u64,
+}
+
+/// Error of the request, answered with `status`, other errors are internal.
+#[derive(Debug, thiserror::Error)]
+#[error("{message}")]
+struct ClientError {
+    status: StatusCode,
+    message: String,
+}
+
+fn bad_request(message: impl ToString) -> anyhow::Error {
+    ClientError {
+        status: StatusCode::BAD_REQUEST,
+        message: message.to_string(),
+    }
+    .into()
+}
+
+fn parse_pubkeys(pubkeys: &[String]) -> Result<Vec<Pubkey>> {
+    pubkeys
+        .iter()
+        .map(|pubkey| {
+            pubkey
+                .parse()
+                .map_err(|error| bad_request(format!("invalid pubkey {:?}: {}", pubkey, error)))
+        })
+        .collect()
+}
+
+/// JSON body of the request, at most `max_body` bytes (checked by `content-length` and while
+/// reading, as the body can be chunked).
+async fn read_json<T: DeserializeOwned>(request: Request<Body>, max_body: usize) -> Result<T> {
+    let too_large = || ClientError {
+        status: StatusCode::PAYLOAD_TOO_LARGE,
+        message: format!("request body is larger than {} bytes", max_body),
+    };
+    let mut body = request.into_body();
+    if body.size_hint().lower() > max_body as u64 {
+        return Err(too_large().into());
+    }
+    let mut bytes = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(bad_request)?;
+        if bytes.len() + chunk.len() > max_body {
+            return Err(too_large().into());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&bytes).map_err(bad_request)
+}
+
+fn json_response<T: Serialize>(value: &T) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(value)?))?)
+}
+
+/// Filter calls of `mutex` and `rwlock` strategies block on locks, so they run on blocking
+/// threads and do not stall tokio workers serving other clients.
+async fn route(
+    request: Request<Body>,
+    filter: Arc<dyn SharedFilter>,
+    max_body: usize,
+) -> Result<Response<Body>> {
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    match (method, path.as_str()) {
+        (Method::POST, "/contains") => {
+            let request: ContainsRequest = read_json(request, max_body).await?;
+            let pubkeys = parse_pubkeys(&request.pubkeys)?;
+            let (matched, elapsed) = tokio::task::spawn_blocking(move || {
+                let ts = Instant::now();
+                (filter.contains_many(&pubkeys), ts.elapsed())
+            })
+            .await?;
+            json_response(&ContainsResponse {
+                matched,
+                elapsed_ns: elapsed.as_nanos() as u64,
+            })
+        }
+        (Method::POST, "/subscribe") => {
+            let request: SubscribeRequest = read_json(request, max_body).await?;
+            let insert = parse_pubkeys(&request.insert)?;
+            let remove = parse_pubkeys(&request.remove)?;
+            let (len, elapsed) = tokio::task::spawn_blocking(move || {
+                let ts = Instant::now();
+                filter.update(&insert, &remove);
+                (filter.len(), ts.elapsed())
+            })
+            .await?;
+            json_response(&SubscribeResponse {
+                len,
+                elapsed_ns: elapsed.as_nanos() as u64,
+            })
+        }
+        _ => Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::from(
+                "not found, use `POST /contains` or `POST /subscribe`\n",
+            ))?),
+    }
+}
+
+async fn handle(
+    request: Request<Body>,
+    filter: Arc<dyn SharedFilter>,
+    max_body: usize,
+) -> Result<Response<Body>, Infallible> {
+    Ok(match route(request, filter, max_body).await {
+        Ok(response) => response,
+        Err(error) => {
+            let mut response = Response::new(Body::from(format!("{}\n", error)));
+            *response.status_mut() = error
+                .downcast_ref::<ClientError>()
+                .map_or(StatusCode::INTERNAL_SERVER_ERROR, |error| error.status);
+            response
+        }
+    })
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let dataset = global.load_dataset(&args.input)?;
+    let config = BenchConfig::builder(&dataset)
+        .set_size(args.set_size)
+        .hit_rate(args.hit_rate)
+        .seed(args.seed)
+        .build()?;
+    let ts = Instant::now();
+    let set = config.fill(HashSet::<Pubkey>::with_capacity(args.set_size));
+    if !global.quiet {
+        println!("Fill HashSet with len {} in: {:?}", set.len(), ts.elapsed());
+    }
+    let filter = args.strategy.shared(set);
+    let max_body = args.max_body;
+
+    let make_service = make_service_fn(move |_conn| {
+        let filter = Arc::clone(&filter);
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle(request, Arc::clone(&filter), max_body)
+            }))
+        }
+    });
+    let server = Server::try_bind(&args.listen)?.serve(make_service);
+    println!(
+        "Listening on http://{} ({} strategy)",
+        server.local_addr(),
+        args.strategy
+    );
+    server.await.map_err(Into::into)
+}
@@ -0,0 +1,471 @@
+use {
+    crate::{
+        compare::{self, Change},
+        results::BenchResult,
+        GlobalArgs,
+    },
+    anyhow::Result,
+    hyper::{
+        service::{make_service_fn, service_fn},
+        Body, Method, Request, Response, Server, StatusCode,
+    },
+    rusqlite::{params, params_from_iter, types::Value, Connection, OpenFlags, Row},
+    serde::Serialize,
+    std::{
+        collections::{BTreeMap, HashMap},
+        convert::Infallible,
+        net::SocketAddr,
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    },
+};
+
+/// Serve read-only REST API over results database of `bench --results-db` for dashboards.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// SQLite database written by `bench --results-db`.
+    #[clap(long, parse(from_os_str))]
+    results_db: PathBuf,
+
+    /// Address of HTTP server.
+    #[clap(long, default_value = "127.0.0.1:8081")]
+    listen: SocketAddr,
+}
+
+const RUN_COLUMNS: &str = "runs.id, runs.started_at, runs.finished_at, runs.version, runs.host,
+    runs.os, runs.cpu, runs.cpus, runs.memory, runs.allocator,
+    (SELECT COUNT(*) FROM results WHERE results.run_id = runs.id)";
+
+const RESULT_COLUMNS: &str = "results.id, results.run_id, results.created_at, runs.host,
+    results.suite, results.bench, results.input, results.set_size, results.threads,
+    results.hit_rate, results.rayon_min_len, results.write_ratio, results.total_ops,
+    results.iters, results.elapsed_ns, results.mean_ns, results.stddev_ns, results.cv,
+    results.per_op_ns, results.success, results.filter_memory, results.rss_peak_kb";
+
+/// Row of `runs` with number of results, config only for a single run.
+#[derive(Debug, Serialize)]
+struct Run {
+    id: i64,
+    started_at: String,
+    /// Not set if run failed, was interrupted or is still running.
+    finished_at: Option<String>,
+    version: String,
+    host: Option<String>,
+    os: String,
+    cpu: Option<String>,
+    cpus: Option<i64>,
+    memory: Option<String>,
+    allocator: String,
+    results: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<serde_json::Value>,
+}
+
+impl Run {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            started_at: row.get(1)?,
+            finished_at: row.get(2)?,
+            version: row.get(3)?,
+            host: row.get(4)?,
+            os: row.get(5)?,
+            cpu: row.get(6)?,
+            cpus: row.get(7)?,
+            memory: row.get(8)?,
+            allocator: row.get(9)?,
+            results: row.get(10)?,
+            config: None,
+        })
+    }
+}
+
+/// Row of `results` with host of the run, without full result JSON.
+#[derive(Debug, Serialize)]
+struct Metrics {
+    id: i64,
+    run_id: i64,
+    created_at: String,
+    host: Option<String>,
+    suite: Option<String>,
+    bench: String,
+    input: String,
+    set_size: i64,
+    threads: i64,
+    hit_rate: Option<f64>,
+    rayon_min_len: Option<i64>,
+    write_ratio: Option<f64>,
+    total_ops: i64,
+    iters: i64,
+    elapsed_ns: i64,
+    mean_ns: i64,
+    stddev_ns: i64,
+    cv: f64,
+    per_op_ns: f64,
+    success: i64,
+    filter_memory: Option<i64>,
+    rss_peak_kb: Option<i64>,
+}
+
+impl Metrics {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            run_id: row.get(1)?,
+            created_at: row.get(2)?,
+            host: row.get(3)?,
+            suite: row.get(4)?,
+            bench: row.get(5)?,
+            input: row.get(6)?,
+            set_size: row.get(7)?,
+            threads: row.get(8)?,
+            hit_rate: row.get(9)?,
+            rayon_min_len: row.get(10)?,
+            write_ratio: row.get(11)?,
+            total_ops: row.get(12)?,
+            iters: row.get(13)?,
+            elapsed_ns: row.get(14)?,
+            mean_ns: row.get(15)?,
+            stddev_ns: row.get(16)?,
+            cv: row.get(17)?,
+            per_op_ns: row.get(18)?,
+            success: row.get(19)?,
+            filter_memory: row.get(20)?,
+            rss_peak_kb: row.get(21)?,
+        })
+    }
+}
+
+/// Benchmark present in both runs of `GET /compare`.
+#[derive(Debug, Serialize)]
+struct ComparedBench {
+    key: String,
+    base_mean_ns: u64,
+    new_mean_ns: u64,
+    /// Relative change of mean iteration time.
+    delta: f64,
+    base_per_op_ns: f64,
+    new_per_op_ns: f64,
+    change: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct Comparison {
+    base: i64,
+    new: i64,
+    threshold: f64,
+    regressions: usize,
+    improvements: usize,
+    benchmarks: Vec<ComparedBench>,
+    only_base: Vec<String>,
+    only_new: Vec<String>,
+}
+
+const fn change_name(change: Change) -> &'static str {
+    match change {
+        Change::Insignificant => "insignificant",
+        Change::Regression => "regression",
+        Change::Improvement => "improvement",
+    }
+}
+
+struct ResultsApi {
+    connection: Mutex<Connection>,
+}
+
+impl ResultsApi {
+    /// `GET /runs?host=&limit=`, latest first.
+    fn runs(&self, query: &HashMap<String, String>) -> Result<Vec<Run>> {
+        let connection = self.connection.lock().expect("poisoned");
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM runs WHERE (?1 IS NULL OR runs.host = ?1) ORDER BY runs.id DESC
+            LIMIT ?2",
+            RUN_COLUMNS
+        ))?;
+        let runs = statement
+            .query_map(
+                params![query.get("host"), limit(query, 100)?],
+                Run::from_row,
+            )?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(runs)
+    }
+
+    /// `GET /runs/{id}` with config of the run.
+    fn run(&self, id: i64) -> Result<Option<Run>> {
+        let connection = self.connection.lock().expect("poisoned");
+        let mut statement = connection.prepare(&format!(
+            "SELECT {}, runs.config FROM runs WHERE runs.id = ?1",
+            RUN_COLUMNS
+        ))?;
+        let mut rows = statement.query(params![id])?;
+        let row = match rows.next()? {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let mut run = Run::from_row(row)?;
+        run.config = Some(serde_json::from_str(&row.get::<_, String>(11)?)?);
+        Ok(Some(run))
+    }
+
+    /// `GET /results?run=&bench=&host=&input=&set_size=&threads=&limit=&order=`, latest first,
+    /// with `order=asc` oldest first, so dashboards can plot metrics of a benchmark over time.
+    /// Input matches by path or file name.
+    fn results(&self, query: &HashMap<String, String>) -> Result<Vec<Metrics>> {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        for (name, column) in [
+            ("run", "results.run_id"),
+            ("bench", "results.bench"),
+            ("host", "runs.host"),
+            ("suite", "results.suite"),
+            ("set_size", "results.set_size"),
+            ("threads", "results.threads"),
+        ] {
+            if let Some(value) = query.get(name) {
+                conditions.push(format!("{} = ?{}", column, values.len() + 1));
+                values.push(match value.parse::<i64>() {
+                    Ok(value) if column != "results.bench" => Value::Integer(value),
+                    _ => Value::Text(value.clone()),
+                });
+            }
+        }
+        if let Some(input) = query.get("input") {
+            let index = values.len() + 1;
+            conditions.push(format!(
+                "(results.input = ?{0} OR results.input LIKE '%/' || ?{0})",
+                index
+            ));
+            values.push(Value::Text(input.clone()));
+        }
+        let order = match query.get("order").map(String::as_str) {
+            None | Some("desc") => "DESC",
+            Some("asc") => "ASC",
+            Some(order) => {
+                return Err(bad_request(format!(
+                    "invalid `order`: {}, use `asc` or `desc`",
+                    order
+                )))
+            }
+        };
+        values.push(Value::Integer(limit(query, 1000)?));
+        let filter = match conditions.is_empty() {
+            true => String::new(),
+            false => format!("WHERE {}", conditions.join(" AND ")),
+        };
+
+        let connection = self.connection.lock().expect("poisoned");
+        let mut statement = connection.prepare(&format!(
+            "SELECT {} FROM results JOIN runs ON runs.id = results.run_id {}
+            ORDER BY results.created_at {order}, results.id {order} LIMIT ?{}",
+            RESULT_COLUMNS,
+            filter,
+            values.len(),
+            order = order,
+        ))?;
+        let results = statement
+            .query_map(params_from_iter(values), Metrics::from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(results)
+    }
+
+    /// Full results of the run by benchmark and parameters, as `compare` keys them.
+    fn bench_results(&self, run: i64) -> Result<BTreeMap<String, BenchResult>> {
+        let connection = self.connection.lock().expect("poisoned");
+        let mut statement =
+            connection.prepare("SELECT result FROM results WHERE run_id = ?1 ORDER BY id")?;
+        let rows = statement.query_map(params![run], |row| row.get::<_, String>(0))?;
+        let mut results = BTreeMap::new();
+        for row in rows {
+            let result = serde_json::from_str::<BenchResult>(&row?)?;
+            results.insert(result.key(), result);
+        }
+        Ok(results)
+    }
+
+    /// `GET /compare?base=&new=&threshold=`, same significance test as `compare`.
+    fn compare(&self, query: &HashMap<String, String>) -> Result<Comparison> {
+        let run = |name| -> Result<i64> {
+            query
+                .get(name)
+                .ok_or_else(|| bad_request(format!("`{}` run id is required", name)))?
+                .parse()
+                .map_err(|error| bad_request(format!("invalid `{}`: {}", name, error)))
+        };
+        let (base, new) = (run("base")?, run("new")?);
+        let threshold = match query.get("threshold") {
+            Some(threshold) => threshold
+                .parse()
+                .map_err(|error| bad_request(format!("invalid `threshold`: {}", error)))?,
+            None => 0.05,
+        };
+        let base_results = self.bench_results(base)?;
+        let new_results = self.bench_results(new)?;
+
+        let mut comparison = Comparison {
+            base,
+            new,
+            threshold,
+            regressions: 0,
+            improvements: 0,
+            benchmarks: vec![],
+            only_base: vec![],
+            only_new: vec![],
+        };
+        for (key, new_result) in new_results.iter() {
+            let base_result = match base_results.get(key) {
+                Some(base_result) => base_result,
+                None => {
+                    comparison.only_new.push(key.clone());
+                    continue;
+                }
+            };
+            let change = compare::change(base_result, new_result, threshold);
+            match change {
+                Change::Regression => comparison.regressions += 1,
+                Change::Improvement => comparison.improvements += 1,
+                Change::Insignificant => {}
+            }
+            comparison.benchmarks.push(ComparedBench {
+                key: key.clone(),
+                base_mean_ns: base_result.iter_stats.mean_ns,
+                new_mean_ns: new_result.iter_stats.mean_ns,
+                delta: new_result.iter_stats.mean_ns as f64
+                    / base_result.iter_stats.mean_ns.max(1) as f64
+                    - 1.0,
+                base_per_op_ns: base_result.per_op_ns(),
+                new_per_op_ns: new_result.per_op_ns(),
+                change: change_name(change),
+            });
+        }
+        comparison.only_base = base_results
+            .keys()
+            .filter(|key| !new_results.contains_key(*key))
+            .cloned()
+            .collect();
+        Ok(comparison)
+    }
+}
+
+fn limit(query: &HashMap<String, String>, default: i64) -> Result<i64> {
+    match query.get("limit") {
+        Some(limit) => limit
+            .parse()
+            .map_err(|error| bad_request(format!("invalid `limit`: {}", error))),
+        None => Ok(default),
+    }
+}
+
+/// Invalid parameters of the request, other errors (database, stored results) are internal.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+struct BadRequest(String);
+
+fn bad_request(message: String) -> anyhow::Error {
+    BadRequest(message).into()
+}
+
+/// Id of the run in the path.
+fn run_id(id: &str) -> Result<i64> {
+    id.parse()
+        .map_err(|error| bad_request(format!("invalid run id: {}", error)))
+}
+
+fn json_response<T: Serialize>(value: &T) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_vec(value)?))?)
+}
+
+fn not_found(message: &str) -> Result<Response<Body>> {
+    Ok(Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .body(Body::from(format!("{}\n", message)))?)
+}
+
+fn route(request: &Request<Body>, api: &ResultsApi) -> Result<Response<Body>> {
+    if request.method() != Method::GET {
+        return Ok(Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .body(Body::from("read-only API, use `GET`\n"))?);
+    }
+    let query = request
+        .uri()
+        .query()
+        .map(|query| {
+            form_urlencoded::parse(query.as_bytes())
+                .into_owned()
+                .collect()
+        })
+        .unwrap_or_default();
+    let segments = request
+        .uri()
+        .path()
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    match segments.as_slice() {
+        ["runs"] => json_response(&api.runs(&query)?),
+        ["runs", id] => match api.run(run_id(id)?)? {
+            Some(run) => json_response(&run),
+            None => not_found("run not found"),
+        },
+        ["runs", id, "results"] => {
+            let mut query = query;
+            query.insert("run".to_owned(), run_id(id)?.to_string());
+            json_response(&api.results(&query)?)
+        }
+        ["results"] => json_response(&api.results(&query)?),
+        ["compare"] => json_response(&api.compare(&query)?),
+        _ => not_found(
+            "not found, use `GET /runs`, `/runs/{id}`, `/runs/{id}/results`, `/results` or `/compare`",
+        ),
+    }
+}
+
+/// SQLite calls block, so requests are routed on blocking threads.
+async fn handle(
+    request: Request<Body>,
+    api: Arc<ResultsApi>,
+) -> Result<Response<Body>, Infallible> {
+    let routed = tokio::task::spawn_blocking(move || route(&request, &api))
+        .await
+        .map_err(Into::into)
+        .and_then(|routed| routed);
+    Ok(match routed {
+        Ok(response) => response,
+        Err(error) => {
+            let mut response = Response::new(Body::from(format!("{}\n", error)));
+            *response.status_mut() = match error.is::<BadRequest>() {
+                true => StatusCode::BAD_REQUEST,
+                false => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            response
+        }
+    })
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    // read-only, so `bench` can append results while API is served
+    let connection = Connection::open_with_flags(
+        &args.results_db,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    let api = Arc::new(ResultsApi {
+        connection: Mutex::new(connection),
+    });
+    if !global.quiet {
+        println!("Runs in {}: {}", args.results_db.display(), {
+            let query = HashMap::from([("limit".to_owned(), "-1".to_owned())]);
+            api.runs(&query)?.len()
+        });
+    }
+
+    let make_service = make_service_fn(move |_conn| {
+        let api = Arc::clone(&api);
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle(request, Arc::clone(&api)))) }
+    });
+    let server = Server::try_bind(&args.listen)?.serve(make_service);
+    println!("Results API on http://{}", server.local_addr());
+    server.await.map_err(Into::into)
+}
@@ -0,0 +1,51 @@
+use {
+    crate::GlobalArgs, anyhow::Result, solana_accounts_filter_bench::dataset::Subsample,
+    std::path::PathBuf,
+};
+
+/// Extract smaller dataset from a large capture for quick runs.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Keep every Nth block.
+    #[clap(
+        long,
+        conflicts_with = "unique-keys",
+        required_unless_present = "unique-keys"
+    )]
+    every: Option<usize>,
+
+    /// Keep consecutive blocks from the start until they have this number of unique Pubkeys.
+    #[clap(long)]
+    unique_keys: Option<usize>,
+
+    /// Out file for the data, format by extension: `.json`, `.jsonl`, `.bin`, `.msgpack`, `.arrow`, optionally with `.gz` or `.zst`
+    #[clap(short, long, parse(from_os_str))]
+    out: PathBuf,
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let subsample = match (args.every, args.unique_keys) {
+        (Some(every), _) => {
+            anyhow::ensure!(every > 0, "every should be positive");
+            Subsample::EveryNth(every)
+        }
+        (None, Some(unique_keys)) => Subsample::UniqueKeys(unique_keys),
+        (None, None) => anyhow::bail!("--every or --unique-keys is required"),
+    };
+
+    let dataset = global.load_dataset(&args.input)?;
+    let subsampled = dataset.subsample(subsample);
+    global.save_dataset(&subsampled, &args.out)?;
+
+    println!(
+        "Total {} of {} blocks, with {} pubkeys",
+        subsampled.blocks.len(),
+        dataset.blocks.len(),
+        subsampled.unique_pubkeys()
+    );
+    Ok(())
+}
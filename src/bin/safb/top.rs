@@ -0,0 +1,83 @@
+use {
+    crate::GlobalArgs,
+    anyhow::Result,
+    serde::Serialize,
+    solana_accounts_filter_bench::known::known_name,
+    std::{fs, path::PathBuf},
+};
+
+/// Print the most frequently referenced accounts of the dataset.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// Input file with the data
+    #[clap(short, long, default_value = "data.json", parse(from_os_str))]
+    input: PathBuf,
+
+    /// Number of accounts to print.
+    #[clap(short = 'n', long, default_value_t = 20)]
+    top: usize,
+
+    /// Optional output file for the list in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize)]
+struct TopAccount {
+    pubkey: String,
+    name: Option<&'static str>,
+    /// Number of blocks referencing the account.
+    count: usize,
+    /// Fraction of all references in the dataset.
+    share: f64,
+    /// Fraction of blocks referencing the account.
+    blocks_fraction: f64,
+}
+
+pub fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    let dataset = global.load_dataset(&args.input)?;
+    let mut counts = dataset.key_counts().into_iter().collect::<Vec<_>>();
+    counts.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let total = counts.iter().map(|(_pubkey, count)| count).sum::<usize>();
+
+    let top = counts
+        .iter()
+        .take(args.top)
+        .map(|(pubkey, count)| TopAccount {
+            pubkey: pubkey.to_string(),
+            name: known_name(pubkey),
+            count: *count,
+            share: *count as f64 / total.max(1) as f64,
+            blocks_fraction: *count as f64 / dataset.blocks.len().max(1) as f64,
+        })
+        .collect::<Vec<_>>();
+
+    if !global.quiet {
+        println!(
+            "Blocks: {}, references: {}, unique pubkeys: {}",
+            dataset.blocks.len(),
+            total,
+            counts.len()
+        );
+    }
+    println!(
+        "{:>4} {:<44} {:>10} {:>7} {:>7}  name",
+        "#", "pubkey", "count", "share", "blocks"
+    );
+    for (index, account) in top.iter().enumerate() {
+        println!(
+            "{:>4} {:<44} {:>10} {:>6.2}% {:>6.2}%  {}",
+            index + 1,
+            account.pubkey,
+            account.count,
+            account.share * 100.0,
+            account.blocks_fraction * 100.0,
+            account.name.unwrap_or("")
+        );
+    }
+
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_string_pretty(&top)?)?;
+    }
+    Ok(())
+}
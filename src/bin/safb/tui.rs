@@ -0,0 +1,437 @@
+use {
+    crate::bench::Rss,
+    anyhow::Result,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    ratatui::{
+        backend::CrosstermBackend,
+        layout::{Constraint, Direction, Layout, Rect},
+        style::{Color, Modifier, Style},
+        text::Line,
+        widgets::{Block, Borders, LineGauge, Paragraph, Sparkline},
+        Frame, Terminal,
+    },
+    solana_accounts_filter_bench::bench::IterProgress,
+    std::{
+        collections::VecDeque,
+        fs::File,
+        io::{self, BufRead, BufReader, BufWriter, IsTerminal, Write},
+        os::unix::io::{FromRawFd, RawFd},
+        path::Path,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Mutex,
+        },
+        thread::{self, JoinHandle},
+        time::{Duration, Instant},
+    },
+};
+
+/// Interval of redraws and key polls.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Window of rolling throughput.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Iterations of the running benchmark used for percentiles and the sparkline.
+const RECENT_ITERS: usize = 10_000;
+
+/// Run of the current suite and input.
+struct RunState {
+    label: String,
+    progress: f64,
+    per_op_ns: Option<f64>,
+}
+
+#[derive(Default)]
+struct State {
+    suite: Option<String>,
+    input: String,
+    runs: Vec<RunState>,
+    current: Option<usize>,
+    min_work: Duration,
+    iters: u32,
+    elapsed: Duration,
+    /// Time and lookups of iterations in `THROUGHPUT_WINDOW`.
+    window: VecDeque<(Instant, usize)>,
+    /// Time per Pubkey of the last iterations.
+    per_op_ns: VecDeque<f64>,
+    filter_memory: Option<usize>,
+    /// Everything printed to stdout while the dashboard is shown.
+    log: Vec<String>,
+}
+
+impl State {
+    fn throughput(&self) -> f64 {
+        match (self.window.front(), self.window.back()) {
+            (Some((first, _)), Some((last, _))) if last > first => {
+                let ops = self
+                    .window
+                    .iter()
+                    .skip(1)
+                    .map(|(_ts, ops)| ops)
+                    .sum::<usize>();
+                ops as f64 / (*last - *first).as_secs_f64()
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn percentile(sorted: &[f64], percentile: usize) -> f64 {
+        sorted
+            .get(sorted.len() * percentile / 100)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn draw(&self, frame: &mut Frame, started: Instant) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(9),
+                Constraint::Length(10),
+            ])
+            .split(frame.size());
+        let header = format!(
+            " safb bench | suite: {} | input: {} | run {}/{} | {:?} | q to quit",
+            self.suite.as_deref().unwrap_or("-"),
+            self.input,
+            self.current.map_or(0, |index| index + 1),
+            self.runs.len(),
+            Duration::from_secs(started.elapsed().as_secs())
+        );
+        frame.render_widget(
+            Paragraph::new(header).style(Style::default().add_modifier(Modifier::REVERSED)),
+            rows[0],
+        );
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(rows[1]);
+        self.draw_runs(frame, columns[0]);
+        let stats = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(7), Constraint::Min(3)])
+            .split(columns[1]);
+        self.draw_stats(frame, stats[0]);
+        self.draw_sparkline(frame, stats[1]);
+
+        let lines = rows[2].height.saturating_sub(2) as usize;
+        let log = self.log[self.log.len().saturating_sub(lines)..]
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(log).block(Block::default().borders(Borders::ALL).title("Log")),
+            rows[2],
+        );
+    }
+
+    fn draw_runs(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("Runs");
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // keep the running benchmark in the middle of long suites
+        let height = inner.height as usize;
+        let current = self.current.unwrap_or_default();
+        let start = current
+            .saturating_sub(height / 2)
+            .min(self.runs.len().saturating_sub(height));
+        let width = self
+            .runs
+            .iter()
+            .map(|run| run.label.len())
+            .max()
+            .unwrap_or_default()
+            .min(inner.width as usize / 2);
+        for (row, (index, run)) in self
+            .runs
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(height)
+            .enumerate()
+        {
+            let color = match self.current {
+                Some(current) if index < current => Color::Green,
+                Some(current) if index == current => Color::Yellow,
+                _ => Color::DarkGray,
+            };
+            let per_op = run
+                .per_op_ns
+                .map_or(String::new(), |ns| format!("{:.2}ns", ns));
+            let label = format!("{:<width$.width$} {:>9}", run.label, per_op, width = width);
+            let gauge = LineGauge::default()
+                .ratio(run.progress.clamp(0.0, 1.0))
+                .label(label)
+                .gauge_style(Style::default().fg(color));
+            let area = Rect {
+                y: inner.y + row as u16,
+                height: 1,
+                ..inner
+            };
+            frame.render_widget(gauge, area);
+        }
+    }
+
+    fn draw_stats(&self, frame: &mut Frame, area: Rect) {
+        let mut sorted = self.per_op_ns.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable_by(f64::total_cmp);
+        let (current, peak) = Rss::read().map_or((0, 0), |rss| (rss.current, rss.peak));
+        let label = self
+            .current
+            .and_then(|index| self.runs.get(index))
+            .map_or("-", |run| run.label.as_str());
+        let lines = vec![
+            Line::from(format!("Bench: {}", label)),
+            Line::from(format!(
+                "Iterations: {}, measured {:.1?} of {:?}",
+                self.iters, self.elapsed, self.min_work
+            )),
+            Line::from(format!(
+                "Throughput: {:.3}M pubkeys/s (last {:?})",
+                self.throughput() / 1e6,
+                THROUGHPUT_WINDOW
+            )),
+            Line::from(format!(
+                "Per pubkey: p50 {:.2}ns, p90 {:.2}ns, p99 {:.2}ns",
+                Self::percentile(&sorted, 50),
+                Self::percentile(&sorted, 90),
+                Self::percentile(&sorted, 99)
+            )),
+            Line::from(format!(
+                "Memory: RSS {} MiB, peak {} MiB, last filter {}",
+                current / 1024,
+                peak / 1024,
+                self.filter_memory
+                    .map_or("-".to_owned(), |bytes| format!("{} KiB", bytes / 1024))
+            )),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Current")),
+            area,
+        );
+    }
+
+    fn draw_sparkline(&self, frame: &mut Frame, area: Rect) {
+        // per iteration throughput in thousands of Pubkeys per second, latest on the right
+        let width = area.width.saturating_sub(2) as usize;
+        let data = self
+            .per_op_ns
+            .iter()
+            .skip(self.per_op_ns.len().saturating_sub(width))
+            .map(|ns| (1e6 / ns.max(f64::MIN_POSITIVE)) as u64)
+            .collect::<Vec<_>>();
+        frame.render_widget(
+            Sparkline::default()
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Throughput per iteration"),
+                )
+                .data(&data)
+                .style(Style::default().fg(Color::Cyan)),
+            area,
+        );
+    }
+}
+
+/// Stdout redirected into a pipe, lines are appended to the log of the dashboard.
+struct Capture {
+    saved: RawFd,
+    reader: JoinHandle<()>,
+}
+
+impl Capture {
+    fn start(state: Arc<Mutex<State>>) -> io::Result<Self> {
+        let mut fds = [0; 2];
+        io::stdout().flush()?;
+        // SAFETY: plain fd syscalls, results are checked
+        unsafe {
+            if libc::pipe(fds.as_mut_ptr()) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let saved = libc::dup(libc::STDOUT_FILENO);
+            if saved < 0 || libc::dup2(fds[1], libc::STDOUT_FILENO) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            libc::close(fds[1]);
+            let pipe = File::from_raw_fd(fds[0]);
+            let reader = thread::spawn(move || {
+                for line in BufReader::new(pipe).lines().map_while(Result::ok) {
+                    state.lock().expect("poisoned").log.push(line);
+                }
+            });
+            Ok(Self { saved, reader })
+        }
+    }
+
+    /// Terminal behind the redirected stdout.
+    fn terminal(&self) -> io::Result<File> {
+        // SAFETY: `saved` is open until `stop`
+        let fd = unsafe { libc::dup(self.saved) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `fd` is a new descriptor owned by the file
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Restore stdout, the pipe is closed, so the reader gets the rest of the output.
+    fn stop(self) {
+        let _ = io::stdout().flush();
+        // SAFETY: `saved` is a valid descriptor duplicated in `start`
+        unsafe {
+            libc::dup2(self.saved, libc::STDOUT_FILENO);
+            libc::close(self.saved);
+        }
+        let _ = self.reader.join();
+    }
+}
+
+/// Terminal dashboard of `bench --tui`: progress of every run of the suite, rolling throughput,
+/// percentiles of time per Pubkey and memory of the running benchmark. Output of the run is
+/// captured into the log pane and printed once the dashboard is closed.
+pub struct Dashboard {
+    state: Arc<Mutex<State>>,
+    stop: Arc<AtomicBool>,
+    ui: Option<(JoinHandle<()>, Capture)>,
+}
+
+impl Dashboard {
+    pub fn start(min_work: Duration) -> Result<Self> {
+        anyhow::ensure!(io::stdout().is_terminal(), "--tui requires a terminal");
+        let state = Arc::new(Mutex::new(State {
+            min_work,
+            ..State::default()
+        }));
+        let capture = Capture::start(Arc::clone(&state))?;
+        let mut terminal =
+            Terminal::new(CrosstermBackend::new(BufWriter::new(capture.terminal()?)))?;
+        terminal::enable_raw_mode()?;
+        execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let ui = thread::spawn({
+            let state = Arc::clone(&state);
+            let stop = Arc::clone(&stop);
+            let started = Instant::now();
+            let saved = capture.saved;
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let _ = terminal.draw(|frame| {
+                        state.lock().expect("poisoned").draw(frame, started);
+                    });
+                    if !event::poll(TICK).unwrap_or(false) {
+                        continue;
+                    }
+                    if let Ok(Event::Key(key)) = event::read() {
+                        let ctrl_c = key.code == KeyCode::Char('c')
+                            && key.modifiers.contains(KeyModifiers::CONTROL);
+                        if key.kind == KeyEventKind::Press
+                            && (key.code == KeyCode::Char('q') || ctrl_c)
+                        {
+                            break;
+                        }
+                    }
+                }
+                let _ = terminal::disable_raw_mode();
+                let _ = execute!(terminal.backend_mut(), LeaveAlternateScreen);
+                if !stop.load(Ordering::Relaxed) {
+                    // quit by the user, the run is interrupted as with Ctrl-C without dashboard
+                    let _ = io::stdout().flush();
+                    // SAFETY: `saved` is open, `stop` of the capture is never called after exit
+                    unsafe { libc::dup2(saved, libc::STDOUT_FILENO) };
+                    for line in state.lock().expect("poisoned").log.iter() {
+                        println!("{}", line);
+                    }
+                    println!("Interrupted");
+                    std::process::exit(130);
+                }
+            }
+        });
+        Ok(Self {
+            state,
+            stop,
+            ui: Some((ui, capture)),
+        })
+    }
+
+    /// Runs of the suite over the input, replace runs of the previous one.
+    pub fn runs(&self, suite: Option<&str>, input: &Path, labels: Vec<String>) {
+        let mut state = self.state.lock().expect("poisoned");
+        state.suite = suite.map(ToOwned::to_owned);
+        state.input = input.display().to_string();
+        state.runs = labels
+            .into_iter()
+            .map(|label| RunState {
+                label,
+                progress: 0.0,
+                per_op_ns: None,
+            })
+            .collect();
+        state.current = None;
+    }
+
+    /// Next run of the suite is started, runs are started in order of `runs`.
+    pub fn start_run(&self) {
+        let mut state = self.state.lock().expect("poisoned");
+        state.current = Some(state.current.map_or(0, |index| index + 1));
+        state.iters = 0;
+        state.elapsed = Duration::ZERO;
+        state.window.clear();
+        state.per_op_ns.clear();
+    }
+
+    /// Progress of the running benchmark, for `BenchConfig::on_iter`.
+    pub fn iter(&self, progress: IterProgress) {
+        let now = Instant::now();
+        let mut state = self.state.lock().expect("poisoned");
+        state.iters = progress.iters;
+        state.elapsed = progress.elapsed;
+        state.window.push_back((now, progress.ops));
+        while let Some((ts, _ops)) = state.window.front() {
+            if now.duration_since(*ts) <= THROUGHPUT_WINDOW {
+                break;
+            }
+            state.window.pop_front();
+        }
+        if state.per_op_ns.len() == RECENT_ITERS {
+            state.per_op_ns.pop_front();
+        }
+        let per_op_ns = progress.iter_elapsed.as_nanos() as f64 / progress.ops.max(1) as f64;
+        state.per_op_ns.push_back(per_op_ns);
+        let ratio = progress.elapsed.as_secs_f64() / state.min_work.as_secs_f64().max(1e-9);
+        if let Some(run) = state.current.and_then(|index| state.runs.get_mut(index)) {
+            run.progress = ratio;
+        }
+    }
+
+    pub fn finish_run(&self, per_op_ns: f64, filter_memory: Option<usize>) {
+        let mut state = self.state.lock().expect("poisoned");
+        state.filter_memory = filter_memory;
+        if let Some(run) = state.current.and_then(|index| state.runs.get_mut(index)) {
+            run.progress = 1.0;
+            run.per_op_ns = Some(per_op_ns);
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        if let Some((ui, capture)) = self.ui.take() {
+            self.stop.store(true, Ordering::Relaxed);
+            let _ = ui.join();
+            capture.stop();
+            for line in self.state.lock().expect("poisoned").log.iter() {
+                println!("{}", line);
+            }
+        }
+    }
+}
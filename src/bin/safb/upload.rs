@@ -0,0 +1,281 @@
+use {
+    crate::environment::Environment,
+    anyhow::Result,
+    hmac::{Hmac, Mac},
+    serde::Serialize,
+    sha2::{Digest, Sha256},
+    solana_accounts_filter_bench::dataset::sha256_file,
+    std::{env, fmt, path::Path, str::FromStr},
+};
+
+/// Object storage of `--upload`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Storage {
+    S3,
+    Gcs,
+}
+
+/// Destination of `--upload`: `s3://bucket/prefix` or `gs://bucket/prefix`.
+#[derive(Debug, Clone)]
+pub struct UploadUrl {
+    storage: Storage,
+    bucket: String,
+    prefix: String,
+}
+
+impl FromStr for UploadUrl {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (storage, path) = if let Some(path) = s.strip_prefix("s3://") {
+            (Storage::S3, path)
+        } else if let Some(path) = s.strip_prefix("gs://") {
+            (Storage::Gcs, path)
+        } else {
+            return Err(format!(
+                "expected s3://bucket/prefix or gs://bucket/prefix: {}",
+                s
+            ));
+        };
+        let (bucket, prefix) = path.split_once('/').unwrap_or((path, ""));
+        if bucket.is_empty() {
+            return Err(format!("bucket is required: {}", s));
+        }
+        Ok(Self {
+            storage,
+            bucket: bucket.to_owned(),
+            prefix: prefix.trim_matches('/').to_owned(),
+        })
+    }
+}
+
+impl fmt::Display for UploadUrl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scheme = match self.storage {
+            Storage::S3 => "s3",
+            Storage::Gcs => "gs",
+        };
+        write!(f, "{}://{}/{}", scheme, self.bucket, self.prefix)
+    }
+}
+
+/// Uploaded files with checksums, rewritten as `manifest.json` after every upload, so it
+/// lists everything uploaded even if instance is terminated in the middle of the run.
+#[derive(Debug, Serialize)]
+struct Manifest {
+    command: &'static str,
+    created_at: String,
+    environment: Environment,
+    files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct ManifestFile {
+    name: String,
+    size: u64,
+    sha256: String,
+}
+
+/// Uploads files to S3 or GCS (XML API with HMAC keys) with AWS Signature Version 4.
+/// Credentials are read from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optional
+/// `AWS_SESSION_TOKEN`, region from `AWS_REGION` (`us-east-1` by default), S3 compatible
+/// storages are supported with `AWS_ENDPOINT_URL`.
+pub struct Uploader {
+    client: reqwest::Client,
+    url: UploadUrl,
+    /// Base URL of objects, bucket is included.
+    base: String,
+    host: String,
+    region: String,
+    access_key: String,
+    secret_key: String,
+    session_token: Option<String>,
+    manifest: Manifest,
+}
+
+impl Uploader {
+    pub fn new(url: UploadUrl, command: &'static str) -> Result<Self> {
+        let var = |name| {
+            env::var(name)
+                .ok()
+                .filter(|value: &String| !value.is_empty())
+        };
+        let access_key = var("AWS_ACCESS_KEY_ID")
+            .ok_or_else(|| anyhow::anyhow!("AWS_ACCESS_KEY_ID is required for --upload"))?;
+        let secret_key = var("AWS_SECRET_ACCESS_KEY")
+            .ok_or_else(|| anyhow::anyhow!("AWS_SECRET_ACCESS_KEY is required for --upload"))?;
+        let (base, region) = match (var("AWS_ENDPOINT_URL"), url.storage) {
+            (Some(endpoint), _) => (
+                format!("{}/{}", endpoint.trim_end_matches('/'), url.bucket),
+                var("AWS_REGION").unwrap_or_else(|| "us-east-1".to_owned()),
+            ),
+            (None, Storage::S3) => {
+                let region = var("AWS_REGION")
+                    .or_else(|| var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_owned());
+                (
+                    format!("https://{}.s3.{}.amazonaws.com", url.bucket, region),
+                    region,
+                )
+            }
+            (None, Storage::Gcs) => (
+                format!("https://storage.googleapis.com/{}", url.bucket),
+                "auto".to_owned(),
+            ),
+        };
+        let host = base
+            .split_once("://")
+            .map_or(base.as_str(), |(_, rest)| rest)
+            .split('/')
+            .next()
+            .unwrap_or_default()
+            .to_owned();
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url,
+            base,
+            host,
+            region,
+            access_key,
+            secret_key,
+            session_token: var("AWS_SESSION_TOKEN"),
+            manifest: Manifest {
+                command,
+                created_at: chrono::Utc::now().to_rfc3339(),
+                environment: Environment::collect(),
+                files: vec![],
+            },
+        })
+    }
+
+    /// Upload file under the prefix by its file name, then rewrite manifest.
+    pub async fn upload(&mut self, path: &Path) -> Result<()> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("invalid file to upload: {:?}", path))?
+            .to_string_lossy()
+            .into_owned();
+        let size = tokio::fs::metadata(path).await?.len();
+        // hashing of a multi-gigabyte dataset would block the runtime
+        let hashed = path.to_owned();
+        let sha256 = tokio::task::spawn_blocking(move || sha256_file(&hashed)).await??;
+        let file = tokio::fs::File::open(path).await?;
+        self.put(&name, size, file.into()).await?;
+        println!("Uploaded {} to {}/{}", path.display(), self.url, name);
+
+        self.manifest.files.retain(|file| file.name != name);
+        self.manifest
+            .files
+            .push(ManifestFile { name, size, sha256 });
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        self.put("manifest.json", manifest.len() as u64, manifest.into())
+            .await
+    }
+
+    async fn put(&self, name: &str, size: u64, body: reqwest::Body) -> Result<()> {
+        let key = match self.url.prefix.as_str() {
+            "" => name.to_owned(),
+            prefix => format!("{}/{}", prefix, name),
+        };
+        let base_path = self.base.splitn(4, '/').nth(3).unwrap_or_default();
+        let path = match base_path {
+            "" => format!("/{}", encode(&key)),
+            base_path => format!("/{}/{}", base_path, encode(&key)),
+        };
+
+        let now = chrono::Utc::now();
+        let date = now.format("%Y%m%d").to_string();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        // body is streamed from the file, so it is not hashed (allowed over HTTPS)
+        let payload = "UNSIGNED-PAYLOAD";
+        let mut headers = vec![
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload.to_owned()),
+            ("x-amz-date", timestamp.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "PUT\n{}\n\n{}\n{}\n{}",
+            path,
+            headers
+                .iter()
+                .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+                .collect::<String>(),
+            signed_headers,
+            payload
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            timestamp,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut signing_key = hmac(format!("AWS4{}", self.secret_key).as_bytes(), &date);
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac(&signing_key, part);
+        }
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key,
+            scope,
+            signed_headers,
+            hex(&hmac(&signing_key, &string_to_sign))
+        );
+
+        let url = format!("{}://{}{}", self.scheme(), self.host, path);
+        let mut request = self
+            .client
+            .put(url)
+            .header("authorization", authorization)
+            .header("content-length", size)
+            .body(body);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            request = request.header(name, value);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("failed to upload {}: {}, {}", key, status, text.trim());
+        }
+        Ok(())
+    }
+
+    fn scheme(&self) -> &str {
+        self.base
+            .split_once("://")
+            .map_or("https", |(scheme, _)| scheme)
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("any key size");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// URI encoding of object key for canonical request, `/` is kept.
+fn encode(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,349 @@
+use {
+    crate::{
+        influx::{InfluxArgs, Point},
+        latency::LatencyStats,
+        metrics::{Gauges, Metrics},
+        parse_fraction, GlobalArgs,
+    },
+    anyhow::Result,
+    serde::Serialize,
+    solana_accounts_filter_bench::{
+        bench::BenchConfig,
+        geyser::{GeyserAccount, GeyserAccountsFilter, GeyserConfig, GeyserFilter},
+        grpc::{
+            proto::{
+                SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeUpdate, UpdateOneof,
+            },
+            GeyserClient,
+        },
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{BTreeMap, HashSet},
+        convert::TryFrom,
+        fs,
+        net::SocketAddr,
+        path::PathBuf,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    },
+    tokio::time::{interval_at, sleep_until, Instant},
+};
+
+/// Apply filter to live stream of account updates and print rolling throughput and latency.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// gRPC endpoint of Yellowstone geyser plugin or `replay`.
+    #[clap(long, default_value = "http://127.0.0.1:10000")]
+    endpoint: String,
+
+    /// Optional `x-token` for authentication.
+    #[clap(long)]
+    x_token: Option<String>,
+
+    /// Geyser plugin YAML (or JSON) config with named accounts filters applied to updates.
+    #[clap(long, parse(from_os_str))]
+    geyser_config: Option<PathBuf>,
+
+    /// Input file with the data for filter of `--set-size` Pubkeys, if geyser config is not set.
+    #[clap(short, long, parse(from_os_str))]
+    input: Option<PathBuf>,
+
+    /// Number of Pubkeys in the filter.
+    #[clap(long, default_value_t = 1_000_000)]
+    set_size: usize,
+
+    /// Fraction of unique Pubkeys from the data added to the filter, rest is random.
+    #[clap(long, default_value_t = 1.0, parse(try_from_str = parse_fraction))]
+    hit_rate: f64,
+
+    /// Seed for PRNG
+    #[clap(short, long, default_value_t = 42)]
+    seed: u64,
+
+    /// Comma separated owner programs of subscribed accounts, by default all accounts.
+    #[clap(long, use_value_delimiter = true)]
+    owners: Vec<Pubkey>,
+
+    /// Seconds between printed stats.
+    #[clap(long, default_value_t = 1)]
+    interval: u64,
+
+    /// Stop after given seconds, by default on Ctrl-C or when stream is finished.
+    #[clap(long)]
+    duration: Option<u64>,
+
+    /// Optional output file for stats of every interval in JSON.
+    #[clap(short, long, parse(from_os_str))]
+    output: Option<PathBuf>,
+
+    /// Optional address for Prometheus metrics of the last interval on `GET /metrics`.
+    #[clap(long)]
+    metrics_addr: Option<SocketAddr>,
+
+    #[clap(flatten)]
+    influx: InfluxArgs,
+}
+
+/// Counters of one interval.
+#[derive(Debug, Default)]
+struct WindowStats {
+    accounts: usize,
+    matched: usize,
+    slots: usize,
+    /// Time of filter lookup of account update, in ns.
+    lookups: Vec<u64>,
+    /// Time from `created_at` of the update to receive, in ns.
+    latencies: Vec<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct IntervalReport {
+    /// Seconds since the start.
+    elapsed: f64,
+    accounts_per_sec: f64,
+    matched_per_sec: f64,
+    slots: usize,
+    lookup: Option<LatencyStats>,
+    latency: Option<LatencyStats>,
+}
+
+#[derive(Debug, Serialize)]
+struct WatchReport {
+    endpoint: String,
+    elapsed_ns: u64,
+    accounts: usize,
+    matched: usize,
+    slots: usize,
+    intervals: Vec<IntervalReport>,
+}
+
+impl WindowStats {
+    fn add(&mut self, update: SubscribeUpdate, filter: &GeyserFilter) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        if let Some(created_at) = update.created_at {
+            let created_at = Duration::new(created_at.seconds as u64, created_at.nanos as u32);
+            // clocks of remote server can be ahead
+            self.latencies
+                .push(now.saturating_sub(created_at).as_nanos() as u64);
+        }
+        match update.update_oneof {
+            Some(UpdateOneof::Account(account)) => {
+                let info = match account.account {
+                    Some(info) => info,
+                    None => return,
+                };
+                let pubkey = match <[u8; 32]>::try_from(info.pubkey.as_slice()) {
+                    Ok(pubkey) => Pubkey::new_from_array(pubkey),
+                    Err(_) => return,
+                };
+                let owner = <[u8; 32]>::try_from(info.owner.as_slice())
+                    .ok()
+                    .map(Pubkey::new_from_array);
+                let account = GeyserAccount {
+                    pubkey: &pubkey,
+                    owner: owner.as_ref(),
+                    data: Some(&info.data),
+                };
+                let start = std::time::Instant::now();
+                let matched = filter.matches(&account);
+                self.lookups.push(start.elapsed().as_nanos() as u64);
+                self.accounts += 1;
+                self.matched += (matched > 0) as usize;
+            }
+            Some(UpdateOneof::Slot(_)) => self.slots += 1,
+            None => {}
+        }
+    }
+
+    fn report(&mut self, elapsed: Duration, interval: Duration) -> IntervalReport {
+        let secs = interval.as_secs_f64();
+        IntervalReport {
+            elapsed: elapsed.as_secs_f64(),
+            accounts_per_sec: self.accounts as f64 / secs,
+            matched_per_sec: self.matched as f64 / secs,
+            slots: self.slots,
+            lookup: LatencyStats::new(std::mem::take(&mut self.lookups)),
+            latency: LatencyStats::new(std::mem::take(&mut self.latencies)),
+        }
+    }
+}
+
+impl IntervalReport {
+    fn print(&self) {
+        let p50_p99 = |stats: &Option<LatencyStats>| match stats {
+            Some(stats) => format!(
+                "p50 {:?}, p99 {:?}",
+                Duration::from_nanos(stats.p50_ns),
+                Duration::from_nanos(stats.p99_ns)
+            ),
+            None => "-".to_owned(),
+        };
+        println!(
+            "{:>8.1}s: accounts {:.0}/s, matched {:.0}/s, slots {}, lookup {}, latency {}",
+            self.elapsed,
+            self.accounts_per_sec,
+            self.matched_per_sec,
+            self.slots,
+            p50_p99(&self.lookup),
+            p50_p99(&self.latency)
+        );
+    }
+}
+
+impl IntervalReport {
+    /// Point of `safb_watch` measurement, latency fields are absent in intervals without updates.
+    fn point(&self, endpoint: &str) -> Point {
+        let mut point = Point::new("safb_watch")
+            .tag("endpoint", endpoint)
+            .float("accounts_per_sec", self.accounts_per_sec)
+            .float("matched_per_sec", self.matched_per_sec)
+            .integer("slots", self.slots);
+        if let Some(lookup) = &self.lookup {
+            point = point
+                .integer("lookup_p50_ns", lookup.p50_ns)
+                .integer("lookup_p99_ns", lookup.p99_ns);
+        }
+        if let Some(latency) = &self.latency {
+            point = point
+                .integer("latency_p50_ns", latency.p50_ns)
+                .integer("latency_p99_ns", latency.p99_ns);
+        }
+        point
+    }
+
+    fn publish(&self, metrics: &Metrics, window: &WindowStats, filter_keys: usize) {
+        metrics.add(window.accounts, window.matched);
+        metrics.set(Gauges {
+            bench: None,
+            lookups_per_second: Some(self.accounts_per_sec),
+            hit_ratio: Some(window.matched as f64 / window.accounts.max(1) as f64),
+            lookup_p99_seconds: self.lookup.as_ref().map(|stats| stats.p99_ns as f64 / 1e9),
+            update_p99_seconds: self.latency.as_ref().map(|stats| stats.p99_ns as f64 / 1e9),
+            filter_keys: Some(filter_keys),
+            filter_memory_bytes: None,
+        });
+    }
+}
+
+impl Args {
+    fn geyser_config(&self, global: &GlobalArgs) -> Result<GeyserConfig> {
+        match (&self.geyser_config, &self.input) {
+            (Some(path), _) => Ok(GeyserConfig::load(path)?),
+            (None, Some(input)) => {
+                let dataset = global.load_dataset(input)?;
+                let set = BenchConfig::builder(&dataset)
+                    .set_size(self.set_size)
+                    .hit_rate(self.hit_rate)
+                    .seed(self.seed)
+                    .build()?
+                    .fill(HashSet::<Pubkey>::with_capacity(self.set_size));
+                let filter = GeyserAccountsFilter {
+                    account: set.into_iter().collect(),
+                    owner: vec![],
+                    account_exclude: vec![],
+                    filters: vec![],
+                };
+                Ok(GeyserConfig {
+                    accounts: BTreeMap::from([("safb".to_owned(), filter)]),
+                })
+            }
+            (None, None) => anyhow::bail!("filters are required: --geyser-config or --input"),
+        }
+    }
+
+    /// Upstream subscription: all accounts (or accounts of `owners`) and slots.
+    fn subscribe_request(&self) -> SubscribeRequest {
+        let mut request = SubscribeRequest::default();
+        request.accounts.insert(
+            "safb".to_owned(),
+            SubscribeRequestFilterAccounts {
+                owner: self.owners.iter().map(ToString::to_string).collect(),
+                ..Default::default()
+            },
+        );
+        request.slots.insert("safb".to_owned(), Default::default());
+        request
+    }
+}
+
+pub async fn run(args: Args, global: &GlobalArgs) -> Result<()> {
+    anyhow::ensure!(args.interval > 0, "interval should be positive");
+    let config = args.geyser_config(global)?;
+    let filter = GeyserFilter::new(&config);
+    let metrics = args.metrics_addr.map(Metrics::serve).transpose()?;
+    let influx = args.influx.client();
+    let mut client = GeyserClient::connect(&args.endpoint, args.x_token.as_deref()).await?;
+    let mut updates = client.subscribe(args.subscribe_request()).await?;
+
+    let start = Instant::now();
+    let period = Duration::from_secs(args.interval);
+    let mut ticks = interval_at(start + period, period);
+    let deadline = sleep_until(match args.duration {
+        Some(secs) => start + Duration::from_secs(secs),
+        // ~30 years, never
+        None => start + Duration::from_secs(1 << 30),
+    });
+    tokio::pin!(deadline);
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    let mut report = WatchReport {
+        endpoint: args.endpoint.clone(),
+        elapsed_ns: 0,
+        accounts: 0,
+        matched: 0,
+        slots: 0,
+        intervals: vec![],
+    };
+    let mut window = WindowStats::default();
+    loop {
+        tokio::select! {
+            update = updates.message() => match update? {
+                Some(update) => window.add(update, &filter),
+                None => break,
+            },
+            _ = ticks.tick() => {
+                let mut window = std::mem::take(&mut window);
+                report.accounts += window.accounts;
+                report.matched += window.matched;
+                report.slots += window.slots;
+                let interval = window.report(start.elapsed(), period);
+                if let Some(metrics) = &metrics {
+                    interval.publish(metrics, &window, config.accounts_len());
+                }
+                if let Some(influx) = &influx {
+                    // do not delay the stream by writes
+                    let (influx, point) = (influx.clone(), interval.point(&args.endpoint));
+                    tokio::spawn(async move { influx.write_or_warn(&[point]).await });
+                }
+                if !global.quiet {
+                    interval.print();
+                }
+                report.intervals.push(interval);
+            }
+            _ = &mut deadline => break,
+            _ = &mut ctrl_c => break,
+        }
+    }
+    report.accounts += window.accounts;
+    report.matched += window.matched;
+    report.slots += window.slots;
+    report.elapsed_ns = start.elapsed().as_nanos() as u64;
+
+    let elapsed = Duration::from_nanos(report.elapsed_ns);
+    println!(
+        "Elapsed: {:?}, accounts: {} ({:.0}/s), matched: {} ({:.0}/s), slots: {}",
+        elapsed,
+        report.accounts,
+        report.accounts as f64 / elapsed.as_secs_f64(),
+        report.matched,
+        report.matched as f64 / elapsed.as_secs_f64(),
+        report.slots
+    );
+    if let Some(output) = &args.output {
+        fs::write(output, serde_json::to_string_pretty(&report)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use {
+    super::Dataset,
+    serde::Serialize,
+    solana_sdk::{clock::UnixTimestamp, pubkey::Pubkey},
+    std::collections::{HashMap, HashSet, VecDeque},
+};
+
+/// Parameters of `Dataset::analyze`.
+#[derive(Debug, Clone, Copy)]
+pub struct AnalyzeConfig {
+    /// Seconds in one bucket of unique keys timeline.
+    pub interval: UnixTimestamp,
+    /// Seconds of sliding window for working set size.
+    pub window: UnixTimestamp,
+    /// Number of hottest keys in the report.
+    pub top: usize,
+}
+
+impl Default for AnalyzeConfig {
+    fn default() -> Self {
+        Self {
+            interval: 600,
+            window: 60,
+            top: 20,
+        }
+    }
+}
+
+/// Statistics of the dataset needed to size a production filter.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetStats {
+    pub blocks: usize,
+    pub total_keys: usize,
+    pub unique_keys: usize,
+    pub keys_per_block: Distribution,
+    /// Fraction of keys of the block present in the previous block.
+    pub overlap: Distribution,
+    pub timeline: Vec<TimelineBucket>,
+    pub hottest: Vec<HotKey>,
+    pub working_set: WorkingSet,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Distribution {
+    pub min: f64,
+    pub mean: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl Distribution {
+    fn new(mut values: Vec<f64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+        Self {
+            min: values[0],
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p50: percentile(0.5),
+            p90: percentile(0.9),
+            p99: percentile(0.99),
+            max: values[values.len() - 1],
+        }
+    }
+}
+
+/// Blocks with block time in `[start_time, start_time + interval)`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TimelineBucket {
+    pub start_time: UnixTimestamp,
+    pub blocks: usize,
+    pub keys: usize,
+    /// Keys not seen in previous buckets.
+    pub new_keys: usize,
+    /// Unique keys since the start of the dataset.
+    pub unique_keys: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotKey {
+    pub pubkey: String,
+    /// Occurrences of the key, number of blocks with it when keys of the block are unique.
+    pub count: usize,
+    /// Occurrences per block.
+    pub fraction: f64,
+}
+
+/// Unique keys in sliding window of blocks.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct WorkingSet {
+    pub window: UnixTimestamp,
+    pub mean: f64,
+    pub max: usize,
+    /// Number of hottest keys which cover 90% of lookups.
+    pub keys_90pct_lookups: usize,
+}
+
+impl Dataset {
+    /// Key counts over time, keys per block, overlap of consecutive blocks, hottest keys
+    /// and working set size.
+    pub fn analyze(&self, config: &AnalyzeConfig) -> DatasetStats {
+        let mut counts = HashMap::<&Pubkey, usize>::new();
+        let mut timeline = Vec::<TimelineBucket>::new();
+        let mut keys_per_block = vec![];
+        let mut overlap = vec![];
+        let mut previous = HashSet::new();
+        let mut window = VecDeque::new();
+        let mut window_keys = HashMap::<&Pubkey, usize>::new();
+        let mut working_set = vec![];
+
+        for block in self.blocks.values() {
+            let keys = block.pubkeys.len();
+            keys_per_block.push(keys as f64);
+
+            let start_time = block.block_time - block.block_time.rem_euclid(config.interval.max(1));
+            if timeline.last().map(|bucket| bucket.start_time) != Some(start_time) {
+                timeline.push(TimelineBucket {
+                    start_time,
+                    blocks: 0,
+                    keys: 0,
+                    new_keys: 0,
+                    unique_keys: counts.len(),
+                });
+            }
+            let bucket = timeline.last_mut().expect("pushed");
+            bucket.blocks += 1;
+            bucket.keys += keys;
+            for pubkey in block.pubkeys.iter() {
+                let count = counts.entry(pubkey).or_default();
+                bucket.new_keys += (*count == 0) as usize;
+                *count += 1;
+            }
+            bucket.unique_keys = counts.len();
+
+            if !previous.is_empty() && keys > 0 {
+                let common = block
+                    .pubkeys
+                    .iter()
+                    .filter(|pubkey| previous.contains(pubkey))
+                    .count();
+                overlap.push(common as f64 / keys as f64);
+            }
+            previous = block.pubkeys.iter().collect();
+
+            window.push_back(block);
+            for pubkey in block.pubkeys.iter() {
+                *window_keys.entry(pubkey).or_default() += 1;
+            }
+            while let Some(first) = window.front() {
+                if first.block_time > block.block_time - config.window {
+                    break;
+                }
+                for pubkey in first.pubkeys.iter() {
+                    if let Some(count) = window_keys.get_mut(pubkey) {
+                        *count -= 1;
+                        if *count == 0 {
+                            window_keys.remove(pubkey);
+                        }
+                    }
+                }
+                window.pop_front();
+            }
+            working_set.push(window_keys.len());
+        }
+
+        let total_keys = keys_per_block.iter().sum::<f64>() as usize;
+        let mut hottest = counts.into_iter().collect::<Vec<_>>();
+        hottest.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let mut covered = 0;
+        let keys_90pct_lookups = hottest
+            .iter()
+            .take_while(|(_pubkey, count)| {
+                let take = (covered as f64) < total_keys as f64 * 0.9;
+                covered += count;
+                take
+            })
+            .count();
+
+        DatasetStats {
+            blocks: self.blocks.len(),
+            total_keys,
+            unique_keys: hottest.len(),
+            keys_per_block: Distribution::new(keys_per_block),
+            overlap: Distribution::new(overlap),
+            timeline,
+            hottest: hottest
+                .iter()
+                .take(config.top)
+                .map(|(pubkey, count)| HotKey {
+                    pubkey: pubkey.to_string(),
+                    count: *count,
+                    fraction: *count as f64 / self.blocks.len() as f64,
+                })
+                .collect(),
+            working_set: WorkingSet {
+                window: config.window,
+                mean: working_set.iter().sum::<usize>() as f64 / working_set.len().max(1) as f64,
+                max: working_set.iter().cloned().max().unwrap_or_default(),
+                keys_90pct_lookups,
+            },
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use {
+    super::{
+        version::{migrate, DATASET_VERSION},
+        AccountOwner, Block, Dataset, DatasetLoader,
+    },
+    crate::{Error, Result},
+    arrow_array::{
+        cast::AsArray,
+        types::{Int64Type, UInt64Type},
+        ArrayRef, FixedSizeBinaryArray, Int64Array, ListArray, RecordBatch, UInt64Array,
+    },
+    arrow_buffer::{Buffer, OffsetBuffer},
+    arrow_ipc::{
+        convert::fb_to_schema,
+        reader::{read_footer_length, FileDecoder},
+        root_as_footer, root_as_message,
+        writer::FileWriter,
+        Block as IpcBlock, Buffer as IpcBuffer, Field as IpcField, Schema as IpcSchema,
+    },
+    arrow_schema::{DataType, Field, FieldRef, Schema},
+    memmap2::Mmap,
+    solana_sdk::{
+        clock::Slot,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        fs::File,
+        io::{BufRead, Write},
+        ptr::NonNull,
+        sync::Arc,
+    },
+};
+
+/// Blocks in one record batch, notebooks can read large captures batch by batch.
+const BATCH_BLOCKS: usize = 4096;
+
+/// Length of the `ARROW1` trailer with footer length.
+const TRAILER_LEN: usize = 10;
+
+/// Prefix of IPC messages, not exported by `arrow-ipc`.
+const CONTINUATION_MARKER: [u8; 4] = [0xff; 4];
+
+/// Field nodes of a record batch: `slot`, `block_time`, `pubkeys` and its items.
+const BATCH_NODES: usize = 4;
+
+/// Width of values in buffers of a record batch, buffers are validity and values of `slot` and
+/// `block_time`, validity and offsets of `pubkeys`, validity and values of Pubkeys.
+const BATCH_BUFFER_WIDTHS: [u64; 8] = [1, 8, 1, 8, 1, 4, 1, 1];
+
+/// Pubkey, owner and executable flag of one account in `accounts` metadata.
+const ACCOUNT_BYTES: usize = PUBKEY_BYTES * 2 + 1;
+
+fn pubkey_field() -> FieldRef {
+    Arc::new(Field::new(
+        "item",
+        DataType::FixedSizeBinary(PUBKEY_BYTES as i32),
+        false,
+    ))
+}
+
+fn schema(dataset: &Dataset) -> Schema {
+    let mut metadata = HashMap::from([("version".to_owned(), DATASET_VERSION.to_string())]);
+    if !dataset.accounts.is_empty() {
+        metadata.insert("accounts".to_owned(), encode_accounts(&dataset.accounts));
+    }
+    Schema::new(vec![
+        Field::new("slot", DataType::UInt64, false),
+        Field::new("block_time", DataType::Int64, false),
+        Field::new("pubkeys", DataType::List(pubkey_field()), false),
+    ])
+    .with_metadata(metadata)
+}
+
+fn encode_accounts(accounts: &BTreeMap<Pubkey, AccountOwner>) -> String {
+    let mut bytes = Vec::with_capacity(accounts.len() * ACCOUNT_BYTES);
+    for (pubkey, account) in accounts {
+        bytes.extend_from_slice(pubkey.as_ref());
+        bytes.extend_from_slice(account.owner.as_ref());
+        bytes.push(account.executable as u8);
+    }
+    base64::encode(bytes)
+}
+
+fn decode_accounts(value: &str) -> Result<BTreeMap<Pubkey, AccountOwner>> {
+    let bytes = base64::decode(value).map_err(Error::parse)?;
+    let chunks = bytes.chunks_exact(ACCOUNT_BYTES);
+    if !chunks.remainder().is_empty() {
+        return Err(Error::parse(format!(
+            "accounts metadata of {} bytes is not a multiple of {}",
+            bytes.len(),
+            ACCOUNT_BYTES
+        )));
+    }
+    Ok(chunks
+        .map(|chunk| {
+            let account = AccountOwner {
+                owner: Pubkey::new(&chunk[PUBKEY_BYTES..PUBKEY_BYTES * 2]),
+                executable: chunk[ACCOUNT_BYTES - 1] != 0,
+            };
+            (Pubkey::new(&chunk[..PUBKEY_BYTES]), account)
+        })
+        .collect())
+}
+
+fn record_batch(schema: &Arc<Schema>, blocks: &[(&Slot, &Block)]) -> Result<RecordBatch> {
+    let slots = UInt64Array::from_iter_values(blocks.iter().map(|(slot, _block)| **slot));
+    let block_times =
+        Int64Array::from_iter_values(blocks.iter().map(|(_slot, block)| block.block_time));
+
+    let bytes = blocks
+        .iter()
+        .flat_map(|(_slot, block)| &block.pubkeys)
+        .flat_map(|pubkey| pubkey.to_bytes())
+        .collect::<Vec<_>>();
+    let values = FixedSizeBinaryArray::try_new(PUBKEY_BYTES as i32, Buffer::from_vec(bytes), None)
+        .map_err(Error::write)?;
+    let offsets =
+        OffsetBuffer::from_lengths(blocks.iter().map(|(_slot, block)| block.pubkeys.len()));
+    let pubkeys = ListArray::try_new(pubkey_field(), offsets, Arc::new(values), None)
+        .map_err(Error::write)?;
+
+    let columns: Vec<ArrayRef> = vec![Arc::new(slots), Arc::new(block_times), Arc::new(pubkeys)];
+    RecordBatch::try_new(Arc::clone(schema), columns).map_err(Error::write)
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a ArrayRef> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| Error::parse(format!("record batch without `{}` column", name)))
+}
+
+fn invalid_column(name: &str) -> Error {
+    Error::parse(format!("unexpected type of `{}` column", name))
+}
+
+fn decode_batch(batch: &RecordBatch, blocks: &mut BTreeMap<Slot, Block>) -> Result<()> {
+    let slots = column(batch, "slot")?
+        .as_primitive_opt::<UInt64Type>()
+        .ok_or_else(|| invalid_column("slot"))?;
+    let block_times = column(batch, "block_time")?
+        .as_primitive_opt::<Int64Type>()
+        .ok_or_else(|| invalid_column("block_time"))?;
+    let pubkeys = column(batch, "pubkeys")?
+        .as_list_opt::<i32>()
+        .ok_or_else(|| invalid_column("pubkeys"))?;
+    let values = pubkeys
+        .values()
+        .as_fixed_size_binary_opt()
+        .filter(|values| values.value_length() == PUBKEY_BYTES as i32)
+        .ok_or_else(|| invalid_column("pubkeys"))?;
+
+    for (row, offsets) in pubkeys.value_offsets().windows(2).enumerate() {
+        let block = Block {
+            block_time: block_times.value(row),
+            pubkeys: (offsets[0] as usize..offsets[1] as usize)
+                .map(|index| Pubkey::new(values.value(index)))
+                .collect(),
+        };
+        blocks.insert(slots.value(row), block);
+    }
+    Ok(())
+}
+
+/// `fb_to_schema` panics on fields with missing names or type parameters, so fields of the
+/// footer are matched against the written schema first.
+fn is_dataset_schema(schema: IpcSchema) -> bool {
+    let expected = self::schema(&Dataset::default());
+    let Some(fields) = schema.fields() else {
+        return false;
+    };
+    fields.len() == expected.fields().len()
+        && fields
+            .iter()
+            .zip(expected.fields())
+            .all(|(field, expected)| is_field(field, expected))
+}
+
+fn is_field(field: IpcField, expected: &Field) -> bool {
+    if field.name() != Some(expected.name().as_str()) || field.dictionary().is_some() {
+        return false;
+    }
+    match expected.data_type() {
+        DataType::UInt64 | DataType::Int64 => field.type_as_int().is_some_and(|int| {
+            int.bitWidth() == 64 && int.is_signed() == (expected.data_type() == &DataType::Int64)
+        }),
+        DataType::FixedSizeBinary(width) => field
+            .type_as_fixed_size_binary()
+            .is_some_and(|binary| binary.byteWidth() == *width),
+        DataType::List(item) => {
+            field.type_as_list().is_some()
+                && field
+                    .children()
+                    .is_some_and(|children| children.len() == 1 && is_field(children.get(0), item))
+        }
+        _ => false,
+    }
+}
+
+/// Message with the record batch of `block`. `FileDecoder` panics on messages shorter than
+/// their prefix and on buffers out of the body, so the layout of the batch is checked first.
+fn batch_data(buffer: &Buffer, block: &IpcBlock, end: usize) -> Result<Buffer> {
+    let out_of_file = || Error::parse("Arrow record batch is out of the file");
+    let offset = usize::try_from(block.offset()).map_err(|_| out_of_file())?;
+    let metadata_len = usize::try_from(block.metaDataLength()).map_err(|_| out_of_file())?;
+    let body_len = usize::try_from(block.bodyLength()).map_err(|_| out_of_file())?;
+    let len = metadata_len.checked_add(body_len).ok_or_else(out_of_file)?;
+    if offset.saturating_add(len) > end {
+        return Err(out_of_file());
+    }
+    let data = buffer.slice_with_length(offset, len);
+
+    // message starts with continuation marker (optional in legacy format) and length
+    let prefix_len = if data.starts_with(&CONTINUATION_MARKER) {
+        8
+    } else {
+        4
+    };
+    let message = data
+        .get(prefix_len..metadata_len)
+        .ok_or_else(|| Error::parse("Arrow record batch without message"))?;
+    let message = root_as_message(message).map_err(|error| Error::parse(error.to_string()))?;
+    if let Some(batch) = message.header_as_record_batch() {
+        let nodes = batch.nodes().unwrap_or_default();
+        let buffers = batch.buffers().unwrap_or_default();
+        let is_valid = |buffer: &IpcBuffer, width: u64| {
+            u64::try_from(buffer.offset())
+                .ok()
+                .zip(u64::try_from(buffer.length()).ok())
+                .and_then(|(offset, length)| Some((offset.checked_add(length)?, length)))
+                .is_some_and(|(buffer_end, length)| {
+                    buffer_end <= body_len as u64 && length % width == 0
+                })
+        };
+        // columns are not nullable, validity bitmaps are not checked before use
+        if nodes.len() != BATCH_NODES
+            || !nodes
+                .iter()
+                .all(|node| node.null_count() == 0 && node.length() >= 0)
+            || buffers.len() != BATCH_BUFFER_WIDTHS.len()
+            || batch.compression().is_some()
+            || !buffers
+                .iter()
+                .zip(BATCH_BUFFER_WIDTHS)
+                .all(|(buffer, width)| is_valid(buffer, width))
+        {
+            return Err(Error::parse("unexpected layout of Arrow record batch"));
+        }
+    }
+    Ok(data)
+}
+
+/// Decode IPC file from `buffer`, record batches are slices of it, so only Pubkeys are copied.
+fn decode(buffer: Buffer) -> Result<Dataset> {
+    let trailer = buffer
+        .len()
+        .checked_sub(TRAILER_LEN)
+        .ok_or_else(|| Error::parse("file is too short for Arrow IPC"))?;
+    let trailer_bytes = buffer[trailer..].try_into().expect("trailer length");
+    let footer_len = read_footer_length(trailer_bytes).map_err(Error::parse)?;
+    let footer_start = trailer
+        .checked_sub(footer_len)
+        .ok_or_else(|| Error::parse("Arrow IPC footer is out of the file"))?;
+    let footer = root_as_footer(&buffer[footer_start..trailer])
+        .map_err(|error| Error::parse(error.to_string()))?;
+    let schema = footer
+        .schema()
+        .ok_or_else(|| Error::parse("Arrow IPC file without schema"))?;
+    if !is_dataset_schema(schema) {
+        return Err(Error::parse(
+            "Arrow schema is not `slot`, `block_time` and `pubkeys` columns",
+        ));
+    }
+    let schema = fb_to_schema(schema);
+
+    let version = schema
+        .metadata
+        .get("version")
+        .ok_or_else(|| Error::parse("Arrow schema without `version` metadata"))?
+        .parse::<u32>()
+        .map_err(Error::parse)?;
+    let accounts = match schema.metadata.get("accounts") {
+        Some(value) => decode_accounts(value)?,
+        None => BTreeMap::new(),
+    };
+
+    let decoder = FileDecoder::new(Arc::new(schema), footer.version());
+    let mut blocks = BTreeMap::new();
+    for block in footer.recordBatches().into_iter().flatten() {
+        let data = batch_data(&buffer, block, footer_start)?;
+        if let Some(batch) = decoder
+            .read_record_batch(block, &data)
+            .map_err(Error::parse)?
+        {
+            decode_batch(&batch, &mut blocks)?;
+        }
+    }
+    migrate(version, blocks, accounts)
+}
+
+/// Load uncompressed file through memory map, record batches are read from the page cache
+/// without copying the file into memory first.
+pub(super) fn load_mmap(file: &File) -> Result<Dataset> {
+    // SAFETY: `Dataset::save_with` replaces files instead of writing into them, so the mapped
+    // file is not modified while loaded, map is dropped after decoding
+    let mmap = Arc::new(unsafe { Mmap::map(file)? });
+    let ptr = NonNull::new(mmap.as_ptr() as *mut u8).expect("mapped pointer is not null");
+    // SAFETY: buffer is immutable and keeps `mmap` alive through the custom allocation
+    let buffer = unsafe { Buffer::from_custom_allocation(ptr, mmap.len(), mmap.clone()) };
+    decode(buffer)
+}
+
+/// Arrow IPC file (Feather v2) with `slot`, `block_time` and `pubkeys` (list of 32 bytes fixed
+/// size binary) columns in record batches of 4096 blocks. Schema metadata has `version` and
+/// `accounts` owners (only if dataset is enriched) as base64 of concatenated Pubkey, owner and
+/// executable byte. Added in version 3, so there is nothing to migrate.
+pub struct Arrow;
+
+impl DatasetLoader for Arrow {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        decode(Buffer::from_vec(bytes))
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let schema = Arc::new(schema(dataset));
+        let mut writer = FileWriter::try_new(writer, &schema).map_err(Error::write)?;
+        let blocks = dataset.blocks.iter().collect::<Vec<_>>();
+        for chunk in blocks.chunks(BATCH_BLOCKS) {
+            let batch = record_batch(&schema, chunk)?;
+            writer.write(&batch).map_err(Error::write)?;
+        }
+        writer.finish().map_err(Error::write)
+    }
+}
@@ -0,0 +1,144 @@
+use {
+    super::{
+        version::{migrate, DATASET_VERSION},
+        Block, Dataset, DatasetLoader, Pubkeys,
+    },
+    crate::{Error, Result},
+    bincode::Options,
+    memmap2::Mmap,
+    std::{
+        collections::BTreeMap,
+        fs::File,
+        io::{BufRead, Write},
+        sync::Arc,
+    },
+};
+
+/// Prefix of versioned format, version 1 starts with number of blocks (`u64`).
+const MAGIC: &[u8; 4] = b"SAFB";
+
+/// Decoded size of streamed (compressed) input, whose size is not known before decoding. It only
+/// bounds inputs which do not end and does not keep the load within memory: serde preallocates
+/// at most 1 MiB for a length prefix, so memory grows with decoded data, not with the length.
+const STREAM_LIMIT: u64 = 64 << 30;
+
+/// Options of `bincode::deserialize` with size limit, corrupted lengths fail at `limit` bytes.
+fn options(limit: u64) -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(limit)
+}
+
+/// Little endian integers of the file read from its bytes.
+pub(super) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    pub(super) offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(super) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self
+            .offset
+            .checked_add(N)
+            .and_then(|end| self.bytes.get(self.offset..end))
+            .ok_or_else(|| Error::parse("unexpected end of file"))?;
+        self.offset += N;
+        Ok(bytes.try_into().expect("N bytes"))
+    }
+
+    pub(super) fn read_u32(&mut self) -> Result<u32> {
+        self.read().map(u32::from_le_bytes)
+    }
+
+    pub(super) fn read_u64(&mut self) -> Result<u64> {
+        self.read().map(u64::from_le_bytes)
+    }
+
+    pub(super) fn read_usize(&mut self) -> Result<usize> {
+        usize::try_from(self.read_u64()?).map_err(Error::parse)
+    }
+}
+
+/// Load uncompressed file without copying Pubkeys: only slot, block time and number of
+/// Pubkeys of every block are decoded, Pubkeys are borrowed from the memory map
+/// (`BTreeMap<Slot, Block>` of `bincode` is number of blocks and then slot, block time, number
+/// of Pubkeys and raw Pubkeys of every block).
+pub(super) fn load_mmap(file: &File) -> Result<Dataset> {
+    // SAFETY: `Dataset::save_with` replaces files instead of writing into them, so the mapped
+    // file is not modified while loaded, blocks keep the map alive
+    let mmap = Arc::new(unsafe { Mmap::map(file)? });
+    let mut reader = ByteReader::new(&mmap);
+    let version = if mmap.starts_with(MAGIC) {
+        reader.offset = MAGIC.len();
+        reader.read_u32()?
+    } else {
+        1
+    };
+
+    let mut blocks = BTreeMap::new();
+    for _ in 0..reader.read_u64()? {
+        let slot = reader.read_u64()?;
+        let block_time = reader.read_u64()? as i64;
+        let len = reader.read_usize()?;
+        let pubkeys = Pubkeys::mapped(&mmap, reader.offset, len)
+            .ok_or_else(|| Error::parse("Pubkeys are out of the file"))?;
+        reader.offset += len * 32;
+        blocks.insert(
+            slot,
+            Block {
+                block_time,
+                pubkeys,
+            },
+        );
+    }
+    let accounts = if version >= 3 {
+        let accounts = &mmap[reader.offset..];
+        options(accounts.len() as u64)
+            .deserialize(accounts)
+            .map_err(Error::parse)?
+    } else {
+        BTreeMap::new()
+    };
+    migrate(version, blocks, accounts)
+}
+
+/// `bincode` encoded blocks with raw Pubkeys, prefixed with `MAGIC` and version (`u32`),
+/// followed by accounts owners since version 3. Uncompressed files are memory-mapped on load,
+/// Pubkeys of blocks are not copied.
+pub struct Bincode;
+
+impl DatasetLoader for Bincode {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let version = if reader.fill_buf()?.starts_with(MAGIC) {
+            reader.consume(MAGIC.len());
+            options(STREAM_LIMIT)
+                .deserialize_from(&mut *reader)
+                .map_err(Error::parse)?
+        } else {
+            1
+        };
+        let blocks = options(STREAM_LIMIT)
+            .deserialize_from(&mut *reader)
+            .map_err(Error::parse)?;
+        let accounts = if version >= 3 {
+            options(STREAM_LIMIT)
+                .deserialize_from(reader)
+                .map_err(Error::parse)?
+        } else {
+            BTreeMap::new()
+        };
+        migrate(version, blocks, accounts)
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(MAGIC)?;
+        bincode::serialize_into(&mut *writer, &DATASET_VERSION).map_err(Error::write)?;
+        bincode::serialize_into(&mut *writer, &dataset.blocks).map_err(Error::write)?;
+        bincode::serialize_into(writer, &dataset.accounts).map_err(Error::write)
+    }
+}
@@ -0,0 +1,117 @@
+use {
+    super::{Block, Dataset},
+    rand::{Rng, RngCore, SeedableRng},
+    rand_chacha::ChaCha8Rng,
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp, DEFAULT_MS_PER_SLOT},
+        pubkey::Pubkey,
+    },
+    std::{collections::HashSet, ops::RangeInclusive},
+};
+
+/// How often keys of the pool are used in blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Popularity {
+    /// Every key is equally likely.
+    Uniform,
+    /// Key of rank `k` (from 1) is used with probability proportional to `1 / k^exponent`,
+    /// few hot accounts (programs, sysvars, AMMs) and long tail, as in real blocks.
+    Zipf { exponent: f64 },
+}
+
+/// Parameters of synthetic dataset, see `GenerateConfig::generate`.
+#[derive(Debug, Clone)]
+pub struct GenerateConfig {
+    pub blocks: usize,
+    /// Number of unique keys in the block, drawn uniformly from the range.
+    pub keys_per_block: RangeInclusive<usize>,
+    /// Number of keys in the pool from which blocks are filled.
+    pub unique_keys: usize,
+    pub popularity: Popularity,
+    pub seed: u64,
+    pub start_slot: Slot,
+    pub start_time: UnixTimestamp,
+}
+
+impl Default for GenerateConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 1_000,
+            keys_per_block: 1_000..=1_000,
+            unique_keys: 1_000_000,
+            popularity: Popularity::Uniform,
+            seed: 42,
+            start_slot: 0,
+            start_time: 1_650_000_000,
+        }
+    }
+}
+
+impl GenerateConfig {
+    /// Generate dataset with consecutive slots, same config and seed give the same dataset.
+    pub fn generate(&self) -> Dataset {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let pool = (0..self.unique_keys)
+            .map(|_| {
+                let mut bytes = [0u8; 32];
+                rng.fill_bytes(&mut bytes);
+                Pubkey::new_from_array(bytes)
+            })
+            .collect::<Vec<_>>();
+        let cdf = match self.popularity {
+            Popularity::Uniform => None,
+            Popularity::Zipf { exponent } => {
+                let mut total = 0.0;
+                let mut cdf = (1..=pool.len())
+                    .map(|rank| {
+                        total += 1.0 / (rank as f64).powf(exponent);
+                        total
+                    })
+                    .collect::<Vec<_>>();
+                cdf.iter_mut().for_each(|value| *value /= total);
+                Some(cdf)
+            }
+        };
+
+        let mut seen = HashSet::new();
+        let blocks = (0..self.blocks)
+            .map(|index| {
+                let len = rng.gen_range(self.keys_per_block.clone()).min(pool.len());
+                seen.clear();
+                let mut pubkeys = Vec::with_capacity(len);
+                // skewed popularity can make unique keys rare, so attempts are limited
+                for _ in 0..len.saturating_mul(10) {
+                    if pubkeys.len() == len {
+                        break;
+                    }
+                    let key = match &cdf {
+                        Some(cdf) => {
+                            let sample = rng.gen::<f64>();
+                            cdf.partition_point(|&value| value < sample)
+                                .min(pool.len() - 1)
+                        }
+                        None => rng.gen_range(0..pool.len()),
+                    };
+                    if seen.insert(key) {
+                        pubkeys.push(pool[key]);
+                    }
+                }
+
+                let slot = self.start_slot + index as Slot;
+                let block_time =
+                    self.start_time + (index as u64 * DEFAULT_MS_PER_SLOT / 1_000) as UnixTimestamp;
+                (
+                    slot,
+                    Block {
+                        block_time,
+                        pubkeys: pubkeys.into(),
+                    },
+                )
+            })
+            .collect();
+        Dataset {
+            blocks,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use {
+    super::{
+        bincode::ByteReader,
+        pubkeys::FileBytes,
+        version::{migrate, DATASET_VERSION},
+        Block, Dataset, DatasetLoader, Pubkeys,
+    },
+    crate::{Error, Result},
+    memmap2::Mmap,
+    std::{
+        collections::{BTreeMap, HashMap},
+        fs::File,
+        io::{BufRead, Write},
+        sync::Arc,
+    },
+};
+
+const MAGIC: &[u8; 4] = b"SAFI";
+
+/// Slot, block time, number of Pubkeys and offset of indices of every block.
+const INDEX_ENTRY_LEN: usize = 4 * 8;
+
+/// Index of blocks only, Pubkeys stay encoded in `bytes` until a block is visited.
+fn decode(bytes: FileBytes) -> Result<Dataset> {
+    if !(*bytes).as_ref().starts_with(MAGIC) {
+        return Err(Error::parse(
+            "file does not start with indexed dataset magic",
+        ));
+    }
+    let mut reader = ByteReader::new((*bytes).as_ref());
+    reader.offset = MAGIC.len();
+    let version = reader.read_u32()?;
+    let keys = reader.read_usize()?;
+    let count = reader.read_usize()?;
+    count
+        .checked_mul(INDEX_ENTRY_LEN)
+        .filter(|len| *len <= (*bytes).as_ref().len())
+        .ok_or_else(|| Error::parse("index is out of the file"))?;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        let slot = reader.read_u64()?;
+        let block_time = reader.read_u64()? as i64;
+        let len = reader.read_usize()?;
+        let offset = reader.read_usize()?;
+        entries.push((slot, block_time, len, offset));
+    }
+    let accounts_len = reader.read_usize()?;
+    let accounts = reader
+        .offset
+        .checked_add(accounts_len)
+        .and_then(|end| (*bytes).as_ref().get(reader.offset..end))
+        .ok_or_else(|| Error::parse("accounts are out of the file"))?;
+    let accounts = bincode::deserialize(accounts).map_err(Error::parse)?;
+    let table_start = reader.offset + accounts_len;
+    let table = keys
+        .checked_mul(32)
+        .and_then(|len| table_start.checked_add(len))
+        .filter(|end| *end <= (*bytes).as_ref().len())
+        .map(|end| table_start..end)
+        .ok_or_else(|| Error::parse("Pubkeys are out of the file"))?;
+
+    let mut blocks = BTreeMap::new();
+    for (slot, block_time, len, offset) in entries {
+        let pubkeys = Pubkeys::lazy(&bytes, table.clone(), offset, len)
+            .ok_or_else(|| Error::parse(format!("invalid Pubkeys of block {}", slot)))?;
+        blocks.insert(
+            slot,
+            Block {
+                block_time,
+                pubkeys,
+            },
+        );
+    }
+    migrate(version, blocks, accounts)
+}
+
+pub(super) fn load_mmap(file: &File) -> Result<Dataset> {
+    // SAFETY: `Dataset::save_with` replaces files instead of writing into them, so the mapped
+    // file is not modified while loaded, blocks keep the map alive
+    let mmap = unsafe { Mmap::map(file)? };
+    decode(Arc::new(mmap))
+}
+
+/// Blocks indexed in the header (`MAGIC`, version, number of unique Pubkeys, number of blocks
+/// and slot, block time, number of Pubkeys and offset of indices of every block), accounts
+/// owners (`bincode` with length), table of unique raw Pubkeys and `u32` indices into the
+/// table for every block. Popular accounts are referenced by many blocks, so the table with
+/// indices is a few times smaller than raw Pubkeys. Only the index is decoded on load, Pubkeys
+/// of a block are decoded on the first access, so runs over a part of the blocks do not decode
+/// the rest. Uncompressed files are memory-mapped on load.
+pub struct Indexed;
+
+impl DatasetLoader for Indexed {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        decode(Arc::new(bytes))
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let mut table = Vec::new();
+        let mut indices = HashMap::new();
+        for pubkey in dataset
+            .blocks
+            .values()
+            .flat_map(|block| block.pubkeys.iter())
+        {
+            indices.entry(*pubkey).or_insert_with(|| {
+                table.push(*pubkey);
+                table.len() - 1
+            });
+        }
+        if table.len() > u32::MAX as usize {
+            return Err(Error::write("too many unique Pubkeys for indexed dataset"));
+        }
+        let accounts = bincode::serialize(&dataset.accounts).map_err(Error::write)?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&DATASET_VERSION.to_le_bytes())?;
+        writer.write_all(&(table.len() as u64).to_le_bytes())?;
+        writer.write_all(&(dataset.blocks.len() as u64).to_le_bytes())?;
+        let mut offset = MAGIC.len()
+            + 4
+            + 8 * 2
+            + dataset.blocks.len() * INDEX_ENTRY_LEN
+            + 8
+            + accounts.len()
+            + table.len() * 32;
+        for (slot, block) in dataset.blocks.iter() {
+            writer.write_all(&slot.to_le_bytes())?;
+            writer.write_all(&block.block_time.to_le_bytes())?;
+            writer.write_all(&(block.pubkeys.len() as u64).to_le_bytes())?;
+            writer.write_all(&(offset as u64).to_le_bytes())?;
+            offset += block.pubkeys.len() * 4;
+        }
+        writer.write_all(&(accounts.len() as u64).to_le_bytes())?;
+        writer.write_all(&accounts)?;
+        writer.write_all(bytemuck::cast_slice(&table))?;
+        for block in dataset.blocks.values() {
+            let block_indices = block
+                .pubkeys
+                .iter()
+                .flat_map(|pubkey| (indices[pubkey] as u32).to_le_bytes())
+                .collect::<Vec<_>>();
+            writer.write_all(&block_indices)?;
+        }
+        Ok(())
+    }
+}
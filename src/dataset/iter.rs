@@ -0,0 +1,59 @@
+use {
+    super::Block,
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{btree_map, BTreeMap},
+        ops::{Bound, RangeBounds},
+    },
+};
+
+/// Blocks of the dataset in slot order, see `Dataset::blocks`.
+#[derive(Debug, Clone)]
+pub struct BlocksIter<'a> {
+    blocks: &'a BTreeMap<Slot, Block>,
+    range: btree_map::Range<'a, Slot, Block>,
+    block_time: (Bound<UnixTimestamp>, Bound<UnixTimestamp>),
+}
+
+impl<'a> BlocksIter<'a> {
+    pub(super) fn new(blocks: &'a BTreeMap<Slot, Block>) -> Self {
+        Self {
+            blocks,
+            range: blocks.range(..),
+            block_time: (Bound::Unbounded, Bound::Unbounded),
+        }
+    }
+
+    /// Only blocks with slot in `slots`, restarts iteration.
+    pub fn slots(mut self, slots: impl RangeBounds<Slot>) -> Self {
+        self.range = self.blocks.range(slots);
+        self
+    }
+
+    /// Only blocks with block time (unix timestamp) in `block_time`.
+    pub fn block_time(mut self, block_time: impl RangeBounds<UnixTimestamp>) -> Self {
+        self.block_time = (
+            block_time.start_bound().cloned(),
+            block_time.end_bound().cloned(),
+        );
+        self
+    }
+
+    /// Pubkeys of selected blocks with slot, unique within a block only.
+    pub fn keys(self) -> impl Iterator<Item = (Slot, &'a Pubkey)> {
+        self.flat_map(|(slot, block)| block.pubkeys.iter().map(move |pubkey| (slot, pubkey)))
+    }
+}
+
+impl<'a> Iterator for BlocksIter<'a> {
+    type Item = (Slot, &'a Block);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.range
+            .find(|(_slot, block)| self.block_time.contains(&block.block_time))
+            .map(|(slot, block)| (*slot, block))
+    }
+}
@@ -0,0 +1,390 @@
+use {
+    super::{
+        version::{migrate, DATASET_VERSION},
+        AccountOwner, Block, Dataset, DatasetLoader,
+    },
+    crate::{Error, Result},
+    serde::{
+        de::{self, MapAccess, SeqAccess, Unexpected, Visitor},
+        ser::{SerializeMap, SerializeSeq},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::BTreeMap,
+        fmt,
+        io::{BufRead, Write},
+    },
+};
+
+/// Base58 encoded Pubkey, decoded from the borrowed or buffered string of the parser without
+/// allocation.
+struct Base58Pubkey(Pubkey);
+
+impl<'de> Deserialize<'de> for Base58Pubkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Base58PubkeyVisitor;
+
+        impl Visitor<'_> for Base58PubkeyVisitor {
+            type Value = Base58Pubkey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("base58 encoded Pubkey")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                let mut bytes = [0; 32];
+                match bs58::decode(value).into(&mut bytes) {
+                    Ok(32) => Ok(Base58Pubkey(Pubkey::new_from_array(bytes))),
+                    _ => Err(E::invalid_value(Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Base58PubkeyVisitor)
+    }
+}
+
+impl Serialize for Base58Pubkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+/// Block with base58 encoded Pubkeys.
+#[derive(Deserialize)]
+struct JsonBlock {
+    block_time: UnixTimestamp,
+    #[serde(deserialize_with = "deserialize_pubkeys")]
+    pubkeys: Vec<Pubkey>,
+}
+
+#[derive(Serialize)]
+struct JsonBlockRef<'a> {
+    block_time: UnixTimestamp,
+    #[serde(serialize_with = "serialize_pubkeys")]
+    pubkeys: &'a [Pubkey],
+}
+
+impl<'a> From<&'a Block> for JsonBlockRef<'a> {
+    fn from(block: &'a Block) -> Self {
+        Self {
+            block_time: block.block_time,
+            pubkeys: &block.pubkeys,
+        }
+    }
+}
+
+impl From<JsonBlock> for Block {
+    fn from(block: JsonBlock) -> Self {
+        Self {
+            block_time: block.block_time,
+            pubkeys: block.pubkeys.into(),
+        }
+    }
+}
+
+/// Fields of the block are not flattened, so Pubkeys are decoded while parsing and not
+/// buffered as strings.
+#[derive(Deserialize)]
+struct JsonLine {
+    slot: Slot,
+    block_time: UnixTimestamp,
+    #[serde(deserialize_with = "deserialize_pubkeys")]
+    pubkeys: Vec<Pubkey>,
+}
+
+#[derive(Serialize)]
+struct JsonLineRef<'a> {
+    slot: Slot,
+    #[serde(flatten)]
+    block: JsonBlockRef<'a>,
+}
+
+fn serialize_pubkeys<S>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    let mut seq = serializer.serialize_seq(Some(pubkeys.len()))?;
+    for pubkey in pubkeys {
+        seq.serialize_element(&pubkey.to_string())?;
+    }
+    seq.end()
+}
+
+/// Deserialize base58 encoded Pubkeys.
+pub fn deserialize_pubkeys<'de, D>(deserializer: D) -> Result<Vec<Pubkey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct PubkeysVisitor;
+
+    impl<'de> Visitor<'de> for PubkeysVisitor {
+        type Value = Vec<Pubkey>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("array of base58 encoded Pubkeys")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            // arrays of JSON have no length, Pubkeys of the block grow as they are parsed
+            let mut pubkeys = Vec::new();
+            while let Some(Base58Pubkey(pubkey)) = seq.next_element()? {
+                pubkeys.push(pubkey);
+            }
+            Ok(pubkeys)
+        }
+    }
+
+    deserializer.deserialize_seq(PubkeysVisitor)
+}
+
+/// Owners of accounts keyed by base58 Pubkey.
+#[derive(Default)]
+struct JsonAccounts(BTreeMap<Pubkey, AccountOwner>);
+
+#[derive(Serialize, Deserialize)]
+struct JsonAccountOwner {
+    owner: Base58Pubkey,
+    executable: bool,
+}
+
+impl<'de> Deserialize<'de> for JsonAccounts {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JsonAccountsVisitor;
+
+        impl<'de> Visitor<'de> for JsonAccountsVisitor {
+            type Value = JsonAccounts;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("account owners keyed by base58 encoded Pubkey")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut accounts = BTreeMap::new();
+                while let Some((Base58Pubkey(pubkey), account)) =
+                    map.next_entry::<Base58Pubkey, JsonAccountOwner>()?
+                {
+                    let owner = AccountOwner {
+                        owner: account.owner.0,
+                        executable: account.executable,
+                    };
+                    accounts.insert(pubkey, owner);
+                }
+                Ok(JsonAccounts(accounts))
+            }
+        }
+
+        deserializer.deserialize_map(JsonAccountsVisitor)
+    }
+}
+
+struct JsonAccountsRef<'a>(&'a BTreeMap<Pubkey, AccountOwner>);
+
+impl Serialize for JsonAccountsRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(pubkey, account)| {
+            (
+                pubkey.to_string(),
+                JsonAccountOwner {
+                    owner: Base58Pubkey(account.owner),
+                    executable: account.executable,
+                },
+            )
+        }))
+    }
+}
+
+/// Blocks keyed by slot.
+struct JsonBlocks(BTreeMap<Slot, Block>);
+
+impl<'de> Deserialize<'de> for JsonBlocks {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JsonBlocksVisitor;
+
+        impl<'de> Visitor<'de> for JsonBlocksVisitor {
+            type Value = JsonBlocks;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("blocks keyed by slot")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut blocks = BTreeMap::new();
+                while let Some((slot, block)) = map.next_entry::<Slot, JsonBlock>()? {
+                    blocks.insert(slot, block.into());
+                }
+                Ok(JsonBlocks(blocks))
+            }
+        }
+
+        deserializer.deserialize_map(JsonBlocksVisitor)
+    }
+}
+
+struct JsonBlocksRef<'a>(&'a BTreeMap<Slot, Block>);
+
+impl Serialize for JsonBlocksRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .map(|(slot, block)| (slot, JsonBlockRef::from(block))),
+        )
+    }
+}
+
+/// Version, blocks and optional accounts of `Json` format, version 1 is object with slots as
+/// keys only.
+struct JsonDataset {
+    version: u32,
+    blocks: BTreeMap<Slot, Block>,
+    accounts: BTreeMap<Pubkey, AccountOwner>,
+}
+
+impl<'de> Deserialize<'de> for JsonDataset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JsonDatasetVisitor;
+
+        impl<'de> Visitor<'de> for JsonDatasetVisitor {
+            type Value = JsonDataset;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter
+                    .write_str("dataset object with version, blocks and accounts or slots as keys")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut version = 1;
+                let mut blocks = BTreeMap::new();
+                let mut accounts = BTreeMap::new();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "version" => version = map.next_value()?,
+                        "blocks" => blocks = map.next_value::<JsonBlocks>()?.0,
+                        "accounts" => accounts = map.next_value::<JsonAccounts>()?.0,
+                        slot => {
+                            let slot = slot.parse().map_err(de::Error::custom)?;
+                            blocks.insert(slot, map.next_value::<JsonBlock>()?.into());
+                        }
+                    }
+                }
+                Ok(JsonDataset {
+                    version,
+                    blocks,
+                    accounts,
+                })
+            }
+        }
+
+        deserializer.deserialize_map(JsonDatasetVisitor)
+    }
+}
+
+/// Object with `version`, `blocks` keyed by slot and `accounts` owners keyed by Pubkey (only
+/// if dataset is enriched), version 1 (written by `download` originally) is object with slots
+/// as keys.
+pub struct Json;
+
+impl DatasetLoader for Json {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let dataset: JsonDataset = serde_json::from_reader(reader).map_err(Error::parse)?;
+        migrate(dataset.version, dataset.blocks, dataset.accounts)
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let with_accounts = !dataset.accounts.is_empty();
+        let mut serializer = serde_json::Serializer::new(writer);
+        let mut map = serializer
+            .serialize_map(Some(2 + with_accounts as usize))
+            .map_err(Error::write)?;
+        map.serialize_entry("version", &DATASET_VERSION)
+            .map_err(Error::write)?;
+        map.serialize_entry("blocks", &JsonBlocksRef(&dataset.blocks))
+            .map_err(Error::write)?;
+        if with_accounts {
+            map.serialize_entry("accounts", &JsonAccountsRef(&dataset.accounts))
+                .map_err(Error::write)?;
+        }
+        SerializeMap::end(map).map_err(Error::write)
+    }
+}
+
+/// First line of `JsonLines` format, absent in version 1.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct JsonLinesHeader {
+    version: u32,
+    #[serde(default)]
+    accounts: JsonAccounts,
+}
+
+#[derive(Serialize)]
+struct JsonLinesHeaderRef<'a> {
+    version: u32,
+    #[serde(skip_serializing_if = "is_empty")]
+    accounts: JsonAccountsRef<'a>,
+}
+
+fn is_empty(accounts: &JsonAccountsRef) -> bool {
+    accounts.0.is_empty()
+}
+
+/// Header with version and accounts owners (only if dataset is enriched) and one JSON object
+/// with slot per line.
+pub struct JsonLines;
+
+impl DatasetLoader for JsonLines {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let mut version = None;
+        let mut blocks = BTreeMap::new();
+        let mut accounts = BTreeMap::new();
+        // one buffer for all lines, Pubkeys are decoded from strings borrowed from it
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if version.is_none() {
+                if let Ok(header) = serde_json::from_str::<JsonLinesHeader>(&line) {
+                    version = Some(header.version);
+                    accounts = header.accounts.0;
+                    continue;
+                }
+                version = Some(1);
+            }
+            let line: JsonLine = serde_json::from_str(&line).map_err(Error::parse)?;
+            let block = Block {
+                block_time: line.block_time,
+                pubkeys: line.pubkeys.into(),
+            };
+            blocks.insert(line.slot, block);
+        }
+        migrate(version.unwrap_or(1), blocks, accounts)
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let header = JsonLinesHeaderRef {
+            version: DATASET_VERSION,
+            accounts: JsonAccountsRef(&dataset.accounts),
+        };
+        serde_json::to_writer(&mut *writer, &header).map_err(Error::write)?;
+        writer.write_all(b"\n")?;
+        for (slot, block) in dataset.blocks.iter() {
+            let line = JsonLineRef {
+                slot: *slot,
+                block: block.into(),
+            };
+            serde_json::to_writer(&mut *writer, &line).map_err(Error::write)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,369 @@
+use {
+    super::{merge::Overlap, version::DATASET_VERSION, Dataset, DatasetFormat},
+    crate::{Error, Result},
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::Slot,
+        hash::Hasher,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+    },
+    std::{
+        collections::{BTreeMap, HashSet},
+        ffi::OsString,
+        fs::{self, File},
+        io::Read,
+        path::{Component, Path, PathBuf},
+    },
+};
+
+/// Suffix appended to the dataset path, `data.bin.zst` is described by
+/// `data.bin.zst.manifest.json`.
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// First and last slots of the dataset.
+fn slot_range(dataset: &Dataset) -> (Option<Slot>, Option<Slot>) {
+    (
+        dataset.blocks.keys().next().copied(),
+        dataset.blocks.keys().next_back().copied(),
+    )
+}
+
+/// SHA-256 of the file in hex, read in chunks, so large datasets are not loaded into memory.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Hasher::default();
+    let mut buffer = vec![0; 1 << 20];
+    loop {
+        match file.read(&mut buffer)? {
+            0 => break,
+            size => hasher.hash(&buffer[..size]),
+        }
+    }
+    Ok(hasher
+        .result()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// One file of the dataset with checksum and slots it holds.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    /// File name, relative to the directory of the manifest.
+    pub file: String,
+    pub size: u64,
+    pub sha256: String,
+    pub blocks: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_slot: Option<Slot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_slot: Option<Slot>,
+}
+
+/// Manifest written next to the dataset by `Dataset::save_with_manifest`, verified by
+/// `Dataset::load` before shards are decoded, so truncated or corrupted files fail on load
+/// instead of producing wrong numbers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    /// `DATASET_VERSION` of the shards.
+    pub version: u32,
+    /// Format of the shards, e.g. `bin.zst`.
+    pub format: String,
+    pub blocks: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_slot: Option<Slot>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_slot: Option<Slot>,
+    pub shards: Vec<Shard>,
+    /// Pubkey of the keypair which signed the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Signature of the manifest serialized without `signature`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// How `Dataset::save_with_manifest` writes the dataset.
+#[derive(Default)]
+pub struct ManifestConfig<'a> {
+    /// Split dataset into files of at most this number of blocks.
+    pub shard_blocks: Option<usize>,
+    /// Sign the manifest, readers can check the signer with `DatasetManifest::read`.
+    pub keypair: Option<&'a Keypair>,
+}
+
+impl DatasetManifest {
+    /// Path of the manifest of the dataset at `path`.
+    pub fn path(path: &Path) -> PathBuf {
+        let mut name = OsString::from(path.as_os_str());
+        name.push(MANIFEST_SUFFIX);
+        PathBuf::from(name)
+    }
+
+    /// Read manifest of the dataset at `path` if it exists, signature is verified if present.
+    pub fn read(path: &Path) -> Result<Option<Self>> {
+        let manifest_path = Self::path(path);
+        let data = match fs::read(&manifest_path) {
+            Ok(data) => data,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let manifest: Self = serde_json::from_slice(&data).map_err(|error| Error::Manifest {
+            path: manifest_path.clone(),
+            reason: error.to_string(),
+        })?;
+        manifest
+            .verify_shards()
+            .and_then(|()| manifest.verify_signature())
+            .map_err(|reason| Error::Manifest {
+                path: manifest_path,
+                reason,
+            })?;
+        Ok(Some(manifest))
+    }
+
+    /// Shards are files next to the manifest, paths outside of its directory are rejected.
+    fn verify_shards(&self) -> Result<(), String> {
+        for shard in &self.shards {
+            let mut components = Path::new(&shard.file).components();
+            if !components.all(|component| matches!(component, Component::Normal(_)))
+                || shard.file.is_empty()
+            {
+                return Err(format!(
+                    "shard {:?} is not a relative path inside the dataset directory",
+                    shard.file
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Signer of the verified manifest, `None` if manifest is not signed.
+    pub fn signer(&self) -> Option<Pubkey> {
+        self.signer
+            .as_deref()
+            .and_then(|signer| signer.parse().ok())
+    }
+
+    fn message(&self) -> Vec<u8> {
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        serde_json::to_vec(&unsigned).expect("manifest serialization")
+    }
+
+    fn sign(&mut self, keypair: &Keypair) {
+        self.signer = Some(keypair.pubkey().to_string());
+        self.signature = Some(keypair.sign_message(&self.message()).to_string());
+    }
+
+    fn verify_signature(&self) -> Result<(), String> {
+        match (&self.signer, &self.signature) {
+            (None, None) => Ok(()),
+            (Some(signer), Some(signature)) => {
+                let signer = signer
+                    .parse::<Pubkey>()
+                    .map_err(|error| format!("invalid signer: {}", error))?;
+                let signature = signature
+                    .parse::<Signature>()
+                    .map_err(|error| format!("invalid signature: {}", error))?;
+                if signature.verify(signer.as_ref(), &self.message()) {
+                    Ok(())
+                } else {
+                    Err(format!("signature does not match signer {}", signer))
+                }
+            }
+            _ => Err("both signer and signature are required".to_owned()),
+        }
+    }
+
+    /// Load shards listed in the manifest of the dataset at `path`, every shard is checked for
+    /// size and checksum before decoding and for blocks and slot range after. Same as
+    /// `Dataset::load` of the path, for a manifest already read with `DatasetManifest::read`.
+    pub fn load(&self, path: &Path) -> Result<Dataset> {
+        let manifest_path = Self::path(path);
+        let error = |reason: String| Error::Manifest {
+            path: manifest_path.clone(),
+            reason,
+        };
+        if self.version > DATASET_VERSION {
+            return Err(Error::UnsupportedVersion {
+                version: self.version,
+                latest: DATASET_VERSION,
+            });
+        }
+        let format = self
+            .format
+            .parse::<DatasetFormat>()
+            .map_err(|_| error(format!("unknown format {}", self.format)))?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut dataset = Dataset::default();
+        for shard in &self.shards {
+            let shard_path = dir.join(&shard.file);
+            let size = fs::metadata(&shard_path)
+                .map_err(|io_error| error(format!("{}: {}", shard.file, io_error)))?
+                .len();
+            if size != shard.size {
+                return Err(error(format!(
+                    "{} has {} bytes, expected {}",
+                    shard.file, size, shard.size
+                )));
+            }
+            let sha256 = sha256_file(&shard_path)?;
+            if sha256 != shard.sha256 {
+                return Err(error(format!(
+                    "{} has SHA-256 {}, expected {}",
+                    shard.file, sha256, shard.sha256
+                )));
+            }
+
+            let loaded = Dataset::load_file(&shard_path, format)?;
+            if loaded.blocks.len() != shard.blocks
+                || slot_range(&loaded) != (shard.first_slot, shard.last_slot)
+            {
+                return Err(error(format!(
+                    "{} blocks or slots do not match the manifest",
+                    shard.file
+                )));
+            }
+            if let (Some(first_slot), Some(last_slot)) =
+                (shard.first_slot, dataset.blocks.keys().next_back())
+            {
+                if first_slot <= *last_slot {
+                    return Err(error(format!(
+                        "{} overlaps slots of previous shards",
+                        shard.file
+                    )));
+                }
+            }
+            dataset.merge(loaded, Overlap::First);
+        }
+
+        if dataset.blocks.len() != self.blocks
+            || slot_range(&dataset) != (self.first_slot, self.last_slot)
+        {
+            return Err(error(
+                "blocks or slots of shards do not match the manifest".to_owned(),
+            ));
+        }
+        Ok(dataset)
+    }
+}
+
+impl Dataset {
+    /// Save dataset (in shards if `config.shard_blocks` is set) with the manifest of checksums
+    /// next to `path`. Shards are named by the path with index before the extension:
+    /// `data.bin.zst` is saved as `data-00000.bin.zst`, `data-00001.bin.zst`, ...
+    pub fn save_with_manifest(
+        &self,
+        path: &Path,
+        format: DatasetFormat,
+        config: ManifestConfig<'_>,
+    ) -> Result<DatasetManifest> {
+        let datasets = match config.shard_blocks {
+            Some(shard_blocks) => self.shards(shard_blocks.max(1)),
+            None => vec![self.clone()],
+        };
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| Error::UnsupportedFormat(path.to_owned()))?;
+        let extension = format.to_string();
+        let stem = name
+            .strip_suffix(&format!(".{}", extension))
+            .unwrap_or(name);
+
+        let mut shards = Vec::with_capacity(datasets.len());
+        for (index, dataset) in datasets.iter().enumerate() {
+            let file = match config.shard_blocks {
+                Some(_) => format!("{}-{:05}.{}", stem, index, extension),
+                None => name.to_owned(),
+            };
+            let shard_path = dir.join(&file);
+            dataset.save_with(&shard_path, format)?;
+            let (first_slot, last_slot) = slot_range(dataset);
+            shards.push(Shard {
+                size: fs::metadata(&shard_path)?.len(),
+                sha256: sha256_file(&shard_path)?,
+                file,
+                blocks: dataset.blocks.len(),
+                first_slot,
+                last_slot,
+            });
+        }
+
+        let (first_slot, last_slot) = slot_range(self);
+        let mut manifest = DatasetManifest {
+            version: DATASET_VERSION,
+            format: extension,
+            blocks: self.blocks.len(),
+            first_slot,
+            last_slot,
+            shards,
+            signer: None,
+            signature: None,
+        };
+        if let Some(keypair) = config.keypair {
+            manifest.sign(keypair);
+        }
+        let data = serde_json::to_vec_pretty(&manifest).map_err(Error::write)?;
+        fs::write(DatasetManifest::path(path), data)?;
+        Ok(manifest)
+    }
+
+    /// Split into datasets of `shard_blocks` consecutive blocks, every shard has owners of its
+    /// Pubkeys and the first one also owners of Pubkeys missed in blocks.
+    fn shards(&self, shard_blocks: usize) -> Vec<Dataset> {
+        let blocks = self.blocks.iter().collect::<Vec<_>>();
+        let mut shards = blocks
+            .chunks(shard_blocks)
+            .map(|chunk| {
+                let blocks = chunk
+                    .iter()
+                    .map(|(slot, block)| (**slot, (*block).clone()))
+                    .collect::<BTreeMap<_, _>>();
+                let pubkeys = blocks
+                    .values()
+                    .flat_map(|block| &block.pubkeys)
+                    .collect::<HashSet<_>>();
+                let accounts = pubkeys
+                    .into_iter()
+                    .filter_map(|pubkey| Some((*pubkey, *self.accounts.get(pubkey)?)))
+                    .collect();
+                Dataset { blocks, accounts }
+            })
+            .collect::<Vec<_>>();
+
+        if shards.is_empty() {
+            shards.push(Dataset {
+                blocks: BTreeMap::new(),
+                accounts: self.accounts.clone(),
+            });
+        } else {
+            let referenced = self
+                .keys()
+                .map(|(_slot, pubkey)| pubkey)
+                .collect::<HashSet<_>>();
+            shards[0].accounts.extend(
+                self.accounts
+                    .iter()
+                    .filter(|(pubkey, _account)| !referenced.contains(pubkey))
+                    .map(|(pubkey, account)| (*pubkey, *account)),
+            );
+        }
+        shards
+    }
+
+    /// Load dataset with manifest at `path`, shards are verified against it.
+    pub(super) fn load_manifest(path: &Path) -> Result<Option<Self>> {
+        DatasetManifest::read(path)?
+            .map(|manifest| manifest.load(path))
+            .transpose()
+    }
+}
@@ -0,0 +1,114 @@
+use {
+    super::Dataset,
+    serde::Serialize,
+    std::{
+        collections::{btree_map::Entry, HashSet},
+        fmt,
+        str::FromStr,
+    },
+};
+
+/// Resolution of slots present in both datasets in `Dataset::merge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overlap {
+    /// Keep block of the dataset merged into.
+    First,
+    /// Replace block with block of the merged dataset.
+    Last,
+    /// Union of Pubkeys of both blocks, block time of the first.
+    Union,
+}
+
+impl Overlap {
+    pub const ALL: [Self; 3] = [Self::First, Self::Last, Self::Union];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::First => "first",
+            Self::Last => "last",
+            Self::Union => "union",
+        }
+    }
+}
+
+impl fmt::Display for Overlap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Overlap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|overlap| overlap.name() == s)
+            .ok_or_else(|| format!("unknown overlap resolution: {}", s))
+    }
+}
+
+/// Slots added and resolved by `Dataset::merge`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct MergeStats {
+    pub added: usize,
+    pub overlapping: usize,
+    /// Overlapping slots with different block time or Pubkeys.
+    pub conflicts: usize,
+}
+
+impl MergeStats {
+    pub fn extend(&mut self, other: Self) {
+        self.added += other.added;
+        self.overlapping += other.overlapping;
+        self.conflicts += other.conflicts;
+    }
+}
+
+impl Dataset {
+    /// Add blocks of `other` (e.g. another window or shard of the same chain), slots present
+    /// in both datasets are resolved with `overlap`, as well as account owners (`Union` keeps
+    /// the first).
+    pub fn merge(&mut self, other: Dataset, overlap: Overlap) -> MergeStats {
+        let mut stats = MergeStats::default();
+        for (pubkey, owner) in other.accounts {
+            match overlap {
+                Overlap::Last => {
+                    self.accounts.insert(pubkey, owner);
+                }
+                Overlap::First | Overlap::Union => {
+                    self.accounts.entry(pubkey).or_insert(owner);
+                }
+            }
+        }
+        for (slot, mut block) in other.blocks {
+            match self.blocks.entry(slot) {
+                Entry::Vacant(entry) => {
+                    stats.added += 1;
+                    entry.insert(block);
+                }
+                Entry::Occupied(mut entry) => {
+                    stats.overlapping += 1;
+                    let current = entry.get_mut();
+                    if *current == block {
+                        continue;
+                    }
+                    stats.conflicts += 1;
+                    match overlap {
+                        Overlap::First => {}
+                        Overlap::Last => *current = block,
+                        Overlap::Union => {
+                            let known = current.pubkeys.iter().copied().collect::<HashSet<_>>();
+                            block
+                                .pubkeys
+                                .to_mut()
+                                .retain(|pubkey| !known.contains(pubkey));
+                            current.pubkeys.to_mut().append(block.pubkeys.to_mut());
+                        }
+                    }
+                }
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,349 @@
+use {
+    crate::{Error, Result},
+    rayon::iter::{IntoParallelRefIterator, ParallelIterator},
+    serde::{Deserialize, Serialize},
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::Pubkey,
+    },
+    std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        fmt,
+        fs::{self, File},
+        io::{BufRead, BufReader, BufWriter, Write},
+        path::Path,
+        str::FromStr,
+    },
+};
+
+mod analyze;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod bincode;
+mod generate;
+mod indexed;
+mod iter;
+mod json;
+mod manifest;
+mod merge;
+mod msgpack;
+mod pubkeys;
+mod subsample;
+mod version;
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::Arrow;
+pub(crate) use json::deserialize_pubkeys;
+pub use {
+    self::bincode::Bincode,
+    analyze::{AnalyzeConfig, DatasetStats, Distribution, HotKey, TimelineBucket, WorkingSet},
+    generate::{GenerateConfig, Popularity},
+    indexed::Indexed,
+    iter::BlocksIter,
+    json::{Json, JsonLines},
+    manifest::{sha256_file, DatasetManifest, ManifestConfig, Shard, MANIFEST_SUFFIX},
+    merge::{MergeStats, Overlap},
+    msgpack::MessagePack,
+    pubkeys::Pubkeys,
+    subsample::Subsample,
+    version::DATASET_VERSION,
+};
+
+/// Block with unique Pubkeys from all transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub block_time: UnixTimestamp,
+    pub pubkeys: Pubkeys,
+}
+
+/// Owner of the account, requested from RPC by `enrich`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountOwner {
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+/// Blocks downloaded by `download` and used by `bench`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Dataset {
+    pub blocks: BTreeMap<Slot, Block>,
+    /// Owners of existing accounts, empty if dataset is not enriched.
+    pub accounts: BTreeMap<Pubkey, AccountOwner>,
+}
+
+impl Dataset {
+    /// Load dataset, format and compression are detected by file extension.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::load_with(path, DatasetFormat::from_path(path)?)
+    }
+
+    /// Load dataset in `format` regardless of file extension. If the dataset has a manifest
+    /// (`data.bin.zst.manifest.json`), files listed in it are verified and loaded instead.
+    pub fn load_with(path: &Path, format: DatasetFormat) -> Result<Self> {
+        match Self::load_manifest(path)? {
+            Some(dataset) => Ok(dataset),
+            None => Self::load_file(path, format),
+        }
+    }
+
+    fn load_file(path: &Path, format: DatasetFormat) -> Result<Self> {
+        let file = File::open(path)?;
+        if format.format == Format::Bincode && format.compression == Compression::None {
+            return self::bincode::load_mmap(&file);
+        }
+        if format.format == Format::Indexed && format.compression == Compression::None {
+            return indexed::load_mmap(&file);
+        }
+        #[cfg(feature = "arrow")]
+        if format.format == Format::Arrow && format.compression == Compression::None {
+            return self::arrow::load_mmap(&file);
+        }
+        format.load(&mut BufReader::new(file))
+    }
+
+    /// Save dataset, format and compression are detected by file extension.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        self.save_with(path, DatasetFormat::from_path(path)?)
+    }
+
+    /// Save dataset in `format` regardless of file extension. Dataset is written to a temporary
+    /// file next to `path` and renamed over it, so a memory-mapped dataset loaded from `path` is
+    /// never modified and a failed save keeps the previous file.
+    pub fn save_with(&self, path: &Path, format: DatasetFormat) -> Result<()> {
+        let mut name = path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = path.with_file_name(name);
+        let saved = File::create(&temp_path)
+            .map_err(Error::from)
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                format.save(self, &mut writer)?;
+                writer.flush().map_err(Into::into)
+            })
+            .and_then(|()| fs::rename(&temp_path, path).map_err(Into::into));
+        if saved.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        saved
+    }
+
+    /// Blocks in slot order, can be filtered with `slots` and `block_time`:
+    /// `dataset.blocks().slots(from..).block_time(..end_time)`.
+    pub fn blocks(&self) -> BlocksIter<'_> {
+        BlocksIter::new(&self.blocks)
+    }
+
+    /// Pubkeys of all blocks with slot, same as `dataset.blocks().keys()`. There is no
+    /// iterator of transactions, `download` dedupes keys of all transactions of a block into
+    /// one list, so transactions are not kept in any dataset format.
+    pub fn keys(&self) -> impl Iterator<Item = (Slot, &Pubkey)> {
+        self.blocks().keys()
+    }
+
+    /// Number of unique Pubkeys in all blocks.
+    pub fn unique_pubkeys(&self) -> usize {
+        self.keys()
+            .map(|(_slot, pubkey)| pubkey)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// Number of blocks referencing every Pubkey.
+    pub fn key_counts(&self) -> HashMap<Pubkey, usize> {
+        let mut counts = HashMap::new();
+        for (_slot, pubkey) in self.keys() {
+            *counts.entry(*pubkey).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Keep only Pubkeys matching `f` in every block, blocks left without Pubkeys are kept.
+    pub fn retain_keys(&mut self, mut f: impl FnMut(&Pubkey) -> bool) {
+        for block in self.blocks.values_mut() {
+            block.pubkeys.to_mut().retain(&mut f);
+        }
+        self.accounts.retain(|pubkey, _owner| f(pubkey));
+    }
+
+    /// Decode Pubkeys of all blocks in parallel, a no-op for formats decoded on load.
+    pub fn decode(&self) {
+        self.blocks.par_iter().for_each(|(_slot, block)| {
+            block.pubkeys.as_slice();
+        });
+    }
+
+    /// Owner of the account if dataset is enriched and account exists.
+    pub fn owner(&self, pubkey: &Pubkey) -> Option<&Pubkey> {
+        self.accounts.get(pubkey).map(|account| &account.owner)
+    }
+}
+
+/// Serialization format of the dataset.
+pub trait DatasetLoader {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset>;
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Object with version and blocks keyed by slot with base58 Pubkeys, `.json`.
+    Json,
+    /// Header with version and one block with slot per line, `.jsonl`.
+    JsonLines,
+    /// `bincode` with raw Pubkeys, `.bin`, memory-mapped on load if not compressed.
+    Bincode,
+    /// MessagePack with raw Pubkeys, `.msgpack`.
+    MessagePack,
+    /// Table of unique Pubkeys with indices of every block decoded on the first access,
+    /// `.safb`, memory-mapped on load if not compressed.
+    Indexed,
+    /// Arrow IPC file (Feather v2) with raw Pubkeys, `.arrow` or `.feather`, memory-mapped on
+    /// load if not compressed.
+    #[cfg(feature = "arrow")]
+    Arrow,
+}
+
+impl Format {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::JsonLines => "jsonl",
+            Self::Bincode => "bin",
+            Self::MessagePack => "msgpack",
+            Self::Indexed => "safb",
+            #[cfg(feature = "arrow")]
+            Self::Arrow => "arrow",
+        }
+    }
+
+    pub fn loader(self) -> &'static dyn DatasetLoader {
+        match self {
+            Self::Json => &Json,
+            Self::JsonLines => &JsonLines,
+            Self::Bincode => &Bincode,
+            Self::MessagePack => &MessagePack,
+            Self::Indexed => &Indexed,
+            #[cfg(feature = "arrow")]
+            Self::Arrow => &Arrow,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    /// `.gz`
+    Gzip,
+    /// `.zst`
+    Zstd,
+}
+
+/// Format with compression, e.g. `data.jsonl.zst`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetFormat {
+    pub format: Format,
+    pub compression: Compression,
+}
+
+impl DatasetFormat {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(Self::from_file_name)
+            .ok_or_else(|| Error::UnsupportedFormat(path.to_owned()))
+    }
+
+    fn from_file_name(name: &str) -> Option<Self> {
+        let (name, compression) = if let Some(name) = name.strip_suffix(".gz") {
+            (name, Compression::Gzip)
+        } else if let Some(name) = name.strip_suffix(".zst") {
+            (name, Compression::Zstd)
+        } else {
+            (name, Compression::None)
+        };
+
+        #[cfg(feature = "arrow")]
+        if name.ends_with(".arrow") || name.ends_with(".feather") {
+            return Some(Self {
+                format: Format::Arrow,
+                compression,
+            });
+        }
+
+        let format = if name.ends_with(".json") {
+            Format::Json
+        } else if name.ends_with(".jsonl") {
+            Format::JsonLines
+        } else if name.ends_with(".bin") {
+            Format::Bincode
+        } else if name.ends_with(".msgpack") {
+            Format::MessagePack
+        } else if name.ends_with(".safb") {
+            Format::Indexed
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            format,
+            compression,
+        })
+    }
+
+    pub fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let loader = self.format.loader();
+        match self.compression {
+            Compression::None => loader.load(reader),
+            Compression::Gzip => {
+                let mut reader = BufReader::new(flate2::bufread::GzDecoder::new(reader));
+                loader.load(&mut reader)
+            }
+            Compression::Zstd => {
+                let mut reader = BufReader::new(zstd::Decoder::with_buffer(reader)?);
+                loader.load(&mut reader)
+            }
+        }
+    }
+
+    pub fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let loader = self.format.loader();
+        match self.compression {
+            Compression::None => loader.save(dataset, writer),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+                loader.save(dataset, &mut encoder)?;
+                encoder.finish()?;
+                Ok(())
+            }
+            Compression::Zstd => {
+                let mut encoder = zstd::Encoder::new(writer, 0)?;
+                loader.save(dataset, &mut encoder)?;
+                encoder.finish()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Extension without file name, e.g. `jsonl.zst`.
+impl fmt::Display for DatasetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.format.extension())?;
+        match self.compression {
+            Compression::None => Ok(()),
+            Compression::Gzip => f.write_str(".gz"),
+            Compression::Zstd => f.write_str(".zst"),
+        }
+    }
+}
+
+/// Format by extension without file name: `json`, `jsonl.zst`, `bin.gz`.
+impl FromStr for DatasetFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_file_name(&format!(".{}", s)).ok_or_else(|| Error::UnsupportedFormat(s.into()))
+    }
+}
@@ -0,0 +1,151 @@
+use {
+    super::{
+        version::{migrate, DATASET_VERSION},
+        AccountOwner, Block, Dataset, DatasetLoader,
+    },
+    crate::{Error, Result},
+    serde::{
+        de::{self, SeqAccess, Visitor},
+        Deserialize, Deserializer, Serialize, Serializer,
+    },
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        pubkey::{Pubkey, PUBKEY_BYTES},
+    },
+    std::{
+        collections::BTreeMap,
+        fmt,
+        io::{BufRead, Write},
+    },
+};
+
+/// Bytes reserved ahead for Pubkeys written as an array instead of `bin`.
+const MAX_PREALLOCATED: usize = 1 << 16;
+
+/// Pubkeys of the block as one MessagePack `bin` of concatenated 32 bytes keys.
+struct PubkeysBytes(Vec<Pubkey>);
+
+struct PubkeysBytesRef<'a>(&'a [Pubkey]);
+
+impl Serialize for PubkeysBytesRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self
+            .0
+            .iter()
+            .flat_map(|pubkey| pubkey.to_bytes())
+            .collect::<Vec<_>>();
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+impl<'de> Deserialize<'de> for PubkeysBytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct PubkeysVisitor;
+
+        impl<'de> Visitor<'de> for PubkeysVisitor {
+            type Value = PubkeysBytes;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("concatenated 32 bytes Pubkeys")
+            }
+
+            fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+                let chunks = bytes.chunks_exact(PUBKEY_BYTES);
+                if !chunks.remainder().is_empty() {
+                    return Err(E::invalid_length(bytes.len(), &self));
+                }
+                Ok(PubkeysBytes(chunks.map(Pubkey::new).collect()))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                // length of the array is not trusted, it can be larger than the file
+                let capacity = seq.size_hint().unwrap_or_default().min(MAX_PREALLOCATED);
+                let mut bytes = Vec::with_capacity(capacity);
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(PubkeysVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+struct MsgpackBlock {
+    block_time: UnixTimestamp,
+    pubkeys: PubkeysBytes,
+}
+
+#[derive(Serialize)]
+struct MsgpackBlockRef<'a> {
+    block_time: UnixTimestamp,
+    pubkeys: PubkeysBytesRef<'a>,
+}
+
+struct MsgpackBlocksRef<'a>(&'a BTreeMap<Slot, Block>);
+
+impl Serialize for MsgpackBlocksRef<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(slot, block)| {
+            (
+                slot,
+                MsgpackBlockRef {
+                    block_time: block.block_time,
+                    pubkeys: PubkeysBytesRef(&block.pubkeys),
+                },
+            )
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+struct MsgpackDataset {
+    version: u32,
+    blocks: BTreeMap<Slot, MsgpackBlock>,
+    #[serde(default)]
+    accounts: BTreeMap<Pubkey, AccountOwner>,
+}
+
+#[derive(Serialize)]
+struct MsgpackDatasetRef<'a> {
+    version: u32,
+    blocks: MsgpackBlocksRef<'a>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    accounts: &'a BTreeMap<Pubkey, AccountOwner>,
+}
+
+/// MessagePack map with `version`, `blocks` keyed by slot and `accounts` owners keyed by Pubkey
+/// (only if dataset is enriched), Pubkeys of the block are one binary string. Added in
+/// version 2, so there is nothing to migrate.
+pub struct MessagePack;
+
+impl DatasetLoader for MessagePack {
+    fn load(&self, reader: &mut dyn BufRead) -> Result<Dataset> {
+        let dataset: MsgpackDataset = rmp_serde::from_read(reader).map_err(Error::parse)?;
+        let blocks = dataset
+            .blocks
+            .into_iter()
+            .map(|(slot, block)| {
+                (
+                    slot,
+                    Block {
+                        block_time: block.block_time,
+                        pubkeys: block.pubkeys.0.into(),
+                    },
+                )
+            })
+            .collect();
+        migrate(dataset.version, blocks, dataset.accounts)
+    }
+
+    fn save(&self, dataset: &Dataset, writer: &mut dyn Write) -> Result<()> {
+        let dataset = MsgpackDatasetRef {
+            version: DATASET_VERSION,
+            blocks: MsgpackBlocksRef(&dataset.blocks),
+            accounts: &dataset.accounts,
+        };
+        rmp_serde::encode::write_named(writer, &dataset).map_err(Error::write)
+    }
+}
@@ -0,0 +1,196 @@
+use {
+    memmap2::Mmap,
+    serde::{Deserialize, Deserializer, Serialize, Serializer},
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        fmt,
+        ops::{Deref, Range},
+        sync::{Arc, OnceLock},
+    },
+};
+
+/// Pubkeys of the block, owned, borrowed from the memory-mapped dataset file (`Pubkey` is
+/// 32 bytes with alignment 1, so raw keys of the file are used in place) or encoded in the
+/// loaded file and decoded on the first access. Borrowed and encoded Pubkeys are copied on the
+/// first mutation with `to_mut`, their clones share the file and decoded Pubkeys.
+#[derive(Clone)]
+pub struct Pubkeys(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Owned(Vec<Pubkey>),
+    Mapped {
+        mmap: Arc<Mmap>,
+        offset: usize,
+        len: usize,
+    },
+    Lazy(Arc<LazyPubkeys>),
+}
+
+/// Bytes of the loaded file, memory-mapped or read.
+pub(super) type FileBytes = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Pubkeys as `u32` indices into the table of unique Pubkeys of the file.
+struct LazyPubkeys {
+    bytes: FileBytes,
+    table: Range<usize>,
+    indices: Range<usize>,
+    decoded: OnceLock<Vec<Pubkey>>,
+}
+
+impl LazyPubkeys {
+    fn decode(&self) -> &[Pubkey] {
+        self.decoded.get_or_init(|| {
+            let bytes = (*self.bytes).as_ref();
+            let table: &[Pubkey] = bytemuck::cast_slice(&bytes[self.table.clone()]);
+            bytes[self.indices.clone()]
+                .chunks_exact(4)
+                .map(|index| table[u32::from_le_bytes(index.try_into().expect("4 bytes")) as usize])
+                .collect()
+        })
+    }
+}
+
+impl Pubkeys {
+    /// `len` Pubkeys at `offset` of `mmap`, `None` if they are out of the map.
+    pub(super) fn mapped(mmap: &Arc<Mmap>, offset: usize, len: usize) -> Option<Self> {
+        let end = len.checked_mul(32)?.checked_add(offset)?;
+        (end <= mmap.len()).then(|| {
+            Self(Repr::Mapped {
+                mmap: Arc::clone(mmap),
+                offset,
+                len,
+            })
+        })
+    }
+
+    /// `len` indices at `offset` of `bytes` into `table` of Pubkeys, decoded on the first
+    /// access. `None` if indices are out of `bytes` or out of `table`.
+    pub(super) fn lazy(
+        bytes: &FileBytes,
+        table: Range<usize>,
+        offset: usize,
+        len: usize,
+    ) -> Option<Self> {
+        let indices = offset..len.checked_mul(4)?.checked_add(offset)?;
+        let table_len = table.len() / 32;
+        let valid = (**bytes)
+            .as_ref()
+            .get(indices.clone())?
+            .chunks_exact(4)
+            .all(|index| {
+                (u32::from_le_bytes(index.try_into().expect("4 bytes")) as usize) < table_len
+            });
+        valid.then(|| {
+            Self(Repr::Lazy(Arc::new(LazyPubkeys {
+                bytes: Arc::clone(bytes),
+                table,
+                indices,
+                decoded: OnceLock::new(),
+            })))
+        })
+    }
+
+    pub fn as_slice(&self) -> &[Pubkey] {
+        self
+    }
+
+    /// Pubkeys are borrowed from the memory-mapped file.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.0, Repr::Mapped { .. })
+    }
+
+    /// Pubkeys are owned or already decoded.
+    pub fn is_decoded(&self) -> bool {
+        match &self.0 {
+            Repr::Lazy(lazy) => lazy.decoded.get().is_some(),
+            _ => true,
+        }
+    }
+
+    /// Owned Pubkeys for mutation, mapped and encoded Pubkeys are copied first.
+    pub fn to_mut(&mut self) -> &mut Vec<Pubkey> {
+        if !matches!(self.0, Repr::Owned(_)) {
+            self.0 = Repr::Owned(self.to_vec());
+        }
+        match &mut self.0 {
+            Repr::Owned(pubkeys) => pubkeys,
+            _ => unreachable!("copied above"),
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<Pubkey> {
+        match self.0 {
+            Repr::Owned(pubkeys) => pubkeys,
+            _ => self.to_vec(),
+        }
+    }
+}
+
+impl Deref for Pubkeys {
+    type Target = [Pubkey];
+
+    fn deref(&self) -> &[Pubkey] {
+        match &self.0 {
+            Repr::Owned(pubkeys) => pubkeys,
+            Repr::Mapped { mmap, offset, len } => {
+                bytemuck::cast_slice(&mmap[*offset..*offset + *len * 32])
+            }
+            Repr::Lazy(lazy) => lazy.decode(),
+        }
+    }
+}
+
+impl Default for Pubkeys {
+    fn default() -> Self {
+        Self(Repr::Owned(vec![]))
+    }
+}
+
+impl From<Vec<Pubkey>> for Pubkeys {
+    fn from(pubkeys: Vec<Pubkey>) -> Self {
+        Self(Repr::Owned(pubkeys))
+    }
+}
+
+impl FromIterator<Pubkey> for Pubkeys {
+    fn from_iter<I: IntoIterator<Item = Pubkey>>(iter: I) -> Self {
+        Self(Repr::Owned(iter.into_iter().collect()))
+    }
+}
+
+impl<'a> IntoIterator for &'a Pubkeys {
+    type Item = &'a Pubkey;
+    type IntoIter = std::slice::Iter<'a, Pubkey>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl PartialEq for Pubkeys {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Pubkeys {}
+
+impl fmt::Debug for Pubkeys {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Same as `Vec<Pubkey>`, so formats do not depend on the representation.
+impl Serialize for Pubkeys {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Pubkeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,49 @@
+use {super::Dataset, solana_sdk::pubkey::Pubkey, std::collections::HashSet};
+
+/// Selection of blocks for `Dataset::subsample`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsample {
+    /// Every Nth block in slot order, starting from the first.
+    EveryNth(usize),
+    /// Consecutive blocks from the first until they have at least given number of unique
+    /// keys, so locality of the capture is preserved.
+    UniqueKeys(usize),
+}
+
+impl Dataset {
+    /// Smaller dataset for quick runs, blocks are copied as is with owners of their accounts.
+    pub fn subsample(&self, subsample: Subsample) -> Dataset {
+        let blocks = match subsample {
+            Subsample::EveryNth(n) => self
+                .blocks
+                .iter()
+                .step_by(n.max(1))
+                .map(|(slot, block)| (*slot, block.clone()))
+                .collect(),
+            Subsample::UniqueKeys(target) => {
+                let mut keys = HashSet::<&Pubkey>::new();
+                self.blocks
+                    .iter()
+                    .take_while(|(_slot, block)| {
+                        let take = keys.len() < target;
+                        keys.extend(block.pubkeys.iter());
+                        take
+                    })
+                    .map(|(slot, block)| (*slot, block.clone()))
+                    .collect()
+            }
+        };
+        let mut dataset = Dataset {
+            blocks,
+            accounts: self.accounts.clone(),
+        };
+        let keys = dataset
+            .keys()
+            .map(|(_slot, pubkey)| *pubkey)
+            .collect::<HashSet<_>>();
+        dataset
+            .accounts
+            .retain(|pubkey, _owner| keys.contains(pubkey));
+        dataset
+    }
+}
@@ -0,0 +1,28 @@
+use {
+    super::{AccountOwner, Block, Dataset},
+    crate::{Error, Result},
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::collections::BTreeMap,
+};
+
+/// Version of the format written by `Dataset::save`. Formats without version (written before
+/// versioning) are version 1.
+pub const DATASET_VERSION: u32 = 3;
+
+/// Upgrade blocks and accounts loaded from format of `version` to the current in-memory
+/// representation.
+pub(super) fn migrate(
+    version: u32,
+    blocks: BTreeMap<Slot, Block>,
+    accounts: BTreeMap<Pubkey, AccountOwner>,
+) -> Result<Dataset> {
+    match version {
+        // version 2 only adds `version` to the formats, version 3 adds optional accounts owners,
+        // blocks are the same
+        1..=3 => Ok(Dataset { blocks, accounts }),
+        _ => Err(Error::UnsupportedVersion {
+            version,
+            latest: DATASET_VERSION,
+        }),
+    }
+}
@@ -0,0 +1,75 @@
+//! Errors of the library, binaries wrap them into `anyhow`.
+
+use {
+    std::{io, path::PathBuf, time::SystemTimeError},
+    thiserror::Error,
+};
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Dataset file can not be decoded.
+    #[error("failed to parse dataset: {0}")]
+    DatasetParse(#[source] BoxError),
+    /// Dataset can not be encoded.
+    #[error("failed to write dataset: {0}")]
+    DatasetWrite(#[source] BoxError),
+    /// Dataset path without known extension.
+    #[error("unknown dataset format: {0:?}")]
+    UnsupportedFormat(PathBuf),
+    /// Dataset files do not match the manifest written with them.
+    #[error("dataset manifest {path:?}: {reason}")]
+    Manifest { path: PathBuf, reason: String },
+    #[error("unsupported dataset version {version}, latest supported is {latest}")]
+    UnsupportedVersion { version: u32, latest: u32 },
+    #[error("invalid geyser config: {0}")]
+    GeyserConfig(#[from] serde_yaml::Error),
+    /// Benchmark requires cargo features disabled in this build.
+    #[error("benchmark {name} is unavailable, build with `--features {}`", features.join(","))]
+    BenchUnavailable {
+        name: &'static str,
+        features: Vec<&'static str>,
+    },
+    /// Benchmark can not run with given `BenchConfig`.
+    #[error("benchmark {name} {reason}")]
+    BenchConfig { name: &'static str, reason: String },
+    /// Parameter of `BenchConfig` out of its range.
+    #[error("invalid bench config: {0}")]
+    InvalidBenchConfig(String),
+    #[error("failed to build thread pool: {0}")]
+    ThreadPool(#[from] rayon::ThreadPoolBuildError),
+    #[error("system clock went backwards: {0}")]
+    Clock(#[from] SystemTimeError),
+    #[error("tokio task failed: {0}")]
+    TokioTask(#[from] tokio::task::JoinError),
+    #[error("pipeline consumers disconnected")]
+    PipelineDisconnected,
+    #[error("gRPC transport: {0}")]
+    Grpc(#[from] tonic::transport::Error),
+    #[error("invalid gRPC endpoint: {0}")]
+    GrpcEndpoint(String),
+    /// Error status of gRPC call, boxed because `Status` is large.
+    #[error("gRPC status: {0}")]
+    GrpcStatus(Box<tonic::Status>),
+}
+
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        Self::GrpcStatus(Box::new(status))
+    }
+}
+
+impl Error {
+    pub(crate) fn parse(error: impl Into<BoxError>) -> Self {
+        Self::DatasetParse(error.into())
+    }
+
+    pub(crate) fn write(error: impl Into<BoxError>) -> Self {
+        Self::DatasetWrite(error.into())
+    }
+}
@@ -0,0 +1,139 @@
+//! C API for filters, see `include/safb.h`. Pubkeys are passed as pointers to 32 bytes.
+
+use {
+    crate::{keyset, AccountsFilter},
+    solana_program::pubkey::{Pubkey, PUBKEY_BYTES},
+    std::{collections::HashSet, slice},
+};
+
+pub const SAFB_FILTER_HASHSET: u32 = 0;
+pub const SAFB_FILTER_FST: u32 = 1;
+pub const SAFB_FILTER_GXHASH: u32 = 2;
+pub const SAFB_FILTER_FLURRY: u32 = 3;
+pub const SAFB_FILTER_SORTED: u32 = 4;
+pub const SAFB_FILTER_PREFIX: u32 = 5;
+pub const SAFB_FILTER_BLOOM: u32 = 6;
+pub const SAFB_FILTER_XOR: u32 = 7;
+
+/// Opaque filter handle.
+pub struct SafbFilter(Box<dyn AccountsFilter + Send + Sync>);
+
+unsafe fn read_pubkey(pubkey: *const u8) -> Pubkey {
+    let mut bytes = [0; PUBKEY_BYTES];
+    bytes.copy_from_slice(slice::from_raw_parts(pubkey, PUBKEY_BYTES));
+    Pubkey::new_from_array(bytes)
+}
+
+/// Create filter of `kind` (`SAFB_FILTER_*`), returns null if kind is unknown or not enabled
+/// in this build. Filter should be released with `safb_filter_free`.
+#[no_mangle]
+pub extern "C" fn safb_filter_new(kind: u32, capacity: usize) -> *mut SafbFilter {
+    let filter: Box<dyn AccountsFilter + Send + Sync> = match kind {
+        SAFB_FILTER_HASHSET => Box::new(HashSet::<Pubkey>::with_capacity(capacity)),
+        #[cfg(feature = "fst")]
+        SAFB_FILTER_FST => Box::<crate::filter::FstFilter>::default(),
+        #[cfg(feature = "gxhash")]
+        SAFB_FILTER_GXHASH => Box::new(crate::filter::GxHashSet::with_capacity_and_hasher(
+            capacity,
+            Default::default(),
+        )),
+        #[cfg(feature = "flurry")]
+        SAFB_FILTER_FLURRY => Box::new(flurry::HashSet::<Pubkey>::with_capacity(capacity)),
+        SAFB_FILTER_SORTED => Box::<keyset::SortedKeys>::default(),
+        SAFB_FILTER_PREFIX => Box::<keyset::PrefixSet>::default(),
+        SAFB_FILTER_BLOOM => Box::new(keyset::BloomFilter::new(capacity)),
+        SAFB_FILTER_XOR => Box::<keyset::XorFilter>::default(),
+        _ => return std::ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(SafbFilter(filter)))
+}
+
+/// # Safety
+///
+/// `filter` should be created by `safb_filter_new` (or null), it can not be used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_free(filter: *mut SafbFilter) {
+    if !filter.is_null() {
+        drop(Box::from_raw(filter));
+    }
+}
+
+/// Returns `true` if Pubkey was not present.
+///
+/// # Safety
+///
+/// `filter` should be valid and not used concurrently, `pubkey` should point to 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_insert(filter: *mut SafbFilter, pubkey: *const u8) -> bool {
+    (*filter).0.insert(read_pubkey(pubkey))
+}
+
+/// Returns `true` if Pubkey was present.
+///
+/// # Safety
+///
+/// `filter` should be valid and not used concurrently, `pubkey` should point to 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_remove(filter: *mut SafbFilter, pubkey: *const u8) -> bool {
+    (*filter).0.remove(&read_pubkey(pubkey))
+}
+
+/// Merge inserted Pubkeys into immutable filters, should be called after bulk insert.
+///
+/// # Safety
+///
+/// `filter` should be valid and not used concurrently.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_finish(filter: *mut SafbFilter) {
+    (*filter).0.finish()
+}
+
+/// # Safety
+///
+/// `filter` should be valid, `pubkey` should point to 32 bytes. Lookups can be done
+/// from many threads, but not concurrently with updates.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_contains(
+    filter: *const SafbFilter,
+    pubkey: *const u8,
+) -> bool {
+    (*filter).0.contains(&read_pubkey(pubkey))
+}
+
+/// Lookup `count` Pubkeys stored one after another, returns number of matches.
+///
+/// # Safety
+///
+/// Same as `safb_filter_contains`, `pubkeys` should point to `count * 32` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_contains_many(
+    filter: *const SafbFilter,
+    pubkeys: *const u8,
+    count: usize,
+) -> usize {
+    (0..count)
+        .filter(|index| {
+            (*filter)
+                .0
+                .contains(&read_pubkey(pubkeys.add(index * PUBKEY_BYTES)))
+        })
+        .count()
+}
+
+/// # Safety
+///
+/// `filter` should be valid.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_len(filter: *const SafbFilter) -> usize {
+    (*filter).0.len()
+}
+
+/// Approximate heap memory used by the filter, in bytes.
+///
+/// # Safety
+///
+/// `filter` should be valid.
+#[no_mangle]
+pub unsafe extern "C" fn safb_filter_memory_usage(filter: *const SafbFilter) -> usize {
+    (*filter).0.memory_usage()
+}
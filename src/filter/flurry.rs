@@ -0,0 +1,39 @@
+use {
+    super::AccountsFilter,
+    flurry::HashSet,
+    std::{
+        hash::{BuildHasher, Hash},
+        mem,
+    },
+};
+
+/// Concurrent set, every operation pins epoch guard.
+impl<K, S> AccountsFilter<K> for HashSet<K, S>
+where
+    K: Hash + Ord + Clone + Send + Sync + 'static,
+    S: BuildHasher + Clone,
+{
+    fn insert(&mut self, key: K) -> bool {
+        HashSet::insert(self, key, &self.guard())
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        HashSet::remove(self, key, &self.guard())
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        HashSet::contains(self, key, &self.guard())
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        // approximation: table of pointers with load factor 3/4 and power of two size,
+        // every entry is separate node with hash, key, value and next pointer
+        let len = HashSet::len(self);
+        let table = (len * 4 / 3).next_power_of_two() * mem::size_of::<usize>();
+        table + len * (mem::size_of::<K>() + 3 * mem::size_of::<usize>())
+    }
+}
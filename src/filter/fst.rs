@@ -0,0 +1,66 @@
+use {
+    super::AccountsFilter,
+    fst::{Set, SetBuilder, Streamer},
+    solana_program::pubkey::Pubkey,
+    std::{collections::BTreeSet, mem},
+};
+
+/// Pubkeys in immutable `fst::Set` (finite state transducer), compact but rebuilt on every change:
+/// inserted Pubkeys are buffered and merged into the set by `finish`, removal rebuilds the set.
+#[derive(Clone, Default)]
+pub struct FstFilter {
+    set: Set<Vec<u8>>,
+    pending: BTreeSet<Pubkey>,
+}
+
+/// Set from sorted unique Pubkeys.
+fn from_sorted(pubkeys: impl IntoIterator<Item = Pubkey>) -> Set<Vec<u8>> {
+    Set::from_iter(pubkeys.into_iter().map(|pubkey| pubkey.to_bytes())).expect("sorted keys")
+}
+
+fn collect<S>(stream: S) -> Set<Vec<u8>>
+where
+    S: for<'a> Streamer<'a, Item = &'a [u8]>,
+{
+    let mut builder = SetBuilder::memory();
+    builder.extend_stream(stream).expect("sorted keys");
+    builder.into_set()
+}
+
+impl AccountsFilter for FstFilter {
+    fn insert(&mut self, pubkey: Pubkey) -> bool {
+        !self.set.contains(pubkey) && self.pending.insert(pubkey)
+    }
+
+    fn remove(&mut self, pubkey: &Pubkey) -> bool {
+        if self.pending.remove(pubkey) {
+            return true;
+        }
+        if !self.set.contains(pubkey) {
+            return false;
+        }
+        let removed = from_sorted([*pubkey]);
+        self.set = collect(self.set.op().add(&removed).difference());
+        true
+    }
+
+    fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.set.contains(pubkey) || self.pending.contains(pubkey)
+    }
+
+    fn len(&self) -> usize {
+        self.set.len() + self.pending.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.set.as_fst().size() + self.pending.len() * mem::size_of::<Pubkey>()
+    }
+
+    fn finish(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let pending = from_sorted(mem::take(&mut self.pending));
+        self.set = collect(self.set.op().add(&pending).union());
+    }
+}
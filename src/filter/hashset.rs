@@ -0,0 +1,40 @@
+use {
+    super::AccountsFilter,
+    std::{
+        collections::HashSet,
+        hash::{BuildHasher, Hash},
+        mem,
+    },
+};
+
+/// `HashSet` with `gxhash` hasher (requires `aes` and `sse2` target features).
+#[cfg(feature = "gxhash")]
+pub type GxHashSet = HashSet<solana_program::pubkey::Pubkey, gxhash::GxBuildHasher>;
+
+impl<K: Eq + Hash, S: BuildHasher> AccountsFilter<K> for HashSet<K, S> {
+    fn insert(&mut self, key: K) -> bool {
+        HashSet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &K) -> bool {
+        HashSet::remove(self, key)
+    }
+
+    fn contains(&self, key: &K) -> bool {
+        HashSet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        // hashbrown keeps load factor at 7/8 with power of two buckets,
+        // every bucket has value and one control byte
+        if self.capacity() == 0 {
+            return 0;
+        }
+        let buckets = (self.capacity() * 8 / 7).next_power_of_two();
+        buckets * (mem::size_of::<K>() + 1)
+    }
+}
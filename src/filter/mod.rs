@@ -0,0 +1,109 @@
+use {crate::keyset::KeySet, solana_program::pubkey::Pubkey};
+
+#[cfg(feature = "flurry")]
+mod flurry;
+#[cfg(feature = "fst")]
+mod fst;
+mod hashset;
+mod shared;
+
+pub use shared::{SharedFilter, SharedStrategy};
+
+#[cfg(feature = "fst")]
+pub use self::fst::FstFilter;
+#[cfg(feature = "gxhash")]
+pub use hashset::GxHashSet;
+
+/// Set of accounts used for filtering Pubkeys from the stream. Generic over fixed-size key,
+/// so the same filters can be used for transaction signatures (`Signature`, `[u8; 64]`)
+/// and program ids.
+pub trait AccountsFilter<K = Pubkey> {
+    /// Add key to the filter, returns `true` if it was not present.
+    fn insert(&mut self, key: K) -> bool;
+
+    /// Remove key from the filter, returns `true` if it was present.
+    fn remove(&mut self, key: &K) -> bool;
+
+    fn contains(&self, key: &K) -> bool;
+
+    /// Number of keys in the filter.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate heap memory used by the filter, in bytes.
+    fn memory_usage(&self) -> usize;
+
+    /// Called after bulk insert, before lookups. Immutable filters merge inserted keys here.
+    fn finish(&mut self) {}
+}
+
+/// Implements `AccountsFilter<$key>` for every `KeySet<$len>`, key is converted with `$to_bytes`.
+macro_rules! impl_keyset_filter {
+    ($key:ty, $len:literal, $to_bytes:expr) => {
+        impl<T: KeySet<$len>> AccountsFilter<$key> for T {
+            fn insert(&mut self, key: $key) -> bool {
+                KeySet::insert(self, $to_bytes(&key))
+            }
+
+            fn remove(&mut self, key: &$key) -> bool {
+                KeySet::remove(self, &$to_bytes(key))
+            }
+
+            fn contains(&self, key: &$key) -> bool {
+                KeySet::contains(self, &$to_bytes(key))
+            }
+
+            fn len(&self) -> usize {
+                KeySet::len(self)
+            }
+
+            fn memory_usage(&self) -> usize {
+                KeySet::memory_usage(self)
+            }
+
+            fn finish(&mut self) {
+                KeySet::finish(self)
+            }
+        }
+    };
+}
+
+impl_keyset_filter!(Pubkey, 32, |pubkey: &Pubkey| pubkey.to_bytes());
+#[cfg(feature = "runtime")]
+impl_keyset_filter!(
+    solana_sdk::signature::Signature,
+    64,
+    |signature: &solana_sdk::signature::Signature| -> [u8; 64] {
+        signature.as_ref().try_into().expect("64 bytes")
+    }
+);
+
+/// `keyset` structures over raw keys.
+impl<const N: usize, T: KeySet<N>> AccountsFilter<[u8; N]> for T {
+    fn insert(&mut self, key: [u8; N]) -> bool {
+        KeySet::insert(self, key)
+    }
+
+    fn remove(&mut self, key: &[u8; N]) -> bool {
+        KeySet::remove(self, key)
+    }
+
+    fn contains(&self, key: &[u8; N]) -> bool {
+        KeySet::contains(self, key)
+    }
+
+    fn len(&self) -> usize {
+        KeySet::len(self)
+    }
+
+    fn memory_usage(&self) -> usize {
+        KeySet::memory_usage(self)
+    }
+
+    fn finish(&mut self) {
+        KeySet::finish(self)
+    }
+}
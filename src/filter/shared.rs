@@ -0,0 +1,225 @@
+use {
+    super::AccountsFilter,
+    arc_swap::ArcSwap,
+    left_right::{Absorb, ReadHandle, ReadHandleFactory, WriteHandle},
+    solana_program::pubkey::Pubkey,
+    std::{
+        fmt,
+        str::FromStr,
+        sync::{Arc, Mutex, RwLock},
+    },
+    thread_local::ThreadLocal,
+};
+
+/// Filter shared between threads, looked up from many readers while one writer updates it.
+pub trait SharedFilter: Send + Sync {
+    /// Insert and remove Pubkeys as one update.
+    fn update(&self, insert: &[Pubkey], remove: &[Pubkey]);
+
+    /// Number of `pubkeys` in the filter, lock or guard is taken once for all of them.
+    fn contains_many(&self, pubkeys: &[Pubkey]) -> usize;
+
+    fn contains(&self, pubkey: &Pubkey) -> bool {
+        self.contains_many(std::slice::from_ref(pubkey)) > 0
+    }
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn apply<F: AccountsFilter>(filter: &mut F, insert: &[Pubkey], remove: &[Pubkey]) {
+    for pubkey in insert {
+        filter.insert(*pubkey);
+    }
+    for pubkey in remove {
+        filter.remove(pubkey);
+    }
+    filter.finish();
+}
+
+fn count<F: AccountsFilter>(filter: &F, pubkeys: &[Pubkey]) -> usize {
+    pubkeys
+        .iter()
+        .filter(|pubkey| filter.contains(pubkey))
+        .count()
+}
+
+/// Update strategy of `SharedFilter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedStrategy {
+    /// Readers and writer take one `Mutex`.
+    Mutex,
+    /// Readers share `RwLock`, writer takes it exclusively.
+    RwLock,
+    /// Writer clones filter and swaps it with `ArcSwap`, readers never wait.
+    ArcSwap,
+    /// Two copies of the filter with `left-right`, writer applies updates to both.
+    LeftRight,
+}
+
+impl SharedStrategy {
+    pub const ALL: [Self; 4] = [Self::Mutex, Self::RwLock, Self::ArcSwap, Self::LeftRight];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Mutex => "mutex",
+            Self::RwLock => "rwlock",
+            Self::ArcSwap => "arcswap",
+            Self::LeftRight => "left-right",
+        }
+    }
+
+    /// Wrap `filter` into shared handle with this strategy.
+    pub fn shared<F>(self, filter: F) -> Arc<dyn SharedFilter>
+    where
+        F: AccountsFilter + Clone + Send + Sync + 'static,
+    {
+        match self {
+            Self::Mutex => Arc::new(Mutex::new(filter)),
+            Self::RwLock => Arc::new(RwLock::new(filter)),
+            Self::ArcSwap => Arc::new(ArcSwap::from_pointee(filter)),
+            Self::LeftRight => Arc::new(LeftRightFilter::new(filter)),
+        }
+    }
+}
+
+impl fmt::Display for SharedStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SharedStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == s)
+            .ok_or_else(|| format!("unknown shared filter strategy: {}", s))
+    }
+}
+
+impl<F: AccountsFilter + Send> SharedFilter for Mutex<F> {
+    fn update(&self, insert: &[Pubkey], remove: &[Pubkey]) {
+        apply(&mut *self.lock().expect("poisoned"), insert, remove);
+    }
+
+    fn contains_many(&self, pubkeys: &[Pubkey]) -> usize {
+        count(&*self.lock().expect("poisoned"), pubkeys)
+    }
+
+    fn len(&self) -> usize {
+        self.lock().expect("poisoned").len()
+    }
+}
+
+impl<F: AccountsFilter + Send + Sync> SharedFilter for RwLock<F> {
+    fn update(&self, insert: &[Pubkey], remove: &[Pubkey]) {
+        apply(&mut *self.write().expect("poisoned"), insert, remove);
+    }
+
+    fn contains_many(&self, pubkeys: &[Pubkey]) -> usize {
+        count(&*self.read().expect("poisoned"), pubkeys)
+    }
+
+    fn len(&self) -> usize {
+        self.read().expect("poisoned").len()
+    }
+}
+
+impl<F: AccountsFilter + Clone + Send + Sync> SharedFilter for ArcSwap<F> {
+    fn update(&self, insert: &[Pubkey], remove: &[Pubkey]) {
+        self.rcu(|filter| {
+            let mut filter = F::clone(filter);
+            apply(&mut filter, insert, remove);
+            filter
+        });
+    }
+
+    fn contains_many(&self, pubkeys: &[Pubkey]) -> usize {
+        count(&**self.load(), pubkeys)
+    }
+
+    fn len(&self) -> usize {
+        self.load().len()
+    }
+}
+
+enum FilterOp {
+    Insert(Pubkey),
+    Remove(Pubkey),
+    Finish,
+}
+
+#[derive(Clone)]
+struct LeftRightInner<F>(F);
+
+impl<F: AccountsFilter + Clone> Absorb<FilterOp> for LeftRightInner<F> {
+    fn absorb_first(&mut self, operation: &mut FilterOp, _other: &Self) {
+        match operation {
+            FilterOp::Insert(pubkey) => {
+                self.0.insert(*pubkey);
+            }
+            FilterOp::Remove(pubkey) => {
+                self.0.remove(pubkey);
+            }
+            FilterOp::Finish => self.0.finish(),
+        }
+    }
+
+    fn sync_with(&mut self, first: &Self) {
+        self.0 = first.0.clone();
+    }
+}
+
+/// `left-right` filter: every reader thread takes handle from factory once and keeps it,
+/// writer appends operations and publishes them under `Mutex`.
+struct LeftRightFilter<F: AccountsFilter + Clone + Sync> {
+    writer: Mutex<WriteHandle<LeftRightInner<F>, FilterOp>>,
+    factory: ReadHandleFactory<LeftRightInner<F>>,
+    readers: ThreadLocal<ReadHandle<LeftRightInner<F>>>,
+}
+
+impl<F: AccountsFilter + Clone + Sync> LeftRightFilter<F> {
+    fn new(filter: F) -> Self {
+        let (writer, reader) = left_right::new_from_empty::<_, FilterOp>(LeftRightInner(filter));
+        Self {
+            writer: Mutex::new(writer),
+            factory: reader.factory(),
+            readers: ThreadLocal::new(),
+        }
+    }
+
+    /// Read handle of the current thread, factory takes a lock only for the first lookup.
+    fn reader(&self) -> &ReadHandle<LeftRightInner<F>> {
+        self.readers.get_or(|| self.factory.handle())
+    }
+}
+
+impl<F: AccountsFilter + Clone + Send + Sync> SharedFilter for LeftRightFilter<F> {
+    fn update(&self, insert: &[Pubkey], remove: &[Pubkey]) {
+        let mut writer = self.writer.lock().expect("poisoned");
+        writer.extend(
+            insert
+                .iter()
+                .map(|pubkey| FilterOp::Insert(*pubkey))
+                .chain(remove.iter().map(|pubkey| FilterOp::Remove(*pubkey)))
+                .chain([FilterOp::Finish]),
+        );
+        writer.publish();
+    }
+
+    fn contains_many(&self, pubkeys: &[Pubkey]) -> usize {
+        let guard = self.reader().enter();
+        guard.map_or(0, |filter| count(&filter.0, pubkeys))
+    }
+
+    fn len(&self) -> usize {
+        let guard = self.reader().enter();
+        guard.map_or(0, |filter| filter.0.len())
+    }
+}
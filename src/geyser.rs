@@ -0,0 +1,219 @@
+//! Accounts filters of geyser plugin config (Yellowstone schema: `account`, `owner`, `filters`)
+//! and composite filter built from it.
+
+use {
+    crate::{dataset::deserialize_pubkeys, Result},
+    serde::Deserialize,
+    solana_sdk::pubkey::Pubkey,
+    std::{
+        collections::{BTreeMap, HashSet},
+        convert::TryFrom,
+        fs::File,
+        io::BufReader,
+        path::Path,
+    },
+};
+
+/// Accounts filters of geyser plugin config, keyed by subscriber name.
+#[derive(Debug, Deserialize)]
+pub struct GeyserConfig {
+    pub accounts: BTreeMap<String, GeyserAccountsFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GeyserAccountsFilter {
+    #[serde(default, alias = "accounts", deserialize_with = "deserialize_pubkeys")]
+    pub account: Vec<Pubkey>,
+    #[serde(default, alias = "owners", deserialize_with = "deserialize_pubkeys")]
+    pub owner: Vec<Pubkey>,
+    #[serde(default, alias = "exclude", deserialize_with = "deserialize_pubkeys")]
+    pub account_exclude: Vec<Pubkey>,
+    /// Filters of account data, all of them should match.
+    #[serde(default)]
+    pub filters: Vec<GeyserDataFilter>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeyserDataFilter {
+    Memcmp(GeyserMemcmp),
+    Datasize(usize),
+}
+
+/// Bytes at offset of account data, in config as `bytes` (array), `base58` or `base64`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawMemcmp")]
+pub struct GeyserMemcmp {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawMemcmp {
+    offset: usize,
+    bytes: Option<Vec<u8>>,
+    base58: Option<String>,
+    base64: Option<String>,
+}
+
+impl TryFrom<RawMemcmp> for GeyserMemcmp {
+    type Error = String;
+
+    fn try_from(raw: RawMemcmp) -> Result<Self, Self::Error> {
+        let bytes = match (raw.bytes, raw.base58, raw.base64) {
+            (Some(bytes), None, None) => bytes,
+            (None, Some(data), None) => bs58::decode(data)
+                .into_vec()
+                .map_err(|error| format!("invalid memcmp base58: {}", error))?,
+            (None, None, Some(data)) => {
+                base64::decode(data).map_err(|error| format!("invalid memcmp base64: {}", error))?
+            }
+            _ => return Err("memcmp should have one of `bytes`, `base58` or `base64`".to_owned()),
+        };
+        Ok(Self {
+            offset: raw.offset,
+            bytes,
+        })
+    }
+}
+
+impl GeyserAccountsFilter {
+    /// Subscriber selects accounts only by listed `account` and `account_exclude`, without
+    /// owner and data filters.
+    pub fn is_account_list(&self) -> bool {
+        !self.account.is_empty() && self.owner.is_empty() && self.filters.is_empty()
+    }
+}
+
+impl GeyserDataFilter {
+    fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            Self::Memcmp(memcmp) => data
+                .get(memcmp.offset..)
+                .is_some_and(|data| data.starts_with(&memcmp.bytes)),
+            Self::Datasize(size) => data.len() == *size,
+        }
+    }
+}
+
+impl GeyserConfig {
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let config: Self = serde_yaml::from_reader(BufReader::new(file))?;
+        if config
+            .accounts
+            .values()
+            .any(|filter| !filter.owner.is_empty() || !filter.filters.is_empty())
+        {
+            tracing::warn!(
+                "geyser config: data filters never match, owner filters need enriched data"
+            );
+        }
+        Ok(config)
+    }
+
+    /// Number of account Pubkeys of all subscribers.
+    pub fn accounts_len(&self) -> usize {
+        self.accounts
+            .values()
+            .map(|filter| filter.account.len())
+            .sum()
+    }
+}
+
+/// Account update checked by `GeyserFilter`. Owner and data are optional because dataset
+/// has only Pubkeys (owners only if enriched), unknown owner or data does not match non-empty
+/// owner or data filters.
+#[derive(Debug, Clone, Copy)]
+pub struct GeyserAccount<'a> {
+    pub pubkey: &'a Pubkey,
+    pub owner: Option<&'a Pubkey>,
+    pub data: Option<&'a [u8]>,
+}
+
+impl<'a> From<&'a Pubkey> for GeyserAccount<'a> {
+    fn from(pubkey: &'a Pubkey) -> Self {
+        Self {
+            pubkey,
+            owner: None,
+            data: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SubscriberFilter {
+    name: String,
+    account: HashSet<Pubkey>,
+    owner: HashSet<Pubkey>,
+    exclude: HashSet<Pubkey>,
+    filters: Vec<GeyserDataFilter>,
+}
+
+impl SubscriberFilter {
+    /// Same as Yellowstone: empty `account` / `owner` match any account, all conditions should match.
+    fn matches(&self, account: &GeyserAccount) -> bool {
+        !self.exclude.contains(account.pubkey)
+            && (self.account.is_empty() || self.account.contains(account.pubkey))
+            && (self.owner.is_empty()
+                || account
+                    .owner
+                    .is_some_and(|owner| self.owner.contains(owner)))
+            && (self.filters.is_empty()
+                || account
+                    .data
+                    .is_some_and(|data| self.filters.iter().all(|filter| filter.matches(data))))
+    }
+}
+
+/// Composite filter of all subscribers of geyser config.
+#[derive(Debug)]
+pub struct GeyserFilter {
+    subscribers: Vec<SubscriberFilter>,
+}
+
+impl GeyserFilter {
+    pub fn new(config: &GeyserConfig) -> Self {
+        Self::with_subscribers(config, |_filter| true)
+    }
+
+    /// Filter of subscribers of `config` selected by `keep`.
+    pub fn with_subscribers(
+        config: &GeyserConfig,
+        keep: impl Fn(&GeyserAccountsFilter) -> bool,
+    ) -> Self {
+        let subscribers = config
+            .accounts
+            .iter()
+            .filter(|(_name, filter)| keep(filter))
+            .map(|(name, filter)| SubscriberFilter {
+                name: name.clone(),
+                account: filter.account.iter().cloned().collect(),
+                owner: filter.owner.iter().cloned().collect(),
+                exclude: filter.account_exclude.iter().cloned().collect(),
+                filters: filter.filters.clone(),
+            })
+            .collect();
+        Self { subscribers }
+    }
+
+    /// Names of subscribers interested in the account.
+    pub fn subscribers<'a>(
+        &'a self,
+        account: &'a GeyserAccount,
+    ) -> impl Iterator<Item = &'a str> + 'a {
+        self.subscribers
+            .iter()
+            .filter(move |subscriber| subscriber.matches(account))
+            .map(|subscriber| subscriber.name.as_str())
+    }
+
+    /// Number of subscribers interested in the account.
+    pub fn matches(&self, account: &GeyserAccount) -> usize {
+        self.subscribers
+            .iter()
+            .filter(|subscriber| subscriber.matches(account))
+            .count()
+    }
+}
@@ -0,0 +1,115 @@
+use {
+    super::proto::{
+        AccountsFilter, MemcmpData, SubscribeRequest, SubscribeRequestFilterAccounts,
+        SubscribeRequestFilterAccountsFilter, SubscribeRequestFilterAccountsFilterMemcmp,
+        SubscribeUpdate, SUBSCRIBE_PATH,
+    },
+    crate::{
+        geyser::{GeyserAccountsFilter, GeyserConfig, GeyserDataFilter},
+        Error, Result,
+    },
+    futures::stream::{self, StreamExt},
+    solana_sdk::pubkey::Pubkey,
+    tonic::{
+        client::Grpc,
+        codec::ProstCodec,
+        codegen::http::uri::PathAndQuery,
+        metadata::AsciiMetadataValue,
+        transport::{Channel, ClientTlsConfig, Endpoint},
+        Request, Streaming,
+    },
+};
+
+fn to_strings(pubkeys: &[Pubkey]) -> Vec<String> {
+    pubkeys.iter().map(ToString::to_string).collect()
+}
+
+impl From<&GeyserDataFilter> for SubscribeRequestFilterAccountsFilter {
+    fn from(filter: &GeyserDataFilter) -> Self {
+        Self {
+            filter: Some(match filter {
+                GeyserDataFilter::Memcmp(memcmp) => {
+                    AccountsFilter::Memcmp(SubscribeRequestFilterAccountsFilterMemcmp {
+                        offset: memcmp.offset as u64,
+                        data: Some(MemcmpData::Bytes(memcmp.bytes.clone())),
+                    })
+                }
+                GeyserDataFilter::Datasize(size) => AccountsFilter::Datasize(*size as u64),
+            }),
+        }
+    }
+}
+
+impl From<&GeyserAccountsFilter> for SubscribeRequestFilterAccounts {
+    /// `account_exclude` is not supported by Yellowstone and ignored.
+    fn from(filter: &GeyserAccountsFilter) -> Self {
+        Self {
+            account: to_strings(&filter.account),
+            owner: to_strings(&filter.owner),
+            filters: filter.filters.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&GeyserConfig> for SubscribeRequest {
+    fn from(config: &GeyserConfig) -> Self {
+        Self {
+            accounts: config
+                .accounts
+                .iter()
+                .map(|(name, filter)| (name.clone(), filter.into()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Client of `Geyser.Subscribe` for replay server or Yellowstone endpoint (TLS for `https`).
+#[derive(Debug, Clone)]
+pub struct GeyserClient {
+    grpc: Grpc<Channel>,
+    x_token: Option<AsciiMetadataValue>,
+}
+
+impl GeyserClient {
+    pub async fn connect(endpoint: &str, x_token: Option<&str>) -> Result<Self> {
+        let mut builder = Endpoint::from_shared(endpoint.to_owned())
+            .map_err(|error| Error::GrpcEndpoint(format!("{}: {}", endpoint, error)))?;
+        if endpoint.starts_with("https") {
+            builder = builder.tls_config(ClientTlsConfig::new())?;
+        }
+        let x_token = x_token
+            .map(|x_token| {
+                x_token
+                    .parse()
+                    .map_err(|error| Error::GrpcEndpoint(format!("invalid x-token: {}", error)))
+            })
+            .transpose()?;
+        Ok(Self {
+            grpc: Grpc::new(builder.connect().await?),
+            x_token,
+        })
+    }
+
+    /// Open subscription with filters of `request`, request stream is kept open until
+    /// updates are dropped.
+    pub async fn subscribe(
+        &mut self,
+        request: SubscribeRequest,
+    ) -> Result<Streaming<SubscribeUpdate>> {
+        self.grpc.ready().await?;
+        let mut request = Request::new(stream::iter([request]).chain(stream::pending()));
+        if let Some(x_token) = &self.x_token {
+            request.metadata_mut().insert("x-token", x_token.clone());
+        }
+        let response = self
+            .grpc
+            .streaming(
+                request,
+                PathAndQuery::from_static(SUBSCRIBE_PATH),
+                ProstCodec::default(),
+            )
+            .await?;
+        Ok(response.into_inner())
+    }
+}
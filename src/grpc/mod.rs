@@ -0,0 +1,11 @@
+//! Yellowstone compatible gRPC (`geyser.proto` subset) for system benchmarks: replay of the
+//! dataset as geyser stream and client for subscribers.
+
+mod client;
+pub mod proto;
+mod replay;
+
+pub use {
+    client::GeyserClient,
+    replay::{ReplayConfig, ReplayServer, ReplaySpeed, ReplayStats},
+};
@@ -0,0 +1,161 @@
+//! Messages of Yellowstone `geyser.proto` used by replay, with the same field tags.
+//! Fields and update kinds which dataset can not fill (account data, transactions, blocks)
+//! are omitted, clients skip them as unknown.
+
+use {
+    prost::Oneof,
+    std::{
+        collections::HashMap,
+        time::{SystemTime, UNIX_EPOCH},
+    },
+};
+
+/// `Geyser` service, same as Yellowstone.
+pub const SERVICE_NAME: &str = "geyser.Geyser";
+pub const SUBSCRIBE_PATH: &str = "/geyser.Geyser/Subscribe";
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequest {
+    #[prost(map = "string, message", tag = "1")]
+    pub accounts: HashMap<String, SubscribeRequestFilterAccounts>,
+    #[prost(map = "string, message", tag = "2")]
+    pub slots: HashMap<String, SubscribeRequestFilterSlots>,
+    #[prost(enumeration = "CommitmentLevel", optional, tag = "6")]
+    pub commitment: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterAccounts {
+    #[prost(string, repeated, tag = "2")]
+    pub account: Vec<String>,
+    #[prost(string, repeated, tag = "3")]
+    pub owner: Vec<String>,
+    #[prost(message, repeated, tag = "4")]
+    pub filters: Vec<SubscribeRequestFilterAccountsFilter>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterAccountsFilter {
+    #[prost(oneof = "AccountsFilter", tags = "1, 2")]
+    pub filter: Option<AccountsFilter>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum AccountsFilter {
+    #[prost(message, tag = "1")]
+    Memcmp(SubscribeRequestFilterAccountsFilterMemcmp),
+    #[prost(uint64, tag = "2")]
+    Datasize(u64),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterAccountsFilterMemcmp {
+    #[prost(uint64, tag = "1")]
+    pub offset: u64,
+    #[prost(oneof = "MemcmpData", tags = "2, 3, 4")]
+    pub data: Option<MemcmpData>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum MemcmpData {
+    #[prost(bytes, tag = "2")]
+    Bytes(Vec<u8>),
+    #[prost(string, tag = "3")]
+    Base58(String),
+    #[prost(string, tag = "4")]
+    Base64(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeRequestFilterSlots {
+    #[prost(bool, optional, tag = "1")]
+    pub filter_by_commitment: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdate {
+    /// Names of subscriber filters matched by the update.
+    #[prost(string, repeated, tag = "1")]
+    pub filters: Vec<String>,
+    #[prost(oneof = "UpdateOneof", tags = "2, 3")]
+    pub update_oneof: Option<UpdateOneof>,
+    /// Time when update was sent by the server.
+    #[prost(message, optional, tag = "11")]
+    pub created_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum UpdateOneof {
+    #[prost(message, tag = "2")]
+    Account(SubscribeUpdateAccount),
+    #[prost(message, tag = "3")]
+    Slot(SubscribeUpdateSlot),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateAccount {
+    #[prost(message, optional, tag = "1")]
+    pub account: Option<SubscribeUpdateAccountInfo>,
+    #[prost(uint64, tag = "2")]
+    pub slot: u64,
+    #[prost(bool, tag = "3")]
+    pub is_startup: bool,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateAccountInfo {
+    #[prost(bytes, tag = "1")]
+    pub pubkey: Vec<u8>,
+    #[prost(uint64, tag = "2")]
+    pub lamports: u64,
+    #[prost(bytes, tag = "3")]
+    pub owner: Vec<u8>,
+    #[prost(bool, tag = "4")]
+    pub executable: bool,
+    #[prost(uint64, tag = "5")]
+    pub rent_epoch: u64,
+    #[prost(bytes, tag = "6")]
+    pub data: Vec<u8>,
+    /// Replay sets it to sequence number of the update in the subscription.
+    #[prost(uint64, tag = "7")]
+    pub write_version: u64,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SubscribeUpdateSlot {
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    #[prost(uint64, optional, tag = "2")]
+    pub parent: Option<u64>,
+    #[prost(enumeration = "CommitmentLevel", tag = "3")]
+    pub status: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum CommitmentLevel {
+    Processed = 0,
+    Confirmed = 1,
+    Finalized = 2,
+}
+
+/// `google.protobuf.Timestamp`.
+#[derive(Clone, Copy, PartialEq, Eq, prost::Message)]
+pub struct Timestamp {
+    #[prost(int64, tag = "1")]
+    pub seconds: i64,
+    #[prost(int32, tag = "2")]
+    pub nanos: i32,
+}
+
+impl Timestamp {
+    pub fn now() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self {
+            seconds: now.as_secs() as i64,
+            nanos: now.subsec_nanos() as i32,
+        }
+    }
+}
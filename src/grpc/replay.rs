@@ -0,0 +1,439 @@
+use {
+    super::proto::{
+        AccountsFilter, CommitmentLevel, MemcmpData, SubscribeRequest,
+        SubscribeRequestFilterAccounts, SubscribeRequestFilterAccountsFilter, SubscribeUpdate,
+        SubscribeUpdateAccount, SubscribeUpdateAccountInfo, SubscribeUpdateSlot, Timestamp,
+        UpdateOneof, SERVICE_NAME, SUBSCRIBE_PATH,
+    },
+    crate::{
+        dataset::Block,
+        geyser::{
+            GeyserAccount, GeyserAccountsFilter, GeyserConfig, GeyserDataFilter, GeyserFilter,
+            GeyserMemcmp,
+        },
+        Dataset, Result,
+    },
+    futures::stream,
+    solana_sdk::{
+        clock::{Slot, DEFAULT_MS_PER_SLOT},
+        pubkey::Pubkey,
+    },
+    std::{
+        convert::{Infallible, TryFrom},
+        fmt,
+        net::SocketAddr,
+        str::FromStr,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, RwLock,
+        },
+        time::Duration,
+    },
+    tokio::{
+        sync::mpsc::{self, error::TrySendError},
+        time::{sleep_until, Instant},
+    },
+    tonic::{
+        body::BoxBody,
+        codec::ProstCodec,
+        codegen::{empty_body, http, Body, BoxFuture, BoxStream, Context, Poll, Service, StdError},
+        server::{Grpc, NamedService},
+        transport::Server,
+        Request, Response, Status, Streaming,
+    },
+};
+
+/// Speed of replay relative to slot time (`DEFAULT_MS_PER_SLOT`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Multiplier of slot time, `1x` is real time.
+    Realtime(f64),
+    /// Blocks are sent as fast as subscriber reads them.
+    Max,
+}
+
+impl fmt::Display for ReplaySpeed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Realtime(speed) => write!(f, "{}x", speed),
+            Self::Max => f.write_str("max"),
+        }
+    }
+}
+
+impl FromStr for ReplaySpeed {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "max" {
+            return Ok(Self::Max);
+        }
+        match s.strip_suffix('x').unwrap_or(s).parse::<f64>() {
+            Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(Self::Realtime(speed)),
+            _ => Err(format!(
+                "invalid replay speed: {}, expected `1x`, `10x` or `max`",
+                s
+            )),
+        }
+    }
+}
+
+/// Parameters of `ReplayServer`.
+#[derive(Debug, Clone, Copy)]
+pub struct ReplayConfig {
+    pub speed: ReplaySpeed,
+    /// Updates buffered for every subscriber, with `Realtime` speed updates are dropped when
+    /// the buffer is full (gap in `write_version`), with `Max` replay waits for subscriber.
+    pub channel_capacity: usize,
+}
+
+impl Default for ReplayConfig {
+    fn default() -> Self {
+        Self {
+            speed: ReplaySpeed::Realtime(1.0),
+            channel_capacity: 100_000,
+        }
+    }
+}
+
+/// Counters of all subscriptions of `ReplayServer`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReplayStats {
+    pub subscriptions: u64,
+    pub sent: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    subscriptions: AtomicU64,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+}
+
+type UpdateSender = mpsc::Sender<Result<SubscribeUpdate, Status>>;
+
+fn parse_pubkeys(pubkeys: &[String]) -> Result<Vec<Pubkey>, String> {
+    pubkeys
+        .iter()
+        .map(|pubkey| {
+            pubkey
+                .parse()
+                .map_err(|error| format!("invalid pubkey {:?}: {}", pubkey, error))
+        })
+        .collect()
+}
+
+fn parse_data_filter(
+    filter: &SubscribeRequestFilterAccountsFilter,
+) -> Result<GeyserDataFilter, String> {
+    match &filter.filter {
+        Some(AccountsFilter::Memcmp(memcmp)) => {
+            let bytes = match &memcmp.data {
+                Some(MemcmpData::Bytes(bytes)) => bytes.clone(),
+                Some(MemcmpData::Base58(data)) => bs58::decode(data)
+                    .into_vec()
+                    .map_err(|error| format!("invalid memcmp base58: {}", error))?,
+                Some(MemcmpData::Base64(data)) => base64::decode(data)
+                    .map_err(|error| format!("invalid memcmp base64: {}", error))?,
+                None => return Err("memcmp without data".to_owned()),
+            };
+            Ok(GeyserDataFilter::Memcmp(GeyserMemcmp {
+                offset: memcmp.offset as usize,
+                bytes,
+            }))
+        }
+        Some(AccountsFilter::Datasize(size)) => Ok(GeyserDataFilter::Datasize(*size as usize)),
+        None => Err("empty accounts filter".to_owned()),
+    }
+}
+
+impl TryFrom<&SubscribeRequestFilterAccounts> for GeyserAccountsFilter {
+    type Error = String;
+
+    fn try_from(filter: &SubscribeRequestFilterAccounts) -> Result<Self, Self::Error> {
+        Ok(Self {
+            account: parse_pubkeys(&filter.account)?,
+            owner: parse_pubkeys(&filter.owner)?,
+            account_exclude: vec![],
+            filters: filter
+                .filters
+                .iter()
+                .map(parse_data_filter)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Filters of one subscription, replaced by every next `SubscribeRequest` of the stream.
+struct Subscription {
+    accounts: GeyserFilter,
+    /// Names of slots filters.
+    slots: Vec<String>,
+    commitment: CommitmentLevel,
+}
+
+impl TryFrom<&SubscribeRequest> for Subscription {
+    type Error = String;
+
+    fn try_from(request: &SubscribeRequest) -> Result<Self, Self::Error> {
+        let config = GeyserConfig {
+            accounts: request
+                .accounts
+                .iter()
+                .map(|(name, filter)| Ok((name.clone(), filter.try_into()?)))
+                .collect::<Result<_, String>>()?,
+        };
+        let commitment = match request.commitment {
+            Some(commitment) => CommitmentLevel::from_i32(commitment)
+                .ok_or_else(|| format!("invalid commitment: {}", commitment))?,
+            None => CommitmentLevel::Processed,
+        };
+        Ok(Self {
+            accounts: GeyserFilter::new(&config),
+            slots: request.slots.keys().cloned().collect(),
+            commitment,
+        })
+    }
+}
+
+impl Subscription {
+    /// Account updates of matched Pubkeys of the block (with owner if dataset is enriched) and
+    /// slot update, `write_version` is incremented for every account update.
+    fn updates(
+        &self,
+        dataset: &Dataset,
+        slot: Slot,
+        block: &Block,
+        write_version: &mut u64,
+    ) -> Vec<SubscribeUpdate> {
+        let created_at = Some(Timestamp::now());
+        let mut updates = block
+            .pubkeys
+            .iter()
+            .filter_map(|pubkey| {
+                let owner = dataset.accounts.get(pubkey);
+                let account = GeyserAccount {
+                    pubkey,
+                    owner: owner.map(|owner| &owner.owner),
+                    data: None,
+                };
+                let filters = self
+                    .accounts
+                    .subscribers(&account)
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>();
+                if filters.is_empty() {
+                    return None;
+                }
+                *write_version += 1;
+                Some(SubscribeUpdate {
+                    filters,
+                    update_oneof: Some(UpdateOneof::Account(SubscribeUpdateAccount {
+                        account: Some(SubscribeUpdateAccountInfo {
+                            pubkey: pubkey.to_bytes().to_vec(),
+                            owner: owner
+                                .map(|owner| owner.owner.to_bytes().to_vec())
+                                .unwrap_or_default(),
+                            executable: owner.is_some_and(|owner| owner.executable),
+                            write_version: *write_version,
+                            ..Default::default()
+                        }),
+                        slot,
+                        is_startup: false,
+                    })),
+                    created_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        if !self.slots.is_empty() {
+            updates.push(SubscribeUpdate {
+                filters: self.slots.clone(),
+                update_oneof: Some(UpdateOneof::Slot(SubscribeUpdateSlot {
+                    slot,
+                    parent: None,
+                    status: self.commitment as i32,
+                })),
+                created_at,
+            });
+        }
+        updates
+    }
+}
+
+/// Yellowstone compatible gRPC server (`Geyser.Subscribe`) replaying blocks of the dataset:
+/// every Pubkey of the block is sent as account update (without data, with owner only if
+/// dataset is enriched) to subscriber with matching filters, followed by slot update if slots
+/// are subscribed. Every subscription replays the dataset from the first block.
+#[derive(Debug, Clone)]
+pub struct ReplayServer {
+    dataset: Arc<Dataset>,
+    config: ReplayConfig,
+    counters: Arc<Counters>,
+}
+
+impl ReplayServer {
+    pub fn new(dataset: Arc<Dataset>, config: ReplayConfig) -> Self {
+        Self {
+            dataset,
+            config,
+            counters: Arc::default(),
+        }
+    }
+
+    pub fn stats(&self) -> ReplayStats {
+        ReplayStats {
+            subscriptions: self.counters.subscriptions.load(Ordering::Relaxed),
+            sent: self.counters.sent.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        Server::builder()
+            .add_service(self)
+            .serve(addr)
+            .await
+            .map_err(Into::into)
+    }
+
+    async fn subscribe(
+        self,
+        request: Request<Streaming<SubscribeRequest>>,
+    ) -> Result<Response<BoxStream<SubscribeUpdate>>, Status> {
+        let mut requests = request.into_inner();
+        let request = requests
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("subscribe request is expected"))?;
+        let subscription = Subscription::try_from(&request).map_err(Status::invalid_argument)?;
+        let subscription = Arc::new(RwLock::new(Ok(subscription)));
+        self.counters.subscriptions.fetch_add(1, Ordering::Relaxed);
+
+        tokio::spawn({
+            let subscription = Arc::clone(&subscription);
+            async move {
+                while let Ok(Some(request)) = requests.message().await {
+                    *subscription.write().expect("poisoned") = Subscription::try_from(&request);
+                }
+            }
+        });
+
+        let (tx, rx) = mpsc::channel(self.config.channel_capacity);
+        tokio::spawn(self.replay(subscription, tx));
+        let updates = stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|update| (update, rx))
+        });
+        Ok(Response::new(Box::pin(updates)))
+    }
+
+    async fn replay(
+        self,
+        subscription: Arc<RwLock<Result<Subscription, String>>>,
+        tx: UpdateSender,
+    ) {
+        let first_slot = match self.dataset.blocks.keys().next() {
+            Some(slot) => *slot,
+            None => return,
+        };
+        let start = Instant::now();
+        let mut write_version = 0;
+        for (slot, block) in self.dataset.blocks.iter() {
+            if let ReplaySpeed::Realtime(speed) = self.config.speed {
+                let offset = Duration::from_millis((slot - first_slot) * DEFAULT_MS_PER_SLOT);
+                sleep_until(start + offset.div_f64(speed)).await;
+            }
+            let updates = match &*subscription.read().expect("poisoned") {
+                Ok(subscription) => {
+                    Ok(subscription.updates(&self.dataset, *slot, block, &mut write_version))
+                }
+                Err(error) => Err(Status::invalid_argument(error)),
+            };
+            let updates = match updates {
+                Ok(updates) => updates,
+                Err(status) => {
+                    let _ = tx.send(Err(status)).await;
+                    return;
+                }
+            };
+            for update in updates {
+                if !self.send(&tx, update).await {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Send update to subscriber, returns `false` if subscriber is disconnected.
+    async fn send(&self, tx: &UpdateSender, update: SubscribeUpdate) -> bool {
+        let sent = match self.config.speed {
+            ReplaySpeed::Max => tx.send(Ok(update)).await.is_ok(),
+            ReplaySpeed::Realtime(_) => match tx.try_send(Ok(update)) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+                    return true;
+                }
+                Err(TrySendError::Closed(_)) => false,
+            },
+        };
+        if sent {
+            self.counters.sent.fetch_add(1, Ordering::Relaxed);
+        }
+        sent
+    }
+}
+
+/// `Geyser.Subscribe` handler for `Grpc::streaming`.
+struct SubscribeService(ReplayServer);
+
+impl Service<Request<Streaming<SubscribeRequest>>> for SubscribeService {
+    type Response = Response<BoxStream<SubscribeUpdate>>;
+    type Error = Status;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Streaming<SubscribeRequest>>) -> Self::Future {
+        Box::pin(self.0.clone().subscribe(request))
+    }
+}
+
+impl<B> Service<http::Request<B>> for ReplayServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        if request.uri().path() != SUBSCRIBE_PATH {
+            // other methods of `Geyser` are not implemented
+            return Box::pin(async {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", "12")
+                    .header("content-type", "application/grpc")
+                    .body(empty_body())
+                    .expect("valid response"))
+            });
+        }
+        let service = SubscribeService(self.clone());
+        Box::pin(async move {
+            let mut grpc = Grpc::new(ProstCodec::default());
+            Ok(grpc.streaming(service, request).await)
+        })
+    }
+}
+
+impl NamedService for ReplayServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
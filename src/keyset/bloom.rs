@@ -0,0 +1,75 @@
+use {
+    super::{hash, Key, KeySet},
+    alloc::{vec, vec::Vec},
+    core::mem,
+};
+
+/// Bloom filter with `bits_per_key` bits for expected number of keys (10 bits give about 1%
+/// false positives), for keys of any size. Keys can not be removed, `remove` always returns `false`.
+/// `len` is the number of inserts: repeated key can not be told from a false positive, so it is
+/// counted again, and a new key is counted even if all its bits were already set.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    hashes: u32,
+    len: usize,
+}
+
+impl BloomFilter {
+    pub const DEFAULT_BITS_PER_KEY: usize = 10;
+
+    pub fn new(capacity: usize) -> Self {
+        Self::with_bits_per_key(capacity, Self::DEFAULT_BITS_PER_KEY)
+    }
+
+    pub fn with_bits_per_key(capacity: usize, bits_per_key: usize) -> Self {
+        let words = (capacity.max(1) * bits_per_key.max(1)).div_ceil(64);
+        Self {
+            bits: vec![0; words],
+            // optimal number of hashes is `bits_per_key * ln(2)`
+            hashes: ((bits_per_key * 693 + 500) / 1000).max(1) as u32,
+            len: 0,
+        }
+    }
+}
+
+/// Bit indexes of the key in `words` words, double hashing with two halves of one hash.
+fn indexes(key: &[u8], hashes: u32, words: usize) -> impl Iterator<Item = (usize, u64)> {
+    let hash = hash(key, 0);
+    let delta = hash.rotate_left(32) | 1;
+    let bits = words as u64 * 64;
+    (0..hashes as u64).map(move |i| {
+        let index = hash.wrapping_add(i.wrapping_mul(delta)) % bits;
+        ((index / 64) as usize, 1 << (index % 64))
+    })
+}
+
+impl<const N: usize> KeySet<N> for BloomFilter {
+    /// Returns `true` if any bit was set, so inserted key was not present.
+    fn insert(&mut self, key: Key<N>) -> bool {
+        let mut inserted = false;
+        for (word, bit) in indexes(&key, self.hashes, self.bits.len()) {
+            inserted |= self.bits[word] & bit == 0;
+            self.bits[word] |= bit;
+        }
+        self.len += 1;
+        inserted
+    }
+
+    fn remove(&mut self, _key: &Key<N>) -> bool {
+        false
+    }
+
+    fn contains(&self, key: &Key<N>) -> bool {
+        indexes(key, self.hashes, self.bits.len()).all(|(word, bit)| self.bits[word] & bit != 0)
+    }
+
+    /// Number of inserts, including repeated keys.
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.bits.len() * mem::size_of::<u64>()
+    }
+}
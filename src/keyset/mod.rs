@@ -0,0 +1,97 @@
+//! Pure key set data structures over raw fixed-size keys (32-byte account keys by default,
+//! 64-byte signatures), only `core` and `alloc` are used, so they build without `std`
+//! (sBPF tooling, embedded hosts). With `std` feature every `KeySet` is also `AccountsFilter`
+//! over `Pubkey` (and `Signature` with `runtime`) or byte arrays.
+
+mod bloom;
+mod prefix;
+mod sorted;
+mod xor;
+
+pub use {bloom::BloomFilter, prefix::PrefixSet, sorted::SortedKeys, xor::XorFilter};
+
+/// Key of `N` bytes, `Pubkey` bytes by default.
+pub type Key<const N: usize = 32> = [u8; N];
+
+/// Set of keys, same contract as `AccountsFilter` without `std`.
+pub trait KeySet<const N: usize = 32> {
+    /// Add key to the set, returns `true` if it was not present.
+    fn insert(&mut self, key: Key<N>) -> bool;
+
+    /// Remove key from the set, returns `true` if it was present.
+    fn remove(&mut self, key: &Key<N>) -> bool;
+
+    fn contains(&self, key: &Key<N>) -> bool;
+
+    /// Number of keys in the set.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate heap memory used by the set, in bytes.
+    fn memory_usage(&self) -> usize;
+
+    /// Called after bulk insert, before lookups.
+    fn finish(&mut self) {}
+}
+
+/// Inherent `len`, `is_empty`, `memory_usage` and `finish` of `KeySet`, so they are not
+/// ambiguous with `AccountsFilter` over several key types implemented for every set. Sets
+/// without key size parameter are sets of keys of any size, their methods do not depend on it.
+macro_rules! impl_inherent {
+    ($set:ident<N>) => {
+        impl<const N: usize> $set<N> {
+            impl_inherent!(@methods N);
+        }
+    };
+    ($set:ident) => {
+        impl $set {
+            impl_inherent!(@methods 32);
+        }
+    };
+    (@methods $n:tt) => {
+        /// Number of keys in the set, see `KeySet::len`.
+        pub fn len(&self) -> usize {
+            KeySet::<$n>::len(self)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            KeySet::<$n>::is_empty(self)
+        }
+
+        /// Approximate heap memory used by the set, in bytes.
+        pub fn memory_usage(&self) -> usize {
+            KeySet::<$n>::memory_usage(self)
+        }
+
+        /// Called after bulk insert, before lookups.
+        pub fn finish(&mut self) {
+            KeySet::<$n>::finish(self)
+        }
+    };
+}
+
+impl_inherent!(BloomFilter);
+impl_inherent!(PrefixSet<N>);
+impl_inherent!(SortedKeys<N>);
+impl_inherent!(XorFilter<N>);
+
+/// `splitmix64` finalizer.
+const fn mix(mut x: u64) -> u64 {
+    x ^= x >> 30;
+    x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x ^= x >> 27;
+    x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Seeded 64-bit hash of the key, keys are not always random (program ids, vanity keys).
+fn hash(key: &[u8], seed: u64) -> u64 {
+    key.chunks(8).fold(seed, |hash, chunk| {
+        let mut word = [0; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        mix(hash ^ u64::from_le_bytes(word))
+    })
+}
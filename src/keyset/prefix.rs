@@ -0,0 +1,65 @@
+use {
+    super::{Key, KeySet},
+    alloc::{collections::BTreeSet, vec::Vec},
+    core::{marker::PhantomData, mem},
+};
+
+/// Sorted array of 8-byte key prefixes, a quarter of `SortedKeys` memory for account keys
+/// (an eighth for signatures), shorter keys are padded with zeros. Keys with the same
+/// prefix are indistinguishable, so lookups have false positives with probability about
+/// `len / 2^64` and keys sharing a prefix are stored once.
+#[derive(Debug, Clone, Default)]
+pub struct PrefixSet<const N: usize = 32> {
+    prefixes: Vec<u64>,
+    pending: BTreeSet<u64>,
+    key: PhantomData<Key<N>>,
+}
+
+fn prefix(key: &[u8]) -> u64 {
+    let mut prefix = [0; 8];
+    let len = key.len().min(8);
+    prefix[..len].copy_from_slice(&key[..len]);
+    u64::from_be_bytes(prefix)
+}
+
+impl<const N: usize> KeySet<N> for PrefixSet<N> {
+    fn insert(&mut self, key: Key<N>) -> bool {
+        let prefix = prefix(&key);
+        self.prefixes.binary_search(&prefix).is_err() && self.pending.insert(prefix)
+    }
+
+    fn remove(&mut self, key: &Key<N>) -> bool {
+        let prefix = prefix(key);
+        if self.pending.remove(&prefix) {
+            return true;
+        }
+        match self.prefixes.binary_search(&prefix) {
+            Ok(index) => {
+                self.prefixes.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn contains(&self, key: &Key<N>) -> bool {
+        let prefix = prefix(key);
+        self.prefixes.binary_search(&prefix).is_ok() || self.pending.contains(&prefix)
+    }
+
+    fn len(&self) -> usize {
+        self.prefixes.len() + self.pending.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        (self.prefixes.capacity() + self.pending.len()) * mem::size_of::<u64>()
+    }
+
+    fn finish(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        self.prefixes.extend(mem::take(&mut self.pending));
+        self.prefixes.sort_unstable();
+    }
+}
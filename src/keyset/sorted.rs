@@ -0,0 +1,81 @@
+use {
+    super::{Key, KeySet},
+    alloc::{collections::BTreeSet, vec::Vec},
+    core::mem,
+};
+
+/// Sorted array of keys with binary search lookups: inserted keys are buffered and merged
+/// by `finish`, removal shifts the array.
+#[derive(Debug, Clone, Default)]
+pub struct SortedKeys<const N: usize = 32> {
+    keys: Vec<Key<N>>,
+    pending: BTreeSet<Key<N>>,
+}
+
+impl<const N: usize> SortedKeys<N> {
+    /// Set from keys in any order, duplicates are removed.
+    pub fn from_keys(keys: impl IntoIterator<Item = Key<N>>) -> Self {
+        let mut keys = keys.into_iter().collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        Self {
+            keys,
+            pending: BTreeSet::new(),
+        }
+    }
+
+    /// Sorted keys merged by the last `finish`.
+    pub fn as_slice(&self) -> &[Key<N>] {
+        &self.keys
+    }
+}
+
+impl<const N: usize> KeySet<N> for SortedKeys<N> {
+    fn insert(&mut self, key: Key<N>) -> bool {
+        self.keys.binary_search(&key).is_err() && self.pending.insert(key)
+    }
+
+    fn remove(&mut self, key: &Key<N>) -> bool {
+        if self.pending.remove(key) {
+            return true;
+        }
+        match self.keys.binary_search(key) {
+            Ok(index) => {
+                self.keys.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn contains(&self, key: &Key<N>) -> bool {
+        self.keys.binary_search(key).is_ok() || self.pending.contains(key)
+    }
+
+    fn len(&self) -> usize {
+        self.keys.len() + self.pending.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        (self.keys.capacity() + self.pending.len()) * mem::size_of::<Key<N>>()
+    }
+
+    fn finish(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let keys = mem::take(&mut self.keys);
+        let pending = mem::take(&mut self.pending);
+        self.keys.reserve_exact(keys.len() + pending.len());
+        let (mut keys, mut pending) = (keys.into_iter().peekable(), pending.into_iter().peekable());
+        loop {
+            let key = match (keys.peek(), pending.peek()) {
+                (Some(key), Some(pending_key)) if key < pending_key => keys.next(),
+                (Some(_), Some(_)) | (None, Some(_)) => pending.next(),
+                (Some(_), None) => keys.next(),
+                (None, None) => break,
+            };
+            self.keys.extend(key);
+        }
+    }
+}
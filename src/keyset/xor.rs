@@ -0,0 +1,137 @@
+use {
+    super::{hash, mix, Key, KeySet},
+    alloc::{collections::BTreeSet, vec, vec::Vec},
+    core::mem,
+};
+
+/// Static xor filter with 8-bit fingerprints (about 9.8 bits per key, 0.4% false positives).
+/// Inserted keys are buffered and built into fingerprints by `finish`, then dropped: the filter
+/// can be built once, later inserts and removals are not supported and return `false`.
+#[derive(Debug, Clone, Default)]
+pub struct XorFilter<const N: usize = 32> {
+    seed: u64,
+    block_len: u32,
+    fingerprints: Vec<u8>,
+    len: usize,
+    pending: BTreeSet<Key<N>>,
+}
+
+/// Fingerprint and positions in every block of hashed key.
+fn positions(hash: u64, block_len: u32) -> (u8, [usize; 3]) {
+    let reduce = |hash: u64| ((hash as u32 as u64 * block_len as u64) >> 32) as usize;
+    let block_len = block_len as usize;
+    (
+        (hash ^ (hash >> 32)) as u8,
+        [
+            reduce(hash),
+            reduce(hash.rotate_left(21)) + block_len,
+            reduce(hash.rotate_left(42)) + 2 * block_len,
+        ],
+    )
+}
+
+impl<const N: usize> XorFilter<N> {
+    /// Filter from keys in any order, duplicates are removed.
+    pub fn from_keys(keys: impl IntoIterator<Item = Key<N>>) -> Self {
+        let mut filter = Self {
+            pending: keys.into_iter().collect(),
+            ..Self::default()
+        };
+        filter.finish();
+        filter
+    }
+
+    /// Is filter built by `finish`, so no more keys can be added.
+    pub fn is_built(&self) -> bool {
+        !self.fingerprints.is_empty()
+    }
+
+    /// Peel hashes of the keys, returns hashes with slot assigned to them in peel order,
+    /// or `None` if the hypergraph has a cycle and another seed should be tried.
+    fn peel(hashes: &[u64], block_len: u32) -> Option<Vec<(u64, usize)>> {
+        let mut xormask = vec![0u64; 3 * block_len as usize];
+        let mut count = vec![0u32; 3 * block_len as usize];
+        for &hash in hashes {
+            for position in positions(hash, block_len).1 {
+                xormask[position] ^= hash;
+                count[position] += 1;
+            }
+        }
+
+        let mut queue = (0..count.len())
+            .filter(|&position| count[position] == 1)
+            .collect::<Vec<_>>();
+        let mut stack = Vec::with_capacity(hashes.len());
+        while let Some(position) = queue.pop() {
+            if count[position] != 1 {
+                continue;
+            }
+            let hash = xormask[position];
+            stack.push((hash, position));
+            for other in positions(hash, block_len).1 {
+                xormask[other] ^= hash;
+                count[other] -= 1;
+                if count[other] == 1 {
+                    queue.push(other);
+                }
+            }
+        }
+        (stack.len() == hashes.len()).then_some(stack)
+    }
+}
+
+impl<const N: usize> KeySet<N> for XorFilter<N> {
+    fn insert(&mut self, key: Key<N>) -> bool {
+        !self.is_built() && self.pending.insert(key)
+    }
+
+    fn remove(&mut self, key: &Key<N>) -> bool {
+        self.pending.remove(key)
+    }
+
+    fn contains(&self, key: &Key<N>) -> bool {
+        if !self.is_built() {
+            return self.pending.contains(key);
+        }
+        let (fingerprint, [a, b, c]) = positions(hash(key, self.seed), self.block_len);
+        fingerprint == self.fingerprints[a] ^ self.fingerprints[b] ^ self.fingerprints[c]
+    }
+
+    fn len(&self) -> usize {
+        self.len + self.pending.len()
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.fingerprints.len() + self.pending.len() * mem::size_of::<Key<N>>()
+    }
+
+    fn finish(&mut self) {
+        if self.is_built() || self.pending.is_empty() {
+            return;
+        }
+        let keys = mem::take(&mut self.pending);
+        // 1.23 slots per key, plus constant for small sets
+        let block_len = ((32 + keys.len() * 123 / 100) / 3).max(1) as u32;
+        let mut seed = 0u64;
+        let stack = loop {
+            seed = mix(seed.wrapping_add(0x9e37_79b9_7f4a_7c15));
+            let hashes = keys.iter().map(|key| hash(key, seed)).collect::<Vec<_>>();
+            if let Some(stack) = Self::peel(&hashes, block_len) {
+                break stack;
+            }
+        };
+
+        let mut fingerprints = vec![0u8; 3 * block_len as usize];
+        for (hash, position) in stack.into_iter().rev() {
+            let (fingerprint, [a, b, c]) = positions(hash, block_len);
+            fingerprints[position] = 0;
+            fingerprints[position] =
+                fingerprint ^ fingerprints[a] ^ fingerprints[b] ^ fingerprints[c];
+        }
+
+        self.seed = seed;
+        self.block_len = block_len;
+        self.fingerprints = fingerprints;
+        self.len = keys.len();
+    }
+}
@@ -0,0 +1,128 @@
+//! Names of well-known programs, sysvars and mints, to annotate hot accounts in reports.
+
+use solana_sdk::pubkey::Pubkey;
+
+const KNOWN_ACCOUNTS: &[(&str, &str)] = &[
+    ("11111111111111111111111111111111", "System Program"),
+    (
+        "Vote111111111111111111111111111111111111111",
+        "Vote Program",
+    ),
+    (
+        "Stake11111111111111111111111111111111111111",
+        "Stake Program",
+    ),
+    (
+        "Config1111111111111111111111111111111111111",
+        "Config Program",
+    ),
+    (
+        "ComputeBudget111111111111111111111111111111",
+        "Compute Budget Program",
+    ),
+    (
+        "AddressLookupTab1e1111111111111111111111111",
+        "Address Lookup Table Program",
+    ),
+    (
+        "NativeLoader1111111111111111111111111111111",
+        "Native Loader",
+    ),
+    (
+        "BPFLoader1111111111111111111111111111111111",
+        "BPF Loader (deprecated)",
+    ),
+    ("BPFLoader2111111111111111111111111111111111", "BPF Loader"),
+    (
+        "BPFLoaderUpgradeab1e11111111111111111111111",
+        "BPF Upgradeable Loader",
+    ),
+    (
+        "Ed25519SigVerify111111111111111111111111111",
+        "Ed25519 Program",
+    ),
+    (
+        "KeccakSecp256k11111111111111111111111111111",
+        "Secp256k1 Program",
+    ),
+    (
+        "SysvarC1ock11111111111111111111111111111111",
+        "Sysvar: Clock",
+    ),
+    (
+        "SysvarEpochSchedu1e111111111111111111111111",
+        "Sysvar: Epoch Schedule",
+    ),
+    (
+        "SysvarFees111111111111111111111111111111111",
+        "Sysvar: Fees",
+    ),
+    (
+        "Sysvar1nstructions1111111111111111111111111",
+        "Sysvar: Instructions",
+    ),
+    (
+        "SysvarRecentB1ockHashes11111111111111111111",
+        "Sysvar: Recent Blockhashes",
+    ),
+    (
+        "SysvarRent111111111111111111111111111111111",
+        "Sysvar: Rent",
+    ),
+    (
+        "SysvarRewards111111111111111111111111111111",
+        "Sysvar: Rewards",
+    ),
+    (
+        "SysvarS1otHashes111111111111111111111111111",
+        "Sysvar: Slot Hashes",
+    ),
+    (
+        "SysvarS1otHistory11111111111111111111111111",
+        "Sysvar: Slot History",
+    ),
+    (
+        "SysvarStakeHistory1111111111111111111111111",
+        "Sysvar: Stake History",
+    ),
+    ("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA", "SPL Token"),
+    (
+        "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb",
+        "SPL Token-2022",
+    ),
+    (
+        "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL",
+        "SPL Associated Token Account",
+    ),
+    ("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo", "SPL Memo v1"),
+    ("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr", "SPL Memo"),
+    (
+        "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s",
+        "Metaplex Token Metadata",
+    ),
+    (
+        "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin",
+        "Serum DEX v3",
+    ),
+    (
+        "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8",
+        "Raydium AMM v4",
+    ),
+    (
+        "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc",
+        "Orca Whirlpools",
+    ),
+    ("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4", "Jupiter v6"),
+    ("So11111111111111111111111111111111111111112", "Wrapped SOL"),
+    ("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v", "USDC"),
+    ("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB", "USDT"),
+];
+
+/// Name of well-known program, sysvar or mint.
+pub fn known_name(pubkey: &Pubkey) -> Option<&'static str> {
+    let address = pubkey.to_string();
+    KNOWN_ACCOUNTS
+        .iter()
+        .find(|(known, _name)| *known == address)
+        .map(|(_known, name)| *name)
+}
@@ -0,0 +1,42 @@
+//! Accounts filters benchmarked by `safb bench`, reusable outside of the CLI.
+//!
+//! Without default `runtime` feature only filters are built (no tokio / rayon / RPC client),
+//! so they can be used in `wasm32-unknown-unknown`. Without `std` feature only `keyset`
+//! is built and the crate is `no_std` (requires `alloc`).
+//!
+//! Common types are re-exported by `prelude`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+// used by binaries, `AllocStats` is re-exported by `bench`
+#[cfg(feature = "runtime")]
+#[doc(hidden)]
+pub mod allocator;
+#[cfg(feature = "runtime")]
+pub mod bench;
+#[cfg(feature = "runtime")]
+pub mod dataset;
+#[cfg(feature = "runtime")]
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod filter;
+#[cfg(feature = "runtime")]
+pub mod geyser;
+#[cfg(feature = "runtime")]
+pub mod grpc;
+pub mod keyset;
+#[cfg(feature = "runtime")]
+pub mod known;
+pub mod prelude;
+
+#[cfg(feature = "std")]
+pub use filter::AccountsFilter;
+#[cfg(feature = "runtime")]
+pub use {
+    dataset::{Dataset, DatasetLoader},
+    error::{Error, Result},
+};
@@ -0,0 +1,34 @@
+//! Filter traits, common implementations, `Dataset` and `BenchConfig` in one import:
+//! `use solana_accounts_filter_bench::prelude::*;`.
+//!
+//! Key sets implement both `KeySet` and `AccountsFilter`, so with `std` only `AccountsFilter` is
+//! exported and methods of sets are not ambiguous (`keyset::KeySet` is imported for keys of
+//! other sizes):
+//!
+//! ```
+//! use {solana_accounts_filter_bench::prelude::*, solana_sdk::pubkey::Pubkey};
+//!
+//! let pubkey = Pubkey::new_unique();
+//! let mut set = SortedKeys::default();
+//! set.insert(pubkey);
+//! set.finish();
+//! assert_eq!(set.len(), 1);
+//! assert!(set.contains(&pubkey));
+//! ```
+
+#[cfg(feature = "fst")]
+pub use crate::filter::FstFilter;
+#[cfg(feature = "gxhash")]
+pub use crate::filter::GxHashSet;
+#[cfg(feature = "std")]
+pub use crate::filter::{AccountsFilter, SharedFilter, SharedStrategy};
+#[cfg(not(feature = "std"))]
+pub use crate::keyset::KeySet;
+pub use crate::keyset::{BloomFilter, PrefixSet, SortedKeys, XorFilter};
+#[cfg(feature = "runtime")]
+pub use crate::{
+    bench::{Bench, BenchConfig, Measurement},
+    dataset::{Dataset, DatasetLoader},
+    geyser::{GeyserAccount, GeyserConfig, GeyserFilter},
+    Error,
+};
@@ -0,0 +1,28 @@
+//! Helpers shared by integration tests, every test crate uses only some of them.
+#![allow(dead_code)]
+
+use {
+    solana_accounts_filter_bench::Dataset,
+    std::path::{Path, PathBuf},
+    tempfile::TempDir,
+};
+
+/// Path of the file in `tests/fixtures`.
+pub fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+/// Dataset of `tests/fixtures/tiny.json`.
+pub fn load_fixture() -> Dataset {
+    Dataset::load(&fixture("tiny.json")).expect("load fixture")
+}
+
+/// Directory for outputs of one test, removed with its files when dropped.
+pub fn temp_dir() -> TempDir {
+    tempfile::Builder::new()
+        .prefix("safb-test-")
+        .tempdir()
+        .expect("temp dir")
+}
@@ -0,0 +1,242 @@
+#![cfg(feature = "runtime")]
+
+mod common;
+
+use {
+    common::{fixture, load_fixture, temp_dir},
+    solana_accounts_filter_bench::{
+        dataset::{DatasetFormat, DatasetManifest, ManifestConfig},
+        Dataset,
+    },
+    std::{fs, process::Command},
+};
+
+fn roundtrip(dataset: &Dataset, name: &str) -> Dataset {
+    let dir = temp_dir();
+    let path = dir.path().join(name);
+    dataset.save(&path).expect("save dataset");
+    Dataset::load(&path).expect("load dataset")
+}
+
+#[test]
+fn bincode_is_mapped_without_compression() {
+    let dataset = load_fixture();
+    let mapped = roundtrip(&dataset, "data.bin");
+    assert_eq!(mapped, dataset);
+    assert!(mapped
+        .blocks
+        .values()
+        .all(|block| block.pubkeys.is_mapped()));
+
+    let compressed = roundtrip(&dataset, "data.bin.zst");
+    assert_eq!(compressed, dataset);
+    assert!(compressed
+        .blocks
+        .values()
+        .all(|block| !block.pubkeys.is_mapped()));
+}
+
+#[test]
+fn mapped_pubkeys_are_copied_on_write() {
+    let mut dataset = load_fixture();
+    let mut mapped = roundtrip(&dataset, "cow.bin");
+    let (_slot, first) = dataset.keys().next().expect("key");
+    let first = *first;
+    dataset.retain_keys(|pubkey| *pubkey != first);
+    mapped.retain_keys(|pubkey| *pubkey != first);
+    assert_eq!(mapped, dataset);
+    assert!(mapped
+        .blocks
+        .values()
+        .all(|block| !block.pubkeys.is_mapped()));
+}
+
+#[test]
+fn truncated_bincode_fails() {
+    let dir = temp_dir();
+    let path = dir.path().join("truncated.bin");
+    load_fixture().save(&path).expect("save dataset");
+    let bytes = fs::read(&path).expect("read dataset");
+    for len in [0, 4, 8, 20, bytes.len() / 2, bytes.len() - 1] {
+        fs::write(&path, &bytes[..len]).expect("write dataset");
+        assert!(Dataset::load(&path).is_err(), "loaded {} bytes", len);
+    }
+}
+
+#[test]
+fn indexed_blocks_are_decoded_on_access() {
+    let dataset = load_fixture();
+    for name in ["data.safb", "data.safb.zst"] {
+        let indexed = roundtrip(&dataset, name);
+        assert!(indexed
+            .blocks
+            .values()
+            .all(|block| !block.pubkeys.is_decoded()));
+
+        let (slot, block) = indexed.blocks.iter().next().expect("block");
+        assert_eq!(block.pubkeys, dataset.blocks[slot].pubkeys);
+        assert!(block.pubkeys.is_decoded());
+        assert!(indexed
+            .blocks
+            .values()
+            .skip(1)
+            .all(|block| !block.pubkeys.is_decoded()));
+
+        assert_eq!(indexed, dataset);
+    }
+}
+
+#[test]
+fn truncated_indexed_fails() {
+    let dir = temp_dir();
+    let path = dir.path().join("truncated.safb");
+    load_fixture().save(&path).expect("save dataset");
+    let bytes = fs::read(&path).expect("read dataset");
+    for len in [0, 4, 8, 20, bytes.len() / 2, bytes.len() - 1] {
+        fs::write(&path, &bytes[..len]).expect("write dataset");
+        assert!(Dataset::load(&path).is_err(), "loaded {} bytes", len);
+    }
+}
+
+#[test]
+fn mapped_bincode_is_saved_over_itself() {
+    let dataset = load_fixture();
+    let dir = temp_dir();
+    let path = dir.path().join("self.bin");
+    dataset.save(&path).expect("save dataset");
+    let mapped = Dataset::load(&path).expect("load dataset");
+    mapped.save(&path).expect("save over mapped dataset");
+    assert_eq!(mapped, dataset);
+    assert_eq!(Dataset::load(&path).expect("load dataset"), dataset);
+}
+
+#[test]
+fn indexed_is_converted_over_itself() {
+    let dir = temp_dir();
+    let path = dir.path().join("self.safb");
+    let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "convert", "--input"])
+        .arg(fixture("tiny.json"))
+        .arg("--output")
+        .arg(&path)
+        .output()
+        .expect("run safb");
+    assert!(output.status.success());
+    // Pubkeys of blocks are decoded from the map while the output is written
+    let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "convert", "--input"])
+        .arg(&path)
+        .arg("--output")
+        .arg(&path)
+        .output()
+        .expect("run safb");
+    assert!(
+        output.status.success(),
+        "convert failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(Dataset::load(&path).expect("load dataset"), load_fixture());
+}
+
+#[test]
+fn msgpack_roundtrip() {
+    let dataset = load_fixture();
+    for name in ["data.msgpack", "data.msgpack.gz", "data.msgpack.zst"] {
+        assert_eq!(roundtrip(&dataset, name), dataset, "roundtrip of {}", name);
+    }
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn arrow_roundtrip() {
+    let dataset = load_fixture();
+    for name in ["data.arrow", "data.arrow.zst", "data.feather"] {
+        assert_eq!(roundtrip(&dataset, name), dataset, "roundtrip of {}", name);
+    }
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn mapped_arrow_is_saved_over_itself() {
+    let dataset = load_fixture();
+    let dir = temp_dir();
+    let path = dir.path().join("self.arrow");
+    dataset.save(&path).expect("save dataset");
+    let loaded = Dataset::load(&path).expect("load dataset");
+    loaded.save(&path).expect("save over mapped dataset");
+    assert_eq!(Dataset::load(&path).expect("load dataset"), dataset);
+}
+
+#[test]
+fn manifest_shards_outside_of_directory_are_rejected() {
+    let dataset = load_fixture();
+    let dir = temp_dir();
+    let path = dir.path().join("escape.bin");
+    let format = DatasetFormat::from_path(&path).expect("format");
+    let config = ManifestConfig {
+        shard_blocks: Some(2),
+        ..ManifestConfig::default()
+    };
+    let manifest = dataset
+        .save_with_manifest(&path, format, config)
+        .expect("save dataset");
+    let manifest_path = DatasetManifest::path(&path);
+    let original = fs::read_to_string(&manifest_path).expect("read manifest");
+    for file in ["../escape.bin", "/tmp/escape.bin", ""] {
+        let escaped = original.replacen(
+            &format!("{:?}", manifest.shards[0].file),
+            &format!("{:?}", file),
+            1,
+        );
+        fs::write(&manifest_path, escaped).expect("write manifest");
+        let error = Dataset::load(&path).expect_err("shard outside of directory");
+        assert!(
+            error.to_string().contains("is not a relative path"),
+            "{}: {}",
+            file,
+            error
+        );
+    }
+}
+
+/// Error of loading JSON dataset with one block of `pubkeys`.
+fn json_error(name: &str, pubkeys: &str) -> String {
+    let dir = temp_dir();
+    let path = dir.path().join(name);
+    let json = format!(
+        r#"{{"version":3,"blocks":{{"0":{{"block_time":0,"pubkeys":{}}}}}}}"#,
+        pubkeys
+    );
+    fs::write(&path, json).expect("write dataset");
+    Dataset::load(&path)
+        .expect_err("invalid dataset")
+        .to_string()
+}
+
+#[test]
+fn invalid_json_pubkeys_fail() {
+    let valid = "55xQ4FfUE6sUarW6k5bMZfgork6QUMiekBTc33dW1sEt";
+    // `0` is not in base58 alphabet
+    let error = json_error("base58.json", r#"["0OIl"]"#);
+    assert!(
+        error.contains(r#"invalid value: string "0OIl", expected base58 encoded Pubkey"#),
+        "{}",
+        error
+    );
+    let short = &valid[..40];
+    let error = json_error("short.json", &format!("[{:?}]", short));
+    assert!(
+        error.contains(&format!(
+            "invalid value: string {:?}, expected base58 encoded Pubkey",
+            short
+        )),
+        "{}",
+        error
+    );
+    let error = json_error("mixed.json", &format!("[{:?}, \"invalid\"]", valid));
+    assert!(
+        error.contains(r#"invalid value: string "invalid", expected base58 encoded Pubkey"#),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,250 @@
+#![cfg(feature = "runtime")]
+
+mod common;
+mod mock_rpc;
+
+use {
+    common::temp_dir,
+    mock_rpc::{Fault, MockBlock, MockRpc},
+    solana_accounts_filter_bench::Dataset,
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        collections::{BTreeMap, BTreeSet},
+        path::Path,
+        process::{Command, Output},
+    },
+};
+
+/// Blocks of 30 slots from 500 to 1950 with step 50, so they take two pages of `getBlocks`,
+/// one second between blocks.
+const FIRST_SLOT: Slot = 500;
+const LAST_SLOT: Slot = 1950;
+const BLOCK_TIME: i64 = 1_650_000_000;
+
+fn payer() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+fn key(slot: Slot, index: u8) -> Pubkey {
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&slot.to_le_bytes());
+    bytes[8] = index;
+    Pubkey::new_from_array(bytes)
+}
+
+fn block_time(slot: Slot) -> i64 {
+    BLOCK_TIME + ((slot - FIRST_SLOT) / 50) as i64
+}
+
+/// Two transactions with shared keys, so Pubkeys of the block are deduped.
+fn chain() -> BTreeMap<Slot, MockBlock> {
+    (FIRST_SLOT..=LAST_SLOT)
+        .step_by(50)
+        .map(|slot| {
+            let block = MockBlock {
+                block_time: block_time(slot),
+                transactions: vec![
+                    vec![payer(), key(slot, 1), key(slot, 2)],
+                    vec![payer(), key(slot, 2), key(slot, 3)],
+                ],
+            };
+            (slot, block)
+        })
+        .collect()
+}
+
+fn expected_pubkeys(slot: Slot) -> BTreeSet<Pubkey> {
+    BTreeSet::from([payer(), key(slot, 1), key(slot, 2), key(slot, 3)])
+}
+
+fn download(rpc: &MockRpc, out: &Path, count: i64) -> Output {
+    download_with(rpc, out, count, &[])
+}
+
+fn download_with(rpc: &MockRpc, out: &Path, count: i64, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "--rpc", &rpc.url(), "download"])
+        .args(["--from", &LAST_SLOT.to_string()])
+        .args(["--count", &count.to_string()])
+        .args(["--retry-delay", "0"])
+        .args(args)
+        .arg("--out")
+        .arg(out)
+        .output()
+        .expect("run safb")
+}
+
+/// Blocks below `first_slot` are cleaned up from `rpc`.
+fn prune(rpc: &MockRpc, first_slot: Slot) {
+    for slot in (FIRST_SLOT..first_slot).step_by(50) {
+        rpc.fail(
+            &format!("getBlock:{}", slot),
+            Fault::Rpc(-32001, "Block cleaned up, does not exist on node"),
+            100,
+        );
+    }
+}
+
+fn assert_success(output: &Output) {
+    assert!(
+        output.status.success(),
+        "download failed: {}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn load(out: &Path) -> Dataset {
+    Dataset::load(out).expect("load downloaded dataset")
+}
+
+fn assert_blocks(dataset: &Dataset, slots: impl Iterator<Item = Slot>) {
+    let slots = slots.collect::<Vec<_>>();
+    assert_eq!(dataset.blocks.keys().copied().collect::<Vec<_>>(), slots);
+    for slot in slots {
+        let block = &dataset.blocks[&slot];
+        assert_eq!(block.block_time, block_time(slot), "block time of {}", slot);
+        assert_eq!(block.pubkeys.len(), 4, "pubkeys of {} are deduped", slot);
+        let pubkeys = block.pubkeys.iter().copied().collect::<BTreeSet<_>>();
+        assert_eq!(pubkeys, expected_pubkeys(slot), "pubkeys of {}", slot);
+    }
+}
+
+#[test]
+fn downloads_all_pages_of_blocks() {
+    let rpc = MockRpc::start(chain());
+    let dir = temp_dir();
+    let out = dir.path().join("all.json");
+    let output = download(&rpc, &out, 3600);
+    assert_success(&output);
+
+    assert_blocks(&load(&out), (FIRST_SLOT..=LAST_SLOT).step_by(50));
+    // [950, 1950], [0, 949] and the empty page below the first block
+    assert_eq!(rpc.requests("getBlocks"), 3);
+    assert_eq!(rpc.requests(&format!("getBlock:{}", LAST_SLOT)), 1);
+}
+
+#[test]
+fn stops_at_block_time() {
+    let rpc = MockRpc::start(chain());
+    let dir = temp_dir();
+    let out = dir.path().join("stop.json");
+    let output = download(&rpc, &out, 10);
+    assert_success(&output);
+
+    // 10 seconds before the first block, blocks below the stop time are not saved
+    assert_blocks(&load(&out), (LAST_SLOT - 500..=LAST_SLOT).step_by(50));
+    assert_eq!(rpc.requests("getBlocks"), 1);
+    assert_eq!(rpc.requests(&format!("getBlock:{}", FIRST_SLOT)), 0);
+}
+
+#[test]
+fn retries_failed_requests() {
+    let rpc = MockRpc::start(chain());
+    rpc.fail("getBlocks", Fault::Status(502), 2);
+    rpc.fail("getBlock:1900", Fault::Status(500), 3);
+    rpc.fail(
+        "getBlock:1500",
+        Fault::Rpc(-32004, "Block not available for slot 1500"),
+        1,
+    );
+    let dir = temp_dir();
+    let out = dir.path().join("retries.json");
+    let output = download(&rpc, &out, 3600);
+    assert_success(&output);
+
+    assert_blocks(&load(&out), (FIRST_SLOT..=LAST_SLOT).step_by(50));
+    // retries are not printed with `--quiet`
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("failed to"), "{}", stdout);
+    assert_eq!(rpc.requests("getBlocks"), 5);
+    assert_eq!(rpc.requests("getBlock:1900"), 4);
+    assert_eq!(rpc.requests("getBlock:1500"), 2);
+}
+
+#[test]
+fn retries_rate_limited_requests() {
+    let rpc = MockRpc::start(chain());
+    rpc.fail("getBlock", Fault::RateLimited, 8);
+    rpc.fail("getBlocks", Fault::RateLimited, 2);
+    let dir = temp_dir();
+    let out = dir.path().join("rate-limited.json");
+    let output = download(&rpc, &out, 3600);
+    assert_success(&output);
+
+    assert_blocks(&load(&out), (FIRST_SLOT..=LAST_SLOT).step_by(50));
+}
+
+#[test]
+fn fails_after_retries_are_exhausted() {
+    let rpc = MockRpc::start(chain());
+    rpc.fail("getBlock:1700", Fault::Status(503), 100);
+    let dir = temp_dir();
+    let out = dir.path().join("exhausted.json");
+    let output = download(&rpc, &out, 3600);
+
+    assert!(!output.status.success(), "download should fail");
+    assert!(!out.exists(), "dataset should not be saved");
+    // first request and 5 retries
+    assert_eq!(rpc.requests("getBlock:1700"), 6);
+}
+
+#[test]
+fn falls_back_to_archive_for_pruned_blocks() {
+    let rpc = MockRpc::start(chain());
+    let archive = MockRpc::start(chain());
+    prune(&rpc, 1200);
+    let dir = temp_dir();
+    let out = dir.path().join("archive.json");
+    let output = download_with(&rpc, &out, 3600, &["--archive-rpc", &archive.url()]);
+    assert_success(&output);
+
+    assert_blocks(&load(&out), (FIRST_SLOT..=LAST_SLOT).step_by(50));
+    for slot in (FIRST_SLOT..=LAST_SLOT).step_by(50) {
+        let key = format!("getBlock:{}", slot);
+        if slot < 1200 {
+            // without retries, older blocks are requested only from the archive once one is pruned
+            assert!(rpc.requests(&key) <= 1, "{} requested from rpc", key);
+            assert_eq!(archive.requests(&key), 1, "{} requested from archive", key);
+        } else {
+            assert_eq!(rpc.requests(&key), 1, "{} requested from rpc", key);
+            assert_eq!(archive.requests(&key), 0, "{} requested from archive", key);
+        }
+    }
+}
+
+#[test]
+fn falls_back_to_archive_for_min_context_slot() {
+    let rpc = MockRpc::start(chain());
+    let archive = MockRpc::start(chain());
+    rpc.fail(
+        "getBlock:1500",
+        Fault::Rpc(-32016, "Minimum context slot has not been reached"),
+        1,
+    );
+    let dir = temp_dir();
+    let out = dir.path().join("min-context-slot.json");
+    let output = download_with(&rpc, &out, 3600, &["--archive-rpc", &archive.url()]);
+    assert_success(&output);
+
+    assert_blocks(&load(&out), (FIRST_SLOT..=LAST_SLOT).step_by(50));
+    assert_eq!(rpc.requests("getBlock:1500"), 1);
+    assert_eq!(archive.requests("getBlock:1500"), 1);
+    // the node is behind, older blocks are not pruned
+    assert_eq!(rpc.requests("getBlock:1450"), 1);
+    assert_eq!(archive.requests("getBlock:1450"), 0);
+}
+
+#[test]
+fn pruned_blocks_without_archive_fail() {
+    let rpc = MockRpc::start(chain());
+    prune(&rpc, 1200);
+    let dir = temp_dir();
+    let out = dir.path().join("pruned.json");
+    let output = download(&rpc, &out, 3600);
+
+    assert!(!output.status.success(), "download should fail");
+    assert!(!out.exists(), "dataset should not be saved");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("--archive-rpc"), "no hint in {}", stderr);
+}
@@ -0,0 +1,335 @@
+#![cfg(feature = "runtime")]
+
+use {
+    proptest::{prelude::*, sample::Index},
+    rand::{rngs::StdRng, RngCore, SeedableRng},
+    solana_accounts_filter_bench::{bench::registry, prelude::*},
+    solana_sdk::pubkey::Pubkey,
+    std::collections::HashSet,
+};
+
+/// Filters of `register_filter!` with property tests below, a newly registered filter fails
+/// `every_registered_filter_is_covered` until it is added here.
+const COVERED: &[&str] = &[
+    "hashset", "gxhash", "flurry", "fst", "sorted", "prefix", "bloom", "xor",
+];
+
+#[derive(Debug, Clone)]
+enum Op {
+    Insert(Index),
+    Remove(Index),
+    Finish,
+}
+
+fn ops() -> impl Strategy<Value = Vec<Op>> {
+    let op = prop_oneof![
+        3 => any::<Index>().prop_map(Op::Insert),
+        2 => any::<Index>().prop_map(Op::Remove),
+        1 => Just(Op::Finish),
+    ];
+    prop::collection::vec(op, 0..64)
+}
+
+/// Random keys and keys sharing one of four 8-byte prefixes, so prefix collisions and
+/// sorted neighbours are common.
+fn key() -> impl Strategy<Value = Pubkey> {
+    prop_oneof![
+        any::<[u8; 32]>().prop_map(Pubkey::new_from_array),
+        (0u8..4, any::<[u8; 24]>()).prop_map(|(prefix, suffix)| {
+            let mut bytes = [0; 32];
+            bytes[7] = prefix;
+            bytes[8..].copy_from_slice(&suffix);
+            Pubkey::new_from_array(bytes)
+        }),
+    ]
+}
+
+/// Keys used by operations.
+fn pool() -> impl Strategy<Value = Vec<Pubkey>> {
+    prop::collection::vec(key(), 1..24)
+}
+
+/// Keys which are only looked up, mostly not in the filter.
+fn queries() -> impl Strategy<Value = Vec<Pubkey>> {
+    prop::collection::vec(key(), 0..32)
+}
+
+/// Exact filter: every operation returns the same as `HashSet`, before and after `finish`.
+fn check_exact<F: AccountsFilter>(
+    mut filter: F,
+    pool: &[Pubkey],
+    ops: &[Op],
+    queries: &[Pubkey],
+) -> Result<(), TestCaseError> {
+    let mut reference = HashSet::new();
+    for op in ops {
+        match op {
+            Op::Insert(index) => {
+                let key = *index.get(pool);
+                prop_assert_eq!(filter.insert(key), reference.insert(key), "insert {}", key);
+            }
+            Op::Remove(index) => {
+                let key = index.get(pool);
+                prop_assert_eq!(filter.remove(key), reference.remove(key), "remove {}", key);
+            }
+            Op::Finish => filter.finish(),
+        }
+        prop_assert_eq!(filter.len(), reference.len());
+        for key in pool {
+            prop_assert_eq!(
+                filter.contains(key),
+                reference.contains(key),
+                "contains {}",
+                key
+            );
+        }
+    }
+    filter.finish();
+    prop_assert_eq!(filter.len(), reference.len());
+    for key in pool.iter().chain(queries) {
+        prop_assert_eq!(
+            filter.contains(key),
+            reference.contains(key),
+            "contains {}",
+            key
+        );
+    }
+    Ok(())
+}
+
+fn prefix(key: &Pubkey) -> [u8; 8] {
+    key.to_bytes()[..8].try_into().expect("8 bytes")
+}
+
+/// `PrefixSet` is exact over 8-byte prefixes: keys sharing a prefix are one key, so lookups of
+/// keys not in the set have false positives only on a shared prefix.
+fn check_prefix<F: AccountsFilter>(
+    mut filter: F,
+    pool: &[Pubkey],
+    ops: &[Op],
+    queries: &[Pubkey],
+) -> Result<(), TestCaseError> {
+    let mut reference = HashSet::new();
+    for op in ops {
+        match op {
+            Op::Insert(index) => {
+                let key = *index.get(pool);
+                let inserted = reference.insert(prefix(&key));
+                prop_assert_eq!(filter.insert(key), inserted);
+            }
+            Op::Remove(index) => {
+                let key = index.get(pool);
+                let removed = reference.remove(&prefix(key));
+                prop_assert_eq!(filter.remove(key), removed);
+            }
+            Op::Finish => filter.finish(),
+        }
+        prop_assert_eq!(filter.len(), reference.len());
+    }
+    filter.finish();
+    for key in pool.iter().chain(queries) {
+        let contains = reference.contains(&prefix(key));
+        prop_assert_eq!(filter.contains(key), contains, "contains {}", key);
+    }
+    Ok(())
+}
+
+/// `BloomFilter` has no false negatives, keys can not be removed, length counts every insert.
+fn check_bloom<F: AccountsFilter>(
+    mut filter: F,
+    pool: &[Pubkey],
+    ops: &[Op],
+) -> Result<(), TestCaseError> {
+    let mut reference = HashSet::new();
+    let mut inserts = 0;
+    for op in ops {
+        match op {
+            Op::Insert(index) => {
+                let key = *index.get(pool);
+                let inserted = filter.insert(key);
+                // a new key may set only bits of other keys, as a false positive
+                prop_assert!(!inserted || reference.insert(key), "reinserted {}", key);
+                reference.insert(key);
+                inserts += 1;
+            }
+            Op::Remove(index) => {
+                prop_assert!(!filter.remove(index.get(pool)));
+            }
+            Op::Finish => filter.finish(),
+        }
+        prop_assert_eq!(filter.len(), inserts);
+        for key in reference.iter() {
+            prop_assert!(filter.contains(key), "lost {}", key);
+        }
+    }
+    Ok(())
+}
+
+/// `XorFilter` is exact until it is built by `finish` with keys, then it is static: inserted
+/// keys are never lost, inserts and removes are rejected, other keys may be false positives.
+fn check_xor<F: AccountsFilter>(
+    mut filter: F,
+    pool: &[Pubkey],
+    ops: &[Op],
+    queries: &[Pubkey],
+) -> Result<(), TestCaseError> {
+    let mut reference = HashSet::new();
+    let mut built = false;
+    for op in ops {
+        match op {
+            Op::Insert(index) => {
+                let key = *index.get(pool);
+                let inserted = !built && reference.insert(key);
+                prop_assert_eq!(filter.insert(key), inserted);
+            }
+            Op::Remove(index) => {
+                let key = index.get(pool);
+                let removed = !built && reference.remove(key);
+                prop_assert_eq!(filter.remove(key), removed);
+            }
+            Op::Finish => {
+                filter.finish();
+                built |= !reference.is_empty();
+            }
+        }
+        prop_assert_eq!(filter.len(), reference.len());
+    }
+    filter.finish();
+    built |= !reference.is_empty();
+    for key in pool.iter().chain(queries) {
+        let contains = filter.contains(key);
+        if reference.contains(key) {
+            prop_assert!(contains, "lost {}", key);
+        } else if !built {
+            prop_assert!(!contains, "contains {}", key);
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn hashset_matches_reference(pool in pool(), ops in ops(), queries in queries()) {
+        check_exact(HashSet::<Pubkey>::new(), &pool, &ops, &queries)?;
+    }
+
+    #[cfg(feature = "gxhash")]
+    #[test]
+    fn gxhash_matches_reference(pool in pool(), ops in ops(), queries in queries()) {
+        check_exact(GxHashSet::default(), &pool, &ops, &queries)?;
+    }
+
+    #[cfg(feature = "flurry")]
+    #[test]
+    fn flurry_matches_reference(pool in pool(), ops in ops(), queries in queries()) {
+        check_exact(flurry::HashSet::<Pubkey>::new(), &pool, &ops, &queries)?;
+    }
+
+    #[cfg(feature = "fst")]
+    #[test]
+    fn fst_matches_reference(pool in pool(), ops in ops(), queries in queries()) {
+        check_exact(FstFilter::default(), &pool, &ops, &queries)?;
+    }
+
+    #[test]
+    fn sorted_matches_reference(pool in pool(), ops in ops(), queries in queries()) {
+        check_exact(SortedKeys::<32>::default(), &pool, &ops, &queries)?;
+    }
+
+    #[test]
+    fn prefix_matches_reference_prefixes(pool in pool(), ops in ops(), queries in queries()) {
+        check_prefix(PrefixSet::default(), &pool, &ops, &queries)?;
+    }
+
+    #[test]
+    fn bloom_has_no_false_negatives(pool in pool(), ops in ops()) {
+        check_bloom(BloomFilter::new(pool.len()), &pool, &ops)?;
+    }
+
+    #[test]
+    fn xor_has_no_false_negatives(pool in pool(), ops in ops(), queries in queries()) {
+        check_xor(XorFilter::default(), &pool, &ops, &queries)?;
+    }
+
+    #[test]
+    fn shared_filters_match_reference(
+        pool in pool(),
+        updates in prop::collection::vec(
+            (prop::collection::vec(any::<Index>(), 0..8), prop::collection::vec(any::<Index>(), 0..8)),
+            0..16,
+        ),
+        queries in queries(),
+    ) {
+        for strategy in SharedStrategy::ALL {
+            let filter = strategy.shared(HashSet::<Pubkey>::new());
+            let mut reference = HashSet::new();
+            for (insert, remove) in updates.iter() {
+                let insert = insert.iter().map(|index| *index.get(&pool)).collect::<Vec<_>>();
+                let remove = remove.iter().map(|index| *index.get(&pool)).collect::<Vec<_>>();
+                filter.update(&insert, &remove);
+                reference.extend(insert.iter().copied());
+                for key in remove.iter() {
+                    reference.remove(key);
+                }
+                prop_assert_eq!(filter.len(), reference.len(), "{}", strategy.name());
+            }
+            let keys = pool.iter().chain(queries.iter()).copied().collect::<Vec<_>>();
+            let expected = keys.iter().filter(|key| reference.contains(*key)).count();
+            prop_assert_eq!(filter.contains_many(&keys), expected, "{}", strategy.name());
+        }
+    }
+}
+
+/// Random keys from fixed seed, false positive rates below are deterministic.
+fn random_keys(rng: &mut StdRng, count: usize) -> Vec<Pubkey> {
+    (0..count)
+        .map(|_| {
+            let mut bytes = [0; 32];
+            rng.fill_bytes(&mut bytes);
+            Pubkey::new_from_array(bytes)
+        })
+        .collect()
+}
+
+fn false_positive_rate<F: AccountsFilter>(mut filter: F) -> f64 {
+    let mut rng = StdRng::seed_from_u64(42);
+    let keys = random_keys(&mut rng, 10_000);
+    for key in keys.iter() {
+        filter.insert(*key);
+    }
+    filter.finish();
+    assert!(
+        keys.iter().all(|key| filter.contains(key)),
+        "false negatives"
+    );
+    let negatives = random_keys(&mut rng, 100_000);
+    let false_positives = negatives.iter().filter(|key| filter.contains(key)).count();
+    false_positives as f64 / negatives.len() as f64
+}
+
+#[test]
+fn false_positive_rates_match_docs() {
+    // about 1% with 10 bits per key
+    let bloom = false_positive_rate(BloomFilter::new(10_000));
+    assert!(bloom < 0.02, "bloom false positive rate {}", bloom);
+    // 0.4% with 8-bit fingerprints
+    let xor = false_positive_rate(XorFilter::default());
+    assert!(xor < 0.008, "xor false positive rate {}", xor);
+    // `len / 2^64`
+    let prefix = false_positive_rate(PrefixSet::default());
+    assert_eq!(prefix, 0.0, "prefix false positive rate");
+}
+
+#[test]
+fn every_registered_filter_is_covered() {
+    for bench in registry() {
+        // every lookup strategy of `register_filter!` has `-pipeline` benchmark
+        if let Some(name) = bench.name.strip_suffix("-pipeline") {
+            assert!(
+                COVERED.contains(&name),
+                "filter {} has no property test in tests/filters.rs",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Minimal JSON-RPC server with canned blocks for `safb download`, failures are injected per
+//! method and slot.
+
+use {
+    serde_json::{json, Value},
+    solana_sdk::{
+        clock::{Slot, UnixTimestamp},
+        hash::Hash,
+        message::{Message, MessageHeader, VersionedMessage},
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::VersionedTransaction,
+    },
+    std::{
+        collections::{BTreeMap, HashMap, VecDeque},
+        io::{BufRead, BufReader, Write},
+        net::{SocketAddr, TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+    },
+};
+
+/// Block served by `getBlock`, every transaction is a list of account keys.
+#[derive(Debug, Clone)]
+pub struct MockBlock {
+    pub block_time: UnixTimestamp,
+    pub transactions: Vec<Vec<Pubkey>>,
+}
+
+/// Response returned instead of the canned one.
+#[derive(Debug, Clone)]
+pub enum Fault {
+    /// HTTP error status with empty body.
+    Status(u16),
+    /// `429 Too Many Requests` with `Retry-After: 0`.
+    RateLimited,
+    /// JSON-RPC error object.
+    Rpc(i64, &'static str),
+}
+
+#[derive(Default)]
+struct State {
+    blocks: BTreeMap<Slot, MockBlock>,
+    /// Faults by `method` or `method:slot`, one is used per request.
+    faults: HashMap<String, VecDeque<Fault>>,
+    /// Served requests as `method` or `method:slot`, including failed ones.
+    requests: Vec<String>,
+}
+
+pub struct MockRpc {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockRpc {
+    pub fn start(blocks: BTreeMap<Slot, MockBlock>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock rpc");
+        let addr = listener.local_addr().expect("local addr");
+        let state = Arc::new(Mutex::new(State {
+            blocks,
+            ..State::default()
+        }));
+        thread::spawn({
+            let state = Arc::clone(&state);
+            move || {
+                for stream in listener.incoming().flatten() {
+                    let state = Arc::clone(&state);
+                    thread::spawn(move || serve_connection(stream, &state));
+                }
+            }
+        });
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Fail the next `count` requests of `key` (`getBlocks` or `getBlock:<slot>`) with `fault`.
+    pub fn fail(&self, key: &str, fault: Fault, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        let faults = state.faults.entry(key.to_owned()).or_default();
+        faults.extend(std::iter::repeat_n(fault, count));
+    }
+
+    /// Number of served requests of `key`.
+    pub fn requests(&self, key: &str) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .requests
+            .iter()
+            .filter(|request| *request == key)
+            .count()
+    }
+}
+
+fn serve_connection(stream: TcpStream, state: &Mutex<State>) {
+    let mut writer = stream.try_clone().expect("clone stream");
+    let mut reader = BufReader::new(stream);
+    // keep-alive connections of the client, until it closes them
+    while let Some(body) = read_request(&mut reader) {
+        let request = serde_json::from_slice::<Value>(&body).expect("json request");
+        let (status, headers, body) = respond(&request, &mut state.lock().unwrap());
+        let head = format!(
+            "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\n{}\r\n",
+            status,
+            body.len(),
+            headers
+        );
+        if writer.write_all(head.as_bytes()).is_err() || writer.write_all(&body).is_err() {
+            return;
+        }
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<Vec<u8>> {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().ok()?;
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
+
+fn respond(request: &Value, state: &mut State) -> (u16, &'static str, Vec<u8>) {
+    let method = request["method"].as_str().unwrap_or_default();
+    let params = &request["params"];
+    let key = match method {
+        "getBlock" | "getBlockTime" => format!("{}:{}", method, params[0]),
+        _ => method.to_owned(),
+    };
+    state.requests.push(key.clone());
+
+    let fault = [key.as_str(), method]
+        .iter()
+        .find_map(|key| state.faults.get_mut(*key)?.pop_front());
+    let result = match fault {
+        Some(Fault::Status(status)) => return (status, "", vec![]),
+        Some(Fault::RateLimited) => return (429, "retry-after: 0\r\n", vec![]),
+        Some(Fault::Rpc(code, message)) => Err((code, message)),
+        None => result(method, params, &state.blocks),
+    };
+    let body = match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+        Err((code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": {"code": code, "message": message}
+        }),
+    };
+    (200, "", serde_json::to_vec(&body).expect("json response"))
+}
+
+fn result(
+    method: &str,
+    params: &Value,
+    blocks: &BTreeMap<Slot, MockBlock>,
+) -> Result<Value, (i64, &'static str)> {
+    let slot = params[0].as_u64().unwrap_or_default();
+    match method {
+        // cluster version is requested by `RpcClient` before the first `getBlocks`
+        "getVersion" => Ok(json!({"solana-core": "1.10.17", "feature-set": 0})),
+        "getSlot" => Ok(json!(blocks.keys().next_back())),
+        "getBlocks" => {
+            let end = params[1].as_u64().unwrap_or(Slot::MAX);
+            Ok(json!(blocks
+                .range(slot..=end)
+                .map(|(slot, _block)| slot)
+                .collect::<Vec<_>>()))
+        }
+        "getBlockTime" => match blocks.get(&slot) {
+            Some(block) => Ok(json!(block.block_time)),
+            None => Err((-32004, "Block not available for slot")),
+        },
+        "getBlock" => match blocks.get(&slot) {
+            Some(block) => Ok(encode_block(slot, block)),
+            None => Err((-32004, "Block not available for slot")),
+        },
+        _ => Err((-32601, "Method not found")),
+    }
+}
+
+/// `getBlock` result with base64 transactions, as requested by `download`.
+fn encode_block(slot: Slot, block: &MockBlock) -> Value {
+    let transactions = block
+        .transactions
+        .iter()
+        .map(|account_keys| {
+            let transaction = VersionedTransaction {
+                signatures: vec![Signature::default()],
+                message: VersionedMessage::Legacy(Message {
+                    header: MessageHeader {
+                        num_required_signatures: 1,
+                        num_readonly_signed_accounts: 0,
+                        num_readonly_unsigned_accounts: 0,
+                    },
+                    account_keys: account_keys.clone(),
+                    recent_blockhash: Hash::default(),
+                    instructions: vec![],
+                }),
+            };
+            let data = base64::encode(bincode::serialize(&transaction).expect("transaction"));
+            json!({"transaction": [data, "base64"], "meta": null})
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "previousBlockhash": Hash::default().to_string(),
+        "blockhash": Hash::default().to_string(),
+        "parentSlot": slot.saturating_sub(1),
+        "transactions": transactions,
+        "rewards": [],
+        "blockTime": block.block_time,
+        "blockHeight": slot,
+    })
+}
@@ -0,0 +1,413 @@
+#![cfg(feature = "runtime")]
+
+mod common;
+
+use {
+    common::{fixture, load_fixture, temp_dir},
+    serde_json::Value,
+    solana_accounts_filter_bench::{
+        bench::{registry, Bench},
+        Dataset,
+    },
+    solana_sdk::pubkey::Pubkey,
+    std::{collections::BTreeMap, fs, path::Path, process::Command},
+};
+
+/// Run one iteration of every selected benchmark over the fixture, results by benchmark name.
+fn bench(args: &[&str]) -> BTreeMap<String, Value> {
+    let dir = temp_dir();
+    let out = dir.path().join("results.json");
+    let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "bench", "--iters", "1", "--rayon-min-lens", "1"])
+        .arg("--input")
+        .arg(fixture("tiny.json"))
+        .args(args)
+        .arg("--output")
+        .arg(&out)
+        .output()
+        .expect("run safb");
+    assert!(
+        output.status.success(),
+        "bench failed: {}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let results = fs::read(&out).expect("read results");
+    serde_json::from_slice::<Vec<Value>>(&results)
+        .expect("results")
+        .into_iter()
+        .map(|result| (result["bench"].as_str().expect("bench").to_owned(), result))
+        .collect()
+}
+
+/// Benchmarks run by default in this build, with or without geyser config.
+fn available(geyser: bool) -> Vec<&'static str> {
+    registry()
+        .iter()
+        .filter(|bench| bench.has(Bench::GEYSER) == geyser && bench.missing_features().is_empty())
+        .map(|bench| bench.name)
+        .collect()
+}
+
+fn assert_ran_once(results: &BTreeMap<String, Value>, expected: &[&str]) {
+    let names = results.keys().map(String::as_str).collect::<Vec<_>>();
+    let mut expected = expected.to_vec();
+    expected.sort_unstable();
+    assert_eq!(names, expected);
+
+    let dataset = load_fixture();
+    let lookups = dataset.keys().count() as u64;
+    for (name, result) in results {
+        assert_eq!(result["iters"], 1, "iterations of {}", name);
+        assert_eq!(result["total_ops"], lookups, "lookups of {}", name);
+    }
+}
+
+#[test]
+fn fixture_is_small() {
+    let size = fs::metadata(fixture("tiny.json")).expect("fixture").len();
+    assert!(size < 16 * 1024, "fixture has {} bytes", size);
+}
+
+#[test]
+fn every_filter_runs_on_fixture() {
+    let results = bench(&["--set-size", "64", "--hit-rate", "0.5"]);
+    assert_ran_once(&results, &available(false));
+    for (name, result) in results.iter() {
+        // half of the set is from the dataset, so every filter has matches
+        assert!(
+            result["success"].as_u64() > Some(0),
+            "no matches in {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn every_geyser_filter_runs_on_fixture() {
+    let config = fixture("geyser.yml");
+    let results = bench(&["--geyser-config", config.to_str().expect("path")]);
+    assert_ran_once(&results, &available(true));
+    for (name, result) in results.iter() {
+        // accounts of both subscribers, excluded account of `bob` is subscribed by `alice`
+        assert_eq!(result["success"], 6, "matches of {}", name);
+    }
+}
+
+#[test]
+fn empty_account_list_matches_every_account() {
+    let dir = temp_dir();
+    let config = dir.path().join("geyser.yml");
+    let geyser = fs::read_to_string(fixture("geyser.yml")).expect("geyser config");
+    fs::write(&config, format!("{}  carol: {{}}\n", geyser)).expect("write geyser config");
+    let results = bench(&["--geyser-config", config.to_str().expect("path")]);
+
+    let dataset = load_fixture();
+    let lookups = dataset.keys().count() as u64;
+    assert_ran_once(&results, &available(true));
+    for (name, result) in results.iter() {
+        // same as Yellowstone, `carol` without accounts is subscribed to all of them
+        assert_eq!(result["success"], 6 + lookups, "matches of {}", name);
+    }
+}
+
+#[test]
+fn duplicated_accounts_match_once() {
+    let dir = temp_dir();
+    let config = dir.path().join("geyser.yml");
+    let geyser = fs::read_to_string(fixture("geyser.yml")).expect("geyser config");
+    let account = "      - FakgmXxPc2z8qdHzAjSWAhbK75JVVTfpfWCFmZPTFNjz\n";
+    let geyser = geyser.replacen(account, &account.repeat(2), 1);
+    fs::write(&config, geyser).expect("write geyser config");
+    let results = bench(&["--geyser-config", config.to_str().expect("path")]);
+
+    assert_ran_once(&results, &available(true));
+    for (name, result) in results.iter() {
+        assert_eq!(result["success"], 6, "matches of {}", name);
+    }
+}
+
+#[test]
+fn results_do_not_depend_on_threads() {
+    let args = [
+        "--set-size",
+        "64",
+        "--hit-rate",
+        "0.5",
+        "--shuffle-keys",
+        "--filters",
+        "hashset,hashset-rayon,hashset-rayon-blocks,hashset-tokio",
+    ];
+    let single = bench(&[&args[..], &["--threads", "1"]].concat());
+    let parallel = bench(&[&args[..], &["--threads", "3"]].concat());
+    assert_eq!(single.len(), 4);
+    for (name, result) in single.iter() {
+        assert_eq!(
+            result["success"], parallel[name]["success"],
+            "matches of {}",
+            name
+        );
+        assert_eq!(
+            result["success"], single["hashset"]["success"],
+            "matches of {}",
+            name
+        );
+    }
+}
+
+#[test]
+fn inputs_are_repeated_and_expanded_from_directories() {
+    let temp = temp_dir();
+    let dir = temp.path().join("inputs");
+    fs::create_dir_all(&dir).expect("inputs dir");
+    let dataset = load_fixture();
+    dataset.save(&dir.join("a.bin")).expect("save dataset");
+    // shards are loaded with the manifest, so the dataset is benchmarked once
+    let status = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "--shard-blocks", "2", "convert", "--input"])
+        .arg(fixture("tiny.json"))
+        .arg("--output")
+        .arg(dir.join("b.bin"))
+        .status()
+        .expect("run safb");
+    assert!(status.success());
+
+    let out = temp.path().join("results.json");
+    let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "bench", "--iters", "1", "--filters", "hashset"])
+        .args(["--set-size", "64", "--hit-rate", "0.5", "--input"])
+        .arg(&dir)
+        .arg("--input")
+        .arg(fixture("tiny.json"))
+        .arg("--output")
+        .arg(&out)
+        .output()
+        .expect("run safb");
+    assert!(
+        output.status.success(),
+        "bench failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Comparison of inputs"), "{}", stdout);
+
+    let results = serde_json::from_slice::<Vec<Value>>(&fs::read(&out).expect("read results"))
+        .expect("results");
+    let inputs = results
+        .iter()
+        .map(|result| {
+            Path::new(result["input"].as_str().expect("input"))
+                .file_name()
+                .expect("file name")
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(inputs, ["a.bin", "b.bin", "tiny.json"]);
+    for result in results.iter() {
+        assert_eq!(result["success"], results[0]["success"]);
+    }
+}
+
+#[test]
+fn buckets_cover_all_lookups() {
+    let results = bench(&[
+        "--set-size",
+        "64",
+        "--hit-rate",
+        "0.5",
+        "--bucket-interval",
+        "2",
+        "--filters",
+        "hashset,hashset-tokio,hashset-pipeline,hashset-rayon-blocks",
+    ]);
+    let dataset = load_fixture();
+    for name in ["hashset", "hashset-tokio", "hashset-pipeline"] {
+        let buckets = results[name]["buckets"].as_array().expect("buckets");
+        let sum = |field: &str| {
+            buckets
+                .iter()
+                .map(|bucket| bucket[field].as_u64().expect("number"))
+                .sum::<u64>()
+        };
+        assert!(buckets.len() > 1, "buckets of {}", name);
+        assert_eq!(
+            sum("blocks"),
+            dataset.blocks.len() as u64,
+            "blocks of {}",
+            name
+        );
+        assert_eq!(
+            sum("total_ops"),
+            results[name]["total_ops"],
+            "lookups of {}",
+            name
+        );
+        assert_eq!(
+            sum("success"),
+            results[name]["success"],
+            "matches of {}",
+            name
+        );
+    }
+    // blocks are not processed one by one
+    assert_eq!(
+        results["hashset-rayon-blocks"]["buckets"],
+        Value::Array(vec![])
+    );
+}
+
+#[test]
+fn pipeline_rejects_shuffle_keys() {
+    let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+        .args(["--quiet", "bench", "--iters", "1", "--shuffle-keys"])
+        .args(["--filters", "hashset-pipeline", "--input"])
+        .arg(fixture("tiny.json"))
+        .output()
+        .expect("run safb");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("can not be used with shuffle keys"));
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    for args in [
+        &["--hit-rate", "1.5"][..],
+        &["--hit-rate", "-0.1"],
+        &["--hit-rate", "NaN"],
+        &["--churn-rate", "0"],
+        &["--churn-rate", "-1"],
+        &["--churn-rate", "inf"],
+        &["--config", "bench.toml", "--set-size", "64"],
+        &["--iters", "0"],
+    ] {
+        // one iteration, unless it is the rejected option
+        let iters = match args.contains(&"--iters") {
+            true => &[][..],
+            false => &["--iters", "1"],
+        };
+        let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+            .args(["--quiet", "bench", "--filters", "hashset"])
+            .args(iters)
+            .args(args)
+            .output()
+            .expect("run safb");
+        assert!(!output.status.success(), "accepted {:?}", args);
+    }
+}
+
+#[test]
+fn merge_resolves_overlapping_slots() {
+    let dataset = load_fixture();
+    // slots 0..8 and 4..12, block of slot 5 differs in the second input
+    let conflict = 5;
+    let mut first = dataset.clone();
+    first.blocks.retain(|slot, _| *slot < 8);
+    let mut second = dataset.clone();
+    second.blocks.retain(|slot, _| *slot >= 4);
+    let block = second.blocks.get_mut(&conflict).expect("block");
+    let added = Pubkey::new_from_array([7; 32]);
+    block.pubkeys.to_mut()[0] = added;
+    let replaced = block.clone();
+    let dir = temp_dir();
+    let inputs = [
+        dir.path().join("first.json"),
+        dir.path().join("second.json"),
+    ];
+    first.save(&inputs[0]).expect("save dataset");
+    second.save(&inputs[1]).expect("save dataset");
+
+    for overlap in ["first", "last", "union"] {
+        let out = dir.path().join(format!("merged-{}.json", overlap));
+        let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+            .args(["--quiet", "merge", "--overlap", overlap, "--out"])
+            .arg(&out)
+            .arg("--input")
+            .args(&inputs)
+            .output()
+            .expect("run safb");
+        assert!(
+            output.status.success(),
+            "merge failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(
+            stdout.contains("overlapping slots: 4, conflicts: 1"),
+            "{}",
+            stdout
+        );
+
+        let merged = Dataset::load(&out).expect("load merged");
+        let mut expected = dataset.clone();
+        let block = expected.blocks.get_mut(&conflict).expect("block");
+        match overlap {
+            "first" => {}
+            "last" => *block = replaced.clone(),
+            _ => block.pubkeys.to_mut().push(added),
+        }
+        assert_eq!(merged, expected, "merge with {} overlap", overlap);
+    }
+}
+
+#[test]
+fn subsample_keeps_ratio_and_is_deterministic() {
+    let dataset = load_fixture();
+    let dir = temp_dir();
+    let subsample = |name: &str, args: &[&str]| {
+        let out = dir.path().join(format!("{}.json", name));
+        let output = Command::new(env!("CARGO_BIN_EXE_safb"))
+            .args(["--quiet", "subsample", "--input"])
+            .arg(fixture("tiny.json"))
+            .args(args)
+            .arg("--out")
+            .arg(&out)
+            .output()
+            .expect("run safb");
+        assert!(
+            output.status.success(),
+            "subsample failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let bytes = fs::read(&out).expect("read subsample");
+        let subsampled = Dataset::load(&out).expect("load subsample");
+        (bytes, subsampled)
+    };
+
+    for every in [1, 3, 5] {
+        let every_arg = every.to_string();
+        let args = ["--every", every_arg.as_str()];
+        let (bytes, subsampled) = subsample(&format!("every-{}", every), &args);
+        let (repeated, _) = subsample(&format!("every-{}-repeated", every), &args);
+        assert_eq!(bytes, repeated, "every {}", every);
+        let slots = subsampled.blocks.keys().copied().collect::<Vec<_>>();
+        let expected = dataset
+            .blocks
+            .keys()
+            .copied()
+            .step_by(every)
+            .collect::<Vec<_>>();
+        assert_eq!(slots.len(), dataset.blocks.len().div_ceil(every));
+        assert_eq!(slots, expected, "every {}", every);
+        for (slot, block) in subsampled.blocks.iter() {
+            assert_eq!(block, &dataset.blocks[slot], "block {}", slot);
+        }
+    }
+
+    let (bytes, subsampled) = subsample("unique", &["--unique-keys", "40"]);
+    let (repeated, _) = subsample("unique-repeated", &["--unique-keys", "40"]);
+    assert_eq!(bytes, repeated);
+    // consecutive blocks from the first, the last one reaches the target
+    let slots = subsampled.blocks.keys().copied().collect::<Vec<_>>();
+    let expected = dataset
+        .blocks
+        .keys()
+        .copied()
+        .take(slots.len())
+        .collect::<Vec<_>>();
+    assert_eq!(slots, expected);
+    assert!(subsampled.unique_pubkeys() >= 40);
+    let mut without_last = subsampled.clone();
+    without_last.blocks.pop_last();
+    assert!(without_last.unique_pubkeys() < 40);
+}